@@ -10,7 +10,7 @@ use std::fmt::Debug;
 #[cfg(feature = "fuzz")]
 use arbitrary::Arbitrary;
 
-use super::{calculator::{CurveCalculator, TradeDirection, SwapWithoutFeesResult}, fees::CurveFees, constant_product::ConstantProductCurve};
+use super::{calculator::{CurveCalculator, TradeDirection, SwapWithoutFeesResult}, fees::CurveFees, constant_product::ConstantProductCurve, stable::StableCurve};
 
 /// Curve types supported by the token-swap program.
 #[cfg_attr(feature = "fuzz", derive(Arbitrary))]
@@ -21,8 +21,8 @@ pub enum CurveType {
     ConstantProduct,
     // Flat line, always providing 1:1 from one token to another
     // ConstantPrice,
-    // /// Stable, like uniswap, but with wide zone of 1:1 instead of one point
-    // Stable,
+    /// Stable, like uniswap, but with wide zone of 1:1 instead of one point
+    Stable = 2,
     // /// Offset curve, like Uniswap, but the token B side has a faked offset
     // Offset,
 }
@@ -124,6 +124,54 @@ impl SwapCurve {
         )
     }
 
+    /// Get the amount of token A or B to deposit for the given amount of pool
+    /// tokens
+    pub fn deposit_single_token_type_exact_out(
+        &self,
+        pool_tokens: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        fees: &CurveFees,
+    ) -> Option<u128> {
+        if pool_tokens == 0 {
+            return Some(0);
+        }
+        let source_amount = self.calculator.deposit_single_token_type_exact_out(
+            pool_tokens,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+        )?;
+        // `deposit_single_token_type` debits the trading fee on *half* the
+        // source amount, so gross the amount up by that fee, rounding up:
+        // source * 2 * denominator / (2 * denominator - numerator)
+        let trade_fee_numerator = u128::from(fees.trade_fee_numerator);
+        if trade_fee_numerator == 0 {
+            return Some(source_amount);
+        }
+        let double_denominator = u128::from(fees.trade_fee_denominator).checked_mul(2)?;
+        let divisor = double_denominator.checked_sub(trade_fee_numerator)?;
+        let mut gross_source_amount = source_amount
+            .checked_mul(double_denominator)?
+            .checked_add(divisor.checked_sub(1)?)?
+            .checked_div(divisor)?;
+        // The minimum fee of one token and the fee's own rounding can still
+        // leave the net amount short, so top it up until it covers the source
+        loop {
+            let half_source_amount = std::cmp::max(1, gross_source_amount.checked_div(2)?);
+            let trade_fee = fees.trading_fee(half_source_amount)?;
+            let net_source_amount = gross_source_amount.checked_sub(trade_fee)?;
+            if net_source_amount >= source_amount {
+                return Some(gross_source_amount);
+            }
+            gross_source_amount = gross_source_amount
+                .checked_add(source_amount.checked_sub(net_source_amount)?)?;
+        }
+    }
+
     /// Get the amount of pool tokens for the withdrawn amount of token A or B
     pub fn withdraw_single_token_type_exact_out(
         &self,
@@ -141,8 +189,10 @@ impl SwapCurve {
         // for the other side. Reference at:
         // https://github.com/balancer-labs/balancer-core/blob/f4ed5d65362a8d6cec21662fb6eae233b0babc1f/contracts/BMath.sol#L117
         let half_source_amount = std::cmp::max(1, source_amount.checked_div(2)?);
+        // The withdrawer pays the fee, so it is added to the amount priced in
+        // pool tokens rather than taken off it
         let trade_fee = fees.trading_fee(half_source_amount)?;
-        let source_amount = source_amount.checked_sub(trade_fee)?;
+        let source_amount = source_amount.checked_add(trade_fee)?;
         self.calculator.withdraw_single_token_type_exact_out(
             source_amount,
             swap_token_a_amount,
@@ -151,6 +201,34 @@ impl SwapCurve {
             trade_direction,
         )
     }
+
+    /// Get the amount of token A or B received for the burned amount of pool
+    /// tokens
+    pub fn withdraw_single_token_type_exact_in(
+        &self,
+        pool_tokens: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        fees: &CurveFees,
+    ) -> Option<u128> {
+        if pool_tokens == 0 {
+            return Some(0);
+        }
+        let destination_amount = self.calculator.withdraw_single_token_type_exact_in(
+            pool_tokens,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+        )?;
+        // Charge the same trading fee as the exact-out withdrawal: the fee
+        // incurred if *half* the destination amount is swapped for the other side.
+        let half_destination_amount = std::cmp::max(1, destination_amount.checked_div(2)?);
+        let trade_fee = fees.trading_fee(half_destination_amount)?;
+        destination_amount.checked_sub(trade_fee)
+    }
 }
 
 /// Default implementation for SwapCurve cannot be derived because of
@@ -213,7 +291,7 @@ impl Pack for SwapCurve {
                 // CurveType::ConstantPrice => {
                 //     Box::new(ConstantPriceCurve::unpack_from_slice(calculator)?)
                 // }
-                // CurveType::Stable => Box::new(StableCurve::unpack_from_slice(calculator)?),
+                CurveType::Stable => Box::new(StableCurve::unpack_from_slice(calculator)?),
                 // CurveType::Offset => Box::new(OffsetCurve::unpack_from_slice(calculator)?),
             },
         })
@@ -243,10 +321,221 @@ impl TryFrom<u8> for CurveType {
         match curve_type {
             0 => Ok(CurveType::ConstantProduct),
             // 1 => Ok(CurveType::ConstantPrice),
-            // 2 => Ok(CurveType::Stable),
+            2 => Ok(CurveType::Stable),
             // 3 => Ok(CurveType::Offset),
             _ => Err(ProgramError::InvalidAccountData),
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOLS: &[(u128, u128, u128)] = &[
+        (1_000_000, 1_000_000, 1_000_000),
+        (50_000_000, 2_000_000, 10_000_000),
+        (1_000, 99_000_000, 3_000),
+        (123_456_789, 987_654, 11_111_111),
+    ];
+
+    fn trading_fees(trade_fee_numerator: u64, trade_fee_denominator: u64) -> CurveFees {
+        CurveFees {
+            trade_fee_numerator,
+            trade_fee_denominator,
+            ..CurveFees::default()
+        }
+    }
+
+    #[test]
+    fn deposit_single_token_type_exact_out_round_trip() {
+        let curve = SwapCurve::default();
+        for fees in [
+            trading_fees(0, 1),
+            trading_fees(25, 10_000),
+            trading_fees(1, 100),
+            trading_fees(1, 3),
+        ] {
+            for &(token_a, token_b, supply) in POOLS {
+                for pool_tokens in [1, 7, supply / 1_000 + 1, supply / 10, supply] {
+                    let source_amount = curve
+                        .deposit_single_token_type_exact_out(
+                            pool_tokens,
+                            token_a,
+                            token_b,
+                            supply,
+                            TradeDirection::AtoB,
+                            &fees,
+                        )
+                        .unwrap();
+                    // depositing the quoted amount mints at least the
+                    // requested pool tokens
+                    let minted = curve
+                        .deposit_single_token_type(
+                            source_amount,
+                            token_a,
+                            token_b,
+                            supply,
+                            TradeDirection::AtoB,
+                            &fees,
+                        )
+                        .unwrap();
+                    assert!(
+                        minted >= pool_tokens,
+                        "fees {:?} pool ({}, {}, {}) wanted {} paid {} minted {}",
+                        fees,
+                        token_a,
+                        token_b,
+                        supply,
+                        pool_tokens,
+                        source_amount,
+                        minted
+                    );
+                    // and one token less would not
+                    if source_amount > 1 {
+                        let minted = curve
+                            .deposit_single_token_type(
+                                source_amount - 1,
+                                token_a,
+                                token_b,
+                                supply,
+                                TradeDirection::AtoB,
+                                &fees,
+                            )
+                            .unwrap();
+                        assert!(minted <= pool_tokens);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn withdraw_single_token_type_exact_in_charges_fee() {
+        let curve = SwapCurve::default();
+        let fees = trading_fees(25, 10_000);
+        for &(token_a, token_b, supply) in POOLS {
+            let pool_tokens = supply / 10;
+            let without_fee = curve
+                .withdraw_single_token_type_exact_in(
+                    pool_tokens,
+                    token_a,
+                    token_b,
+                    supply,
+                    TradeDirection::BtoA,
+                    &trading_fees(0, 1),
+                )
+                .unwrap();
+            let with_fee = curve
+                .withdraw_single_token_type_exact_in(
+                    pool_tokens,
+                    token_a,
+                    token_b,
+                    supply,
+                    TradeDirection::BtoA,
+                    &fees,
+                )
+                .unwrap();
+            assert!(with_fee < without_fee);
+            // withdrawing the net amount exactly out costs no more pool tokens
+            let pool_tokens_exact_out = curve
+                .withdraw_single_token_type_exact_out(
+                    with_fee,
+                    token_a,
+                    token_b,
+                    supply,
+                    TradeDirection::BtoA,
+                    &fees,
+                )
+                .unwrap();
+            assert!(pool_tokens_exact_out <= pool_tokens);
+        }
+    }
+
+    #[test]
+    fn withdraw_single_token_type_exact_out_charges_fee() {
+        let curve = SwapCurve::default();
+        let fees = trading_fees(1, 100);
+        for &(token_a, token_b, supply) in POOLS {
+            let source_amount = token_a / 100;
+            let without_fee = curve
+                .withdraw_single_token_type_exact_out(
+                    source_amount,
+                    token_a,
+                    token_b,
+                    supply,
+                    TradeDirection::AtoB,
+                    &trading_fees(0, 1),
+                )
+                .unwrap();
+            let with_fee = curve
+                .withdraw_single_token_type_exact_out(
+                    source_amount,
+                    token_a,
+                    token_b,
+                    supply,
+                    TradeDirection::AtoB,
+                    &fees,
+                )
+                .unwrap();
+            // the fee on swapping half the amount is burned on top of it
+            let fee_only = curve
+                .withdraw_single_token_type_exact_out(
+                    source_amount + fees.trading_fee(source_amount / 2).unwrap(),
+                    token_a,
+                    token_b,
+                    supply,
+                    TradeDirection::AtoB,
+                    &trading_fees(0, 1),
+                )
+                .unwrap();
+            assert!(with_fee > without_fee);
+            assert_eq!(with_fee, fee_only);
+        }
+
+        // 10_000 out of a 1_000_000 / 1_000_000 pool is priced as 10_050:
+        // 1_000_000 * (1 - sqrt(1 - 10_050 / 1_000_000)), rounded up
+        let (token_a, token_b, supply) = POOLS[0];
+        assert_eq!(
+            curve.withdraw_single_token_type_exact_out(
+                10_000,
+                token_a,
+                token_b,
+                supply,
+                TradeDirection::AtoB,
+                &fees,
+            ),
+            Some(5_038)
+        );
+    }
+
+    #[test]
+    fn swap_owner_fee_is_minted_with_the_trading_fee() {
+        let curve = SwapCurve::default();
+        let fees = CurveFees {
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            owner_trade_fee_numerator: 5,
+            owner_trade_fee_denominator: 10_000,
+            ..CurveFees::default()
+        };
+        let (token_a, token_b, supply) = POOLS[0];
+        let result = curve
+            .swap(100_000, token_a, token_b, TradeDirection::AtoB, &fees)
+            .unwrap();
+        assert_eq!(result.owner_fee, 50);
+        let pool_tokens = curve
+            .withdraw_single_token_type_exact_out(
+                result.owner_fee,
+                result.new_swap_source_amount,
+                result.new_swap_destination_amount,
+                supply,
+                TradeDirection::AtoB,
+                &fees,
+            )
+            .unwrap();
+        // the owner fee of 50 A is priced as 51 A, with the minimum fee of one
+        // token on half of it, out of the 1_100_000 A left after the swap
+        assert_eq!(pool_tokens, 24);
+    }
+}
@@ -7,11 +7,15 @@ use arbitrary::Arbitrary;
 
 use crate::error::SwapError;
 
-/// Initial amount of pool tokens for swap contract, hard-coded to something
-/// "sensible" given a maximum of u128.
-/// Note that on Ethereum, Uniswap uses the geometric mean of all provided
-/// input amounts, and Balancer uses 100 * 10 ^ 18.
-pub const INITIAL_SWAP_POOL_AMOUNT: u128 = 1_000_000_000;
+/// Amount of pool tokens minted to the pool's own locked liquidity account on
+/// initialization and never withdrawable, as in Uniswap V2.  Keeping the pool
+/// token supply above zero prevents share inflation by donation and stops a
+/// drained pool from being re-priced by the next depositor.
+pub const MINIMUM_LIQUIDITY: u128 = 1_000;
+
+/// Largest number of decimals accepted for the pool mint, since `10 ^ 18` is the
+/// largest power of ten that fits in a u64 pool token amount.
+pub const MAX_POOL_DECIMALS: u8 = 18;
 
 /// Hardcode the number of token types in a pool, used to calculate the
 /// equivalent pool tokens for the owner trading fee.
@@ -97,10 +101,32 @@ pub trait CurveCalculator: Debug + DynPack {
         trade_direction: TradeDirection,
     ) -> Option<SwapWithoutFeesResult>;
 
-    /// Get the supply for a new pool
-    /// The default implementation is a Balancer-style fixed initial supply
-    fn new_pool_supply(&self) -> u128 {
-        INITIAL_SWAP_POOL_AMOUNT
+    /// Get the supply for a new pool, given the initial token amounts
+    /// The default implementation is the Uniswap-style geometric mean of the
+    /// initial amounts, rescaled from the trading tokens' decimals to the pool
+    /// token's decimals:
+    /// `sqrt(a * b) * 10 ^ pool_decimals / sqrt(10 ^ (a_decimals + b_decimals))`
+    fn new_pool_supply(
+        &self,
+        token_a_amount: u128,
+        token_b_amount: u128,
+        token_a_decimals: u8,
+        token_b_decimals: u8,
+        pool_decimals: u8,
+    ) -> Option<u128> {
+        let geometric_mean = PreciseNumber::new(token_a_amount)?
+            .checked_mul(&PreciseNumber::new(token_b_amount)?)?
+            .sqrt()?;
+        let token_scale = PreciseNumber::new(
+            10u128.checked_pow(u32::from(token_a_decimals) + u32::from(token_b_decimals))?,
+        )?
+        .sqrt()?;
+        let pool_scale = PreciseNumber::new(10u128.checked_pow(u32::from(pool_decimals))?)?;
+        geometric_mean
+            .checked_mul(&pool_scale)?
+            .checked_div(&token_scale)?
+            .floor()?
+            .to_imprecise()
     }
 
     /// Get the amount of trading tokens for the given amount of pool tokens,
@@ -132,6 +158,21 @@ pub trait CurveCalculator: Debug + DynPack {
         trade_direction: TradeDirection,
     ) -> Option<u128>;
 
+    /// Get the amount of token A or B to deposit for the given amount of pool
+    /// tokens.
+    ///
+    /// This is the exact-out counterpart of `deposit_single_token_type`, used
+    /// for single-sided deposits of a fixed pool token amount.  It rounds up so
+    /// that the depositor never receives more than they paid for.
+    fn deposit_single_token_type_exact_out(
+        &self,
+        pool_tokens: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
     /// Get the amount of pool tokens for the withdrawn amount of token A or B.
     ///
     /// This is used for single-sided withdrawals and owner trade fee
@@ -151,6 +192,23 @@ pub trait CurveCalculator: Debug + DynPack {
         trade_direction: TradeDirection,
     ) -> Option<u128>;
 
+    /// Get the amount of token A or B received for the burned amount of pool
+    /// tokens.
+    ///
+    /// This is the exact-in counterpart of
+    /// `withdraw_single_token_type_exact_out`, used for single-sided
+    /// withdrawals of a fixed pool token amount.  It essentially performs a
+    /// withdrawal followed by a swap, so it will change the spot price of the
+    /// pool.
+    fn withdraw_single_token_type_exact_in(
+        &self,
+        pool_tokens: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
     /// Validate that the given curve has no invalid parameters
     fn validate(&self) -> Result<(), SwapError>;
 
@@ -11,7 +11,7 @@ use {
         program_error::ProgramError,
         program_pack::{IsInitialized, Pack, Sealed},
     },
-    spl_math::{checked_ceil_div::CheckedCeilDiv, precise_number::PreciseNumber},
+    spl_math::{checked_ceil_div::CheckedCeilDiv, precise_number::PreciseNumber, uint::U256},
 };
 
 /// ConstantProductCurve struct implementing CurveCalculator
@@ -122,6 +122,40 @@ pub fn deposit_single_token_type(
     }
 }
 
+/// Get the amount of token A or B to deposit for the given amount of pool tokens.
+///
+/// This inverts `deposit_single_token_type`, giving
+/// `R * ((1 + pool_tokens / pool_supply) ^ 2 - 1)` for a pool balance `R` of the
+/// deposited token.  Like `withdraw_single_token_type_exact_in`, it is evaluated
+/// as `R * pool_tokens * (2 * pool_supply + pool_tokens) / pool_supply ^ 2` in
+/// integers.
+pub fn deposit_single_token_type_exact_out(
+    pool_tokens: u128,
+    swap_token_a_amount: u128,
+    swap_token_b_amount: u128,
+    pool_supply: u128,
+    trade_direction: TradeDirection,
+    round_direction: RoundDirection,
+) -> Option<u128> {
+    let swap_source_amount = match trade_direction {
+        TradeDirection::AtoB => swap_token_a_amount,
+        TradeDirection::BtoA => swap_token_b_amount,
+    };
+    let pool_supply = U256::from(pool_supply);
+    let pool_tokens = U256::from(pool_tokens);
+    let numerator = U256::from(swap_source_amount)
+        .checked_mul(pool_tokens)?
+        .checked_mul(pool_supply.checked_add(pool_supply)?.checked_add(pool_tokens)?)?;
+    let denominator = pool_supply.checked_mul(pool_supply)?;
+    let source_amount = match round_direction {
+        RoundDirection::Floor => numerator.checked_div(denominator)?,
+        RoundDirection::Ceiling => numerator
+            .checked_add(denominator.checked_sub(U256::one())?)?
+            .checked_div(denominator)?,
+    };
+    u128::try_from(source_amount).ok()
+}
+
 /// Get the amount of pool tokens for the withdrawn amount of token A or B.
 ///
 /// The constant product implementation uses the Balancer formulas found at
@@ -153,6 +187,79 @@ pub fn withdraw_single_token_type_exact_out(
     }
 }
 
+/// Get the amount of token A or B received for the burned amount of pool tokens.
+///
+/// This inverts `withdraw_single_token_type_exact_out`, giving
+/// `R * (1 - (1 - pool_tokens / pool_supply) ^ 2)` for a pool balance `R` of the
+/// withdrawn token.  It is evaluated as
+/// `R * pool_tokens * (2 * pool_supply - pool_tokens) / pool_supply ^ 2` in
+/// integers, since squaring the ratio at `PreciseNumber` precision can round
+/// the result up by a token for small burns.
+pub fn withdraw_single_token_type_exact_in(
+    pool_tokens: u128,
+    swap_token_a_amount: u128,
+    swap_token_b_amount: u128,
+    pool_supply: u128,
+    trade_direction: TradeDirection,
+    round_direction: RoundDirection,
+) -> Option<u128> {
+    let swap_destination_amount = match trade_direction {
+        TradeDirection::AtoB => swap_token_a_amount,
+        TradeDirection::BtoA => swap_token_b_amount,
+    };
+    let pool_supply = U256::from(pool_supply);
+    let pool_tokens = U256::from(pool_tokens);
+    let remaining_supply = pool_supply.checked_sub(pool_tokens)?;
+    let numerator = U256::from(swap_destination_amount)
+        .checked_mul(pool_tokens)?
+        .checked_mul(pool_supply.checked_add(remaining_supply)?)?;
+    let denominator = pool_supply.checked_mul(pool_supply)?;
+    let destination_amount = match round_direction {
+        RoundDirection::Floor => numerator.checked_div(denominator)?,
+        RoundDirection::Ceiling => numerator
+            .checked_add(denominator.checked_sub(U256::one())?)?
+            .checked_div(denominator)?,
+    };
+    u128::try_from(destination_amount).ok()
+}
+
+/// Get the amount of source token to swap before depositing both sides, so that
+/// the swapped output and the remaining source token match the pool ratio.
+///
+/// With `a` the source amount, `R` the pool's source token balance and `f` the
+/// fraction of the swapped amount taken as fees, the swap amount `s` solves
+/// `(1 - f) * s^2 + (2 - f) * R * s - a * R = 0`, rearranged as
+/// `s = 2a / ((2 - f) + sqrt((2 - f)^2 + 4 * (1 - f) * a / R))` to keep the
+/// square root within range.
+pub fn zap_in_swap_amount(
+    source_amount: u128,
+    swap_source_amount: u128,
+    fee_numerator: u128,
+    fee_denominator: u128,
+) -> Option<u128> {
+    let one = PreciseNumber::new(1)?;
+    let two = PreciseNumber::new(2)?;
+    let four = PreciseNumber::new(4)?;
+    let fee = if fee_numerator == 0 {
+        PreciseNumber::new(0)?
+    } else {
+        PreciseNumber::new(fee_numerator)?.checked_div(&PreciseNumber::new(fee_denominator)?)?
+    };
+    let one_minus_fee = one.checked_sub(&fee)?;
+    let two_minus_fee = two.checked_sub(&fee)?;
+    let source_amount = PreciseNumber::new(source_amount)?;
+    let swap_source_amount = PreciseNumber::new(swap_source_amount)?;
+    let ratio = source_amount.checked_div(&swap_source_amount)?;
+    let root = two_minus_fee
+        .checked_mul(&two_minus_fee)?
+        .checked_add(&four.checked_mul(&one_minus_fee)?.checked_mul(&ratio)?)?
+        .sqrt()?;
+    let swap_amount = two
+        .checked_mul(&source_amount)?
+        .checked_div(&two_minus_fee.checked_add(&root)?)?;
+    swap_amount.floor()?.to_imprecise()
+}
+
 /// Calculates the total normalized value of the curve given the liquidity
 /// parameters.
 ///
@@ -219,6 +326,24 @@ impl CurveCalculator for ConstantProductCurve {
         )
     }
 
+    fn deposit_single_token_type_exact_out(
+        &self,
+        pool_tokens: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        deposit_single_token_type_exact_out(
+            pool_tokens,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+            RoundDirection::Ceiling,
+        )
+    }
+
     fn withdraw_single_token_type_exact_out(
         &self,
         source_amount: u128,
@@ -237,6 +362,24 @@ impl CurveCalculator for ConstantProductCurve {
         )
     }
 
+    fn withdraw_single_token_type_exact_in(
+        &self,
+        pool_tokens: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        withdraw_single_token_type_exact_in(
+            pool_tokens,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+            RoundDirection::Floor,
+        )
+    }
+
     fn normalized_value(
         &self,
         swap_token_a_amount: u128,
@@ -271,3 +414,217 @@ impl Pack for ConstantProductCurve {
 impl DynPack for ConstantProductCurve {
     fn pack_into_slice(&self, _output: &mut [u8]) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{base::SwapCurve, calculator::MAX_POOL_DECIMALS, fees::CurveFees};
+
+    fn constant_product_curve() -> SwapCurve {
+        SwapCurve {
+            curve_type: crate::curve::base::CurveType::ConstantProduct,
+            calculator: Box::new(ConstantProductCurve),
+        }
+    }
+
+    fn trading_fees(trade_fee_numerator: u64, trade_fee_denominator: u64) -> CurveFees {
+        CurveFees {
+            trade_fee_numerator,
+            trade_fee_denominator,
+            ..CurveFees::default()
+        }
+    }
+
+    /// Checks that the value of one pool token, sqrt(a * b) / supply, did not
+    /// go down between the two states
+    fn assert_share_value_not_decreased(
+        (token_a_before, token_b_before, supply_before): (u128, u128, u128),
+        (token_a_after, token_b_after, supply_after): (u128, u128, u128),
+    ) {
+        let value_before = U256::from(token_a_before)
+            * U256::from(token_b_before)
+            * U256::from(supply_after)
+            * U256::from(supply_after);
+        let value_after = U256::from(token_a_after)
+            * U256::from(token_b_after)
+            * U256::from(supply_before)
+            * U256::from(supply_before);
+        assert!(
+            value_after >= value_before,
+            "before ({}, {}, {}) after ({}, {}, {})",
+            token_a_before,
+            token_b_before,
+            supply_before,
+            token_a_after,
+            token_b_after,
+            supply_after
+        );
+    }
+
+    const POOLS: &[(u128, u128, u128)] = &[
+        (1_000_000, 1_000_000, 1_000_000),
+        (50_000_000, 2_000_000, 10_000_000),
+        (1_000, 99_000_000, 3_000),
+        (123_456_789, 987_654, 11_111_111),
+    ];
+
+    #[test]
+    fn zap_in_swap_amount_without_fees() {
+        // with no fee the optimal swap is R * (sqrt(1 + x / R) - 1)
+        assert_eq!(zap_in_swap_amount(3_000, 1_000, 0, 1), Some(1_000));
+        assert_eq!(zap_in_swap_amount(8_000, 1_000, 0, 1), Some(2_000));
+        assert_eq!(zap_in_swap_amount(0, 1_000, 0, 1), Some(0));
+        assert_eq!(zap_in_swap_amount(1_000, 0, 0, 1), None);
+    }
+
+    #[test]
+    fn zap_in_swap_amount_leaves_pool_ratio() {
+        let tests: &[(u128, u128, u128, u64, u64)] = &[
+            (1_000_000, 1_000_000, 1_000_000, 25, 10_000),
+            (1_000_000, 50_000_000, 2_000_000, 30, 10_000),
+            (10_000_000, 1_000_000, 1_000_000, 25, 10_000),
+            (5_000, 1_000_000_000, 3_000_000_000, 1, 100),
+            (123_456_789, 987_654_321, 555_555_555, 0, 1),
+        ];
+        let curve = constant_product_curve();
+        for &(source_amount, swap_source_amount, swap_destination_amount, num, den) in tests {
+            let fees = trading_fees(num, den);
+            let (fee_numerator, fee_denominator) = fees.total_trading_fee_fraction().unwrap();
+            let swap_amount = zap_in_swap_amount(
+                source_amount,
+                swap_source_amount,
+                fee_numerator,
+                fee_denominator,
+            )
+            .unwrap();
+            assert!(swap_amount < source_amount);
+            let result = curve
+                .swap(
+                    swap_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    TradeDirection::AtoB,
+                    &fees,
+                )
+                .unwrap();
+            // what is left of the source and what the swap returned are in
+            // the ratio of the pool after the swap, up to a unit of rounding
+            // on either side and a basis point for the approximate square root
+            let remaining_source_amount = source_amount - result.source_amount_swapped;
+            let left = remaining_source_amount * result.new_swap_destination_amount;
+            let right = result.destination_amount_swapped * result.new_swap_source_amount;
+            let difference = left.max(right) - left.min(right);
+            let tolerance = 2 * (result.new_swap_source_amount + result.new_swap_destination_amount)
+                + left.max(right) / 10_000;
+            assert!(
+                difference <= tolerance,
+                "source {} swapped {} left {} right {}",
+                source_amount,
+                swap_amount,
+                left,
+                right
+            );
+        }
+    }
+
+    #[test]
+    fn withdraw_single_token_type_exact_in_rounding() {
+        for &(token_a, token_b, supply) in POOLS {
+            for pool_tokens in [1, supply / 1_000 + 1, supply / 10, supply / 2, supply - 1] {
+                let floor = withdraw_single_token_type_exact_in(
+                    pool_tokens,
+                    token_a,
+                    token_b,
+                    supply,
+                    TradeDirection::AtoB,
+                    RoundDirection::Floor,
+                )
+                .unwrap();
+                let ceiling = withdraw_single_token_type_exact_in(
+                    pool_tokens,
+                    token_a,
+                    token_b,
+                    supply,
+                    TradeDirection::AtoB,
+                    RoundDirection::Ceiling,
+                )
+                .unwrap();
+                assert!(floor <= ceiling && ceiling - floor <= 1);
+                assert!(floor < token_a);
+            }
+        }
+    }
+
+    #[test]
+    fn withdraw_single_token_type_exact_in_round_trip() {
+        let curve = ConstantProductCurve;
+        for &(token_a, token_b, supply) in POOLS {
+            for pool_tokens in [1, supply / 1_000 + 1, supply / 10, supply / 2, supply - 1] {
+                let destination_amount = curve
+                    .withdraw_single_token_type_exact_in(
+                        pool_tokens,
+                        token_a,
+                        token_b,
+                        supply,
+                        TradeDirection::AtoB,
+                    )
+                    .unwrap();
+                if destination_amount == 0 {
+                    continue;
+                }
+                // withdrawing the same amount exactly out never costs more
+                // pool tokens than were burned for it
+                let pool_tokens_exact_out = curve
+                    .withdraw_single_token_type_exact_out(
+                        destination_amount,
+                        token_a,
+                        token_b,
+                        supply,
+                        TradeDirection::AtoB,
+                    )
+                    .unwrap();
+                assert!(pool_tokens_exact_out <= pool_tokens);
+                assert_share_value_not_decreased(
+                    (token_a, token_b, supply),
+                    (token_a - destination_amount, token_b, supply - pool_tokens),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn new_pool_supply_is_geometric_mean() {
+        let curve = ConstantProductCurve;
+        // same decimals for the tokens and the pool: plain geometric mean
+        assert_eq!(curve.new_pool_supply(1_000_000, 1_000_000, 6, 6, 6), Some(1_000_000));
+        assert_eq!(curve.new_pool_supply(100, 10_000, 2, 2, 2), Some(1_000));
+        assert_eq!(curve.new_pool_supply(2, 3, 0, 0, 0), Some(2));
+        // independent of how the deposit is split, for the same product
+        assert_eq!(
+            curve.new_pool_supply(4_000_000, 250_000, 6, 6, 6),
+            curve.new_pool_supply(1_000_000, 1_000_000, 6, 6, 6)
+        );
+    }
+
+    #[test]
+    fn new_pool_supply_scales_decimals() {
+        let curve = ConstantProductCurve;
+        // 1 token of 9 decimals and 1 token of 6 decimals mint 1 pool token
+        assert_eq!(
+            curve.new_pool_supply(1_000_000_000, 1_000_000, 9, 6, 6),
+            Some(1_000_000)
+        );
+        assert_eq!(
+            curve.new_pool_supply(1_000_000_000, 1_000_000, 9, 6, 9),
+            Some(1_000_000_000)
+        );
+        // sub-unit results round down
+        assert_eq!(curve.new_pool_supply(10, 10, 2, 2, 0), Some(0));
+        // the largest inputs still compute, and are left to the u64
+        // conversion when minting
+        let supply = curve
+            .new_pool_supply(u128::from(u64::MAX), u128::from(u64::MAX), 0, 0, MAX_POOL_DECIMALS)
+            .unwrap();
+        assert!(u64::try_from(supply).is_err());
+    }
+}
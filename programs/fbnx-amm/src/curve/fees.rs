@@ -110,6 +110,30 @@ impl CurveFees {
         )
     }
 
+    /// Combined trade and owner trading fee, as a single fraction of the
+    /// trading tokens, returned as `(numerator, denominator)`
+    pub fn total_trading_fee_fraction(&self) -> Option<(u128, u128)> {
+        let trade_fee_numerator = u128::from(self.trade_fee_numerator);
+        let trade_fee_denominator = u128::from(self.trade_fee_denominator);
+        let owner_trade_fee_numerator = u128::from(self.owner_trade_fee_numerator);
+        let owner_trade_fee_denominator = u128::from(self.owner_trade_fee_denominator);
+        if owner_trade_fee_numerator == 0 {
+            if trade_fee_numerator == 0 {
+                return Some((0, 1));
+            }
+            return Some((trade_fee_numerator, trade_fee_denominator));
+        }
+        if trade_fee_numerator == 0 {
+            return Some((owner_trade_fee_numerator, owner_trade_fee_denominator));
+        }
+        Some((
+            trade_fee_numerator
+                .checked_mul(owner_trade_fee_denominator)?
+                .checked_add(owner_trade_fee_numerator.checked_mul(trade_fee_denominator)?)?,
+            trade_fee_denominator.checked_mul(owner_trade_fee_denominator)?,
+        ))
+    }
+
     /// Validate that the fees are reasonable
     pub fn validate(&self) -> std::result::Result<(), SwapError> {
         validate_fraction(self.trade_fee_numerator, self.trade_fee_denominator)?;
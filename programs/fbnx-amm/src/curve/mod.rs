@@ -3,4 +3,6 @@
 pub mod base;
 pub mod calculator;
 pub mod constant_product;
-pub mod fees;
\ No newline at end of file
+pub mod fees;
+pub mod stable;
+pub mod stable_swap;
\ No newline at end of file
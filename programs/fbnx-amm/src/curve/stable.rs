@@ -0,0 +1,393 @@
+//! Two-token StableSwap curve, for moving a pool of pegged tokens onto a
+//! flatter curve with `migrate_pool`
+
+use crate::error::SwapError;
+
+use {
+    crate::curve::{
+        calculator::{
+            CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult, TradeDirection,
+            TradingTokenResult,
+        },
+        constant_product::pool_tokens_to_trading_tokens,
+        stable_swap::{self, MAX_AMP},
+    },
+    arrayref::{array_mut_ref, array_ref},
+    solana_program::{
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+    },
+    spl_math::{precise_number::PreciseNumber, uint::U256},
+};
+
+/// StableCurve struct implementing CurveCalculator
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StableCurve {
+    /// Amplification coefficient (A)
+    pub amp: u64,
+}
+
+/// Order the pool's balances so that the traded token comes first
+fn balances(
+    swap_token_a_amount: u128,
+    swap_token_b_amount: u128,
+    trade_direction: TradeDirection,
+) -> [u128; 2] {
+    match trade_direction {
+        TradeDirection::AtoB => [swap_token_a_amount, swap_token_b_amount],
+        TradeDirection::BtoA => [swap_token_b_amount, swap_token_a_amount],
+    }
+}
+
+/// `x * numerator / denominator` without intermediate overflow, rounded in
+/// the given direction
+fn mul_div(
+    x: u128,
+    numerator: u128,
+    denominator: u128,
+    round_direction: RoundDirection,
+) -> Option<u128> {
+    let product = U256::from(x).checked_mul(U256::from(numerator))?;
+    let denominator = U256::from(denominator);
+    let result = match round_direction {
+        RoundDirection::Floor => product.checked_div(denominator)?,
+        RoundDirection::Ceiling => product
+            .checked_add(denominator.checked_sub(U256::one())?)?
+            .checked_div(denominator)?,
+    };
+    u128::try_from(result).ok()
+}
+
+impl CurveCalculator for StableCurve {
+    /// Stable curve swap keeps the StableSwap invariant `D` constant
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<SwapWithoutFeesResult> {
+        let destination_amount_swapped = stable_swap::swap_without_fees(
+            self.amp,
+            &[swap_source_amount, swap_destination_amount],
+            0,
+            1,
+            source_amount,
+        )?;
+        if destination_amount_swapped == 0 {
+            return None;
+        }
+        Some(SwapWithoutFeesResult {
+            source_amount_swapped: source_amount,
+            destination_amount_swapped,
+        })
+    }
+
+    /// Two-sided deposits and withdrawals keep the pool's ratio, so they are
+    /// the same simple ratio calculation as for the constant product curve
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            round_direction,
+        )
+    }
+
+    /// Get the amount of pool tokens for the deposited amount of token A or B,
+    /// as the deposit's share of the growth of `D`.  The trading fee has
+    /// already been taken by `SwapCurve`, so no imbalance fee is charged here.
+    fn deposit_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        stable_swap::deposit(
+            self.amp,
+            &balances(swap_token_a_amount, swap_token_b_amount, trade_direction),
+            &[source_amount, 0],
+            pool_supply,
+            0,
+            1,
+        )
+    }
+
+    /// Inverts `deposit_single_token_type`: grow `D` by the pool tokens' share,
+    /// rounded up, and solve for the deposited token's new balance.  One more
+    /// token covers the rounding of the iterative solution.
+    fn deposit_single_token_type_exact_out(
+        &self,
+        pool_tokens: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let balances = balances(swap_token_a_amount, swap_token_b_amount, trade_direction);
+        let d0 = stable_swap::compute_d(self.amp, &balances)?;
+        let d_added = mul_div(pool_tokens, d0, pool_supply, RoundDirection::Ceiling)?;
+        let d1 = d0.checked_add(d_added)?;
+        let new_balance = stable_swap::compute_y(self.amp, &balances, 0, d1)?;
+        new_balance.checked_sub(balances[0])?.checked_add(1)
+    }
+
+    /// Get the pool tokens to burn for the withdrawn amount of token A or B, as
+    /// the withdrawal's share of the loss of `D`, rounded up
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let balances = balances(swap_token_a_amount, swap_token_b_amount, trade_direction);
+        let d0 = stable_swap::compute_d(self.amp, &balances)?;
+        let d1 = stable_swap::compute_d(
+            self.amp,
+            &[balances[0].checked_sub(source_amount)?, balances[1]],
+        )?;
+        // `compute_d` converges to within one, so charge for one more unit of
+        // `D` lost than measured
+        let d_removed = d0.saturating_sub(d1).checked_add(1)?;
+        mul_div(d_removed, pool_supply, d0, RoundDirection::Ceiling)
+    }
+
+    fn withdraw_single_token_type_exact_in(
+        &self,
+        pool_tokens: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        stable_swap::withdraw_one(
+            self.amp,
+            &balances(swap_token_a_amount, swap_token_b_amount, trade_direction),
+            pool_tokens,
+            pool_supply,
+            0,
+            0,
+            1,
+        )
+    }
+
+    /// Half of the invariant `D`, which is the sum of the balances when the
+    /// pool is balanced, so that a balanced pool is valued the same as under
+    /// the constant product curve
+    fn normalized_value(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+    ) -> Option<PreciseNumber> {
+        let d = stable_swap::compute_d(self.amp, &[swap_token_a_amount, swap_token_b_amount])?;
+        PreciseNumber::new(d)?.checked_div(&PreciseNumber::new(2)?)
+    }
+
+    fn validate(&self) -> Result<(), SwapError> {
+        if self.amp == 0 || self.amp > MAX_AMP {
+            Err(SwapError::InvalidCurve)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// IsInitialized is required to use `Pack::pack` and `Pack::unpack`
+impl IsInitialized for StableCurve {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+impl Sealed for StableCurve {}
+impl Pack for StableCurve {
+    const LEN: usize = 8;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        (self as &dyn DynPack).pack_into_slice(output);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<StableCurve, ProgramError> {
+        let amp = array_ref![input, 0, 8];
+        Ok(Self {
+            amp: u64::from_le_bytes(*amp),
+        })
+    }
+}
+
+impl DynPack for StableCurve {
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let amp = array_mut_ref![output, 0, 8];
+        *amp = self.amp.to_le_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::base::{CurveType, SwapCurve};
+
+    const AMPS: &[u64] = &[1, 100, 10_000];
+
+    const POOLS: &[(u128, u128, u128)] = &[
+        (1_000_000, 1_000_000, 1_000_000),
+        (50_000_000, 20_000_000, 10_000_000),
+        (1_000_000_000, 3_000_000_000, 2_000_000_000),
+        (123_456_789, 98_765_432, 11_111_111),
+    ];
+
+    /// Asserts that the invariant per pool token did not go down from `before`
+    /// to `after`, both given as `(token_a, token_b, supply)`
+    fn assert_share_value_not_decreased(
+        amp: u64,
+        before: (u128, u128, u128),
+        after: (u128, u128, u128),
+    ) {
+        let d_before = stable_swap::compute_d(amp, &[before.0, before.1]).unwrap();
+        let d_after = stable_swap::compute_d(amp, &[after.0, after.1]).unwrap();
+        assert!(
+            U256::from(d_after) * U256::from(before.2)
+                >= U256::from(d_before) * U256::from(after.2),
+            "share value decreased from {:?} to {:?}",
+            before,
+            after
+        );
+    }
+
+    #[test]
+    fn pack_curve() {
+        let curve = SwapCurve {
+            curve_type: CurveType::Stable,
+            calculator: Box::new(StableCurve { amp: 85 }),
+        };
+        let mut packed = [0u8; SwapCurve::LEN];
+        Pack::pack_into_slice(&curve, &mut packed);
+        assert_eq!(packed[0], 2);
+        assert_eq!(SwapCurve::unpack_from_slice(&packed).unwrap(), curve);
+    }
+
+    #[test]
+    fn validate_amp() {
+        assert!(StableCurve { amp: 0 }.validate().is_err());
+        assert!(StableCurve { amp: MAX_AMP + 1 }.validate().is_err());
+        assert!(StableCurve { amp: 1 }.validate().is_ok());
+        assert!(StableCurve { amp: MAX_AMP }.validate().is_ok());
+    }
+
+    #[test]
+    fn swap_near_peg() {
+        let curve = StableCurve { amp: 100 };
+        let result = curve
+            .swap_without_fees(1_000_000, 1_000_000_000, 1_000_000_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 1_000_000);
+        assert!(result.destination_amount_swapped < 1_000_000);
+        assert!(result.destination_amount_swapped > 999_000);
+    }
+
+    #[test]
+    fn normalized_value_of_balanced_pool() {
+        for amp in AMPS {
+            let value = StableCurve { amp: *amp }
+                .normalized_value(1_000_000, 1_000_000)
+                .unwrap();
+            assert_eq!(value.to_imprecise().unwrap(), 1_000_000);
+        }
+    }
+
+    #[test]
+    fn deposit_single_token_type_exact_out_round_trip() {
+        for amp in AMPS {
+            let curve = StableCurve { amp: *amp };
+            for (token_a, token_b, supply) in POOLS {
+                for pool_tokens in [1, 7, supply / 1_000, supply / 10, *supply] {
+                    for direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                        let source = curve
+                            .deposit_single_token_type_exact_out(
+                                pool_tokens, *token_a, *token_b, *supply, direction,
+                            )
+                            .unwrap();
+                        let minted = curve
+                            .deposit_single_token_type(
+                                source, *token_a, *token_b, *supply, direction,
+                            )
+                            .unwrap();
+                        assert!(
+                            minted >= pool_tokens,
+                            "amp {} pool {:?}: {} source minted {} < {}",
+                            amp,
+                            (token_a, token_b, supply),
+                            source,
+                            minted,
+                            pool_tokens
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn deposit_single_token_type_keeps_share_value() {
+        for amp in AMPS {
+            let curve = StableCurve { amp: *amp };
+            for (token_a, token_b, supply) in POOLS {
+                for source in [1, 13, token_a / 1_000, *token_a] {
+                    let minted = curve
+                        .deposit_single_token_type(
+                            source, *token_a, *token_b, *supply, TradeDirection::AtoB,
+                        )
+                        .unwrap();
+                    assert_share_value_not_decreased(
+                        *amp,
+                        (*token_a, *token_b, *supply),
+                        (token_a + source, *token_b, supply + minted),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn withdraw_single_token_type_keeps_share_value() {
+        for amp in AMPS {
+            let curve = StableCurve { amp: *amp };
+            for (token_a, token_b, supply) in POOLS {
+                for amount in [1, 13, token_b / 1_000, token_b / 2] {
+                    let burned = curve
+                        .withdraw_single_token_type_exact_out(
+                            amount, *token_a, *token_b, *supply, TradeDirection::BtoA,
+                        )
+                        .unwrap();
+                    assert_share_value_not_decreased(
+                        *amp,
+                        (*token_a, *token_b, *supply),
+                        (*token_a, token_b - amount, supply - burned),
+                    );
+                }
+                for pool_tokens in [1, 13, supply / 1_000, supply / 2] {
+                    let withdrawn = curve
+                        .withdraw_single_token_type_exact_in(
+                            pool_tokens, *token_a, *token_b, *supply, TradeDirection::BtoA,
+                        )
+                        .unwrap_or(0);
+                    assert_share_value_not_decreased(
+                        *amp,
+                        (*token_a, *token_b, *supply),
+                        (*token_a, token_b - withdrawn, supply - pool_tokens),
+                    );
+                }
+            }
+        }
+    }
+}
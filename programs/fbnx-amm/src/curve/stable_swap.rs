@@ -0,0 +1,414 @@
+//! StableSwap invariant for pools holding two or more tokens
+//!
+//! Implements `A * n^n * sum(x) + D = A * D * n^n + D^(n+1) / (n^n * prod(x))`
+//! as described in <https://curve.fi/files/stableswap-paper.pdf>, using the
+//! iterations of Curve's reference implementation.  All balances are expected
+//! to be normalized to a common precision.
+
+use spl_math::uint::U256;
+
+/// Maximum number of tokens in a multi-asset pool
+pub const MAX_TOKENS_IN_MULTI_POOL: usize = 8;
+
+/// Maximum amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Newton iterations before giving up on convergence
+const MAX_ITERATIONS: u16 = 256;
+
+fn converged(a: U256, b: U256) -> bool {
+    if a > b {
+        a - b <= U256::one()
+    } else {
+        b - a <= U256::one()
+    }
+}
+
+/// Compute the invariant `D` of the given balances
+pub fn compute_d(amp: u64, balances: &[u128]) -> Option<u128> {
+    let n = U256::from(balances.len());
+    let sum = balances
+        .iter()
+        .try_fold(U256::zero(), |sum, x| sum.checked_add(U256::from(*x)))?;
+    if sum.is_zero() {
+        return Some(0);
+    }
+    let ann = U256::from(amp).checked_mul(n)?;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for x in balances {
+            let denominator = U256::from(*x).checked_mul(n)?;
+            if denominator.is_zero() {
+                return None;
+            }
+            d_p = d_p.checked_mul(d)?.checked_div(denominator)?;
+        }
+        let d_prev = d;
+        let numerator = ann
+            .checked_mul(sum)?
+            .checked_add(d_p.checked_mul(n)?)?
+            .checked_mul(d)?;
+        let denominator = ann
+            .checked_sub(U256::one())?
+            .checked_mul(d)?
+            .checked_add(n.checked_add(U256::one())?.checked_mul(d_p)?)?;
+        d = numerator.checked_div(denominator)?;
+        if converged(d, d_prev) {
+            return u128::try_from(d).ok();
+        }
+    }
+    None
+}
+
+/// Compute the balance of the token at `index` which keeps the invariant at
+/// `d`, given the other balances.  The balance at `index` is ignored.
+pub fn compute_y(amp: u64, balances: &[u128], index: usize, d: u128) -> Option<u128> {
+    let n = U256::from(balances.len());
+    let ann = U256::from(amp).checked_mul(n)?;
+    let d = U256::from(d);
+    let mut c = d;
+    let mut sum = U256::zero();
+    for (k, x) in balances.iter().enumerate() {
+        if k == index {
+            continue;
+        }
+        let x = U256::from(*x);
+        sum = sum.checked_add(x)?;
+        let denominator = x.checked_mul(n)?;
+        if denominator.is_zero() {
+            return None;
+        }
+        c = c.checked_mul(d)?.checked_div(denominator)?;
+    }
+    c = c.checked_mul(d)?.checked_div(ann.checked_mul(n)?)?;
+    let b = sum.checked_add(d.checked_div(ann)?)?;
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let numerator = y.checked_mul(y)?.checked_add(c)?;
+        let denominator = y
+            .checked_mul(U256::from(2))?
+            .checked_add(b)?
+            .checked_sub(d)?;
+        y = numerator.checked_div(denominator)?;
+        if converged(y, y_prev) {
+            return u128::try_from(y).ok();
+        }
+    }
+    None
+}
+
+/// Fee charged on the imbalance of a deposit or single sided withdrawal, as
+/// the share `n / (4 * (n - 1))` of the trading fee that a swap restoring the
+/// balance would pay
+fn imbalance_fee(
+    amount: u128,
+    token_count: usize,
+    fee_numerator: u128,
+    fee_denominator: u128,
+) -> Option<u128> {
+    if fee_numerator == 0 || amount == 0 {
+        return Some(0);
+    }
+    let n = token_count as u128;
+    amount
+        .checked_mul(fee_numerator)?
+        .checked_mul(n)?
+        .checked_div(fee_denominator.checked_mul(4)?.checked_mul(n.checked_sub(1)?)?)
+}
+
+/// Get the amount of token `j` received for `source_amount` of token `i`,
+/// after fees have been taken from the source amount
+pub fn swap_without_fees(
+    amp: u64,
+    balances: &[u128],
+    i: usize,
+    j: usize,
+    source_amount: u128,
+) -> Option<u128> {
+    let d = compute_d(amp, balances)?;
+    let mut new_balances = balances.to_vec();
+    new_balances[i] = new_balances[i].checked_add(source_amount)?;
+    let y = compute_y(amp, &new_balances, j, d)?;
+    // round against the user
+    balances[j].checked_sub(y)?.checked_sub(1)
+}
+
+/// Get the amount of pool tokens for depositing `amounts` of each token.
+/// Deposits away from the pool's balance pay the imbalance fee, which stays in
+/// the pool.
+pub fn deposit(
+    amp: u64,
+    balances: &[u128],
+    amounts: &[u128],
+    pool_supply: u128,
+    fee_numerator: u128,
+    fee_denominator: u128,
+) -> Option<u128> {
+    let d0 = compute_d(amp, balances)?;
+    let new_balances = balances
+        .iter()
+        .zip(amounts)
+        .map(|(x, amount)| x.checked_add(*amount))
+        .collect::<Option<Vec<u128>>>()?;
+    let d1 = compute_d(amp, &new_balances)?;
+    if d1 <= d0 {
+        return None;
+    }
+    let mut fee_balances = new_balances.clone();
+    for (k, x) in balances.iter().enumerate() {
+        let ideal_balance = U256::from(d1)
+            .checked_mul(U256::from(*x))?
+            .checked_div(U256::from(d0))?;
+        let ideal_balance = u128::try_from(ideal_balance).ok()?;
+        let difference = ideal_balance.abs_diff(new_balances[k]);
+        let fee = imbalance_fee(difference, balances.len(), fee_numerator, fee_denominator)?;
+        fee_balances[k] = fee_balances[k].checked_sub(fee)?;
+    }
+    let d2 = compute_d(amp, &fee_balances)?;
+    let pool_tokens = U256::from(pool_supply)
+        .checked_mul(U256::from(d2.checked_sub(d0)?))?
+        .checked_div(U256::from(d0))?;
+    u128::try_from(pool_tokens).ok()
+}
+
+/// Get the amount of token `index` received for burning `pool_tokens`, after
+/// the imbalance fee
+pub fn withdraw_one(
+    amp: u64,
+    balances: &[u128],
+    pool_tokens: u128,
+    pool_supply: u128,
+    index: usize,
+    fee_numerator: u128,
+    fee_denominator: u128,
+) -> Option<u128> {
+    let d0 = compute_d(amp, balances)?;
+    let removed = U256::from(pool_tokens)
+        .checked_mul(U256::from(d0))?
+        .checked_div(U256::from(pool_supply))?;
+    let d1 = d0.checked_sub(u128::try_from(removed).ok()?)?;
+    let new_y = compute_y(amp, balances, index, d1)?;
+
+    let mut reduced_balances = balances.to_vec();
+    for (k, x) in balances.iter().enumerate() {
+        let scaled_balance = U256::from(*x)
+            .checked_mul(U256::from(d1))?
+            .checked_div(U256::from(d0))?;
+        let scaled_balance = u128::try_from(scaled_balance).ok()?;
+        let expected_change = if k == index {
+            scaled_balance.checked_sub(new_y)?
+        } else {
+            x.checked_sub(scaled_balance)?
+        };
+        let fee = imbalance_fee(expected_change, balances.len(), fee_numerator, fee_denominator)?;
+        reduced_balances[k] = reduced_balances[k].checked_sub(fee)?;
+    }
+    let y = compute_y(amp, &reduced_balances, index, d1)?;
+    // round against the user
+    reduced_balances[index].checked_sub(y)?.checked_sub(1)
+}
+
+/// Get the amount of pool tokens to mint for `fee_amount` of token `index`
+/// kept in the pool, so that their holder owns exactly that amount's share of
+/// the invariant.  Generalises `withdraw_single_token_type_exact_out` for the
+/// owner fee.
+pub fn fee_to_pool_tokens(
+    amp: u64,
+    balances: &[u128],
+    index: usize,
+    fee_amount: u128,
+    pool_supply: u128,
+) -> Option<u128> {
+    if fee_amount == 0 {
+        return Some(0);
+    }
+    let d1 = compute_d(amp, balances)?;
+    let mut balances_without_fee = balances.to_vec();
+    balances_without_fee[index] = balances_without_fee[index].checked_sub(fee_amount)?;
+    let d0 = compute_d(amp, &balances_without_fee)?;
+    let pool_tokens = U256::from(pool_supply)
+        .checked_mul(U256::from(d1.checked_sub(d0)?))?
+        .checked_div(U256::from(d0))?;
+    u128::try_from(pool_tokens).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMPS: &[u64] = &[1, 100, 10_000];
+
+    const POOLS: &[&[u128]] = &[
+        &[1_000_000, 1_000_000],
+        &[50_000_000, 20_000_000, 30_000_000],
+        &[1_000_000_000, 3_000_000_000, 2_000_000_000, 500_000_000],
+        &[123_456_789, 98_765_432, 111_111_111, 87_654_321, 100_000_000],
+    ];
+
+    /// Asserts that the invariant per pool token did not go down from the
+    /// `before` balances and supply to the `after` ones
+    fn assert_share_value_not_decreased(
+        amp: u64,
+        before: (&[u128], u128),
+        after: (&[u128], u128),
+    ) {
+        let d_before = compute_d(amp, before.0).unwrap();
+        let d_after = compute_d(amp, after.0).unwrap();
+        assert!(
+            U256::from(d_after) * U256::from(before.1) >= U256::from(d_before) * U256::from(after.1),
+            "share value decreased from {:?} to {:?}",
+            before,
+            after
+        );
+    }
+
+    #[test]
+    fn compute_d_of_balanced_pool_is_sum() {
+        for amp in AMPS {
+            for n in 2..=MAX_TOKENS_IN_MULTI_POOL {
+                assert_eq!(compute_d(*amp, &vec![1_000_000; n]), Some(1_000_000 * n as u128));
+            }
+        }
+        assert_eq!(compute_d(100, &[0, 0, 0]), Some(0));
+        // an empty balance has no invariant
+        assert_eq!(compute_d(100, &[1_000, 0, 1_000]), None);
+    }
+
+    #[test]
+    fn compute_d_of_imbalanced_pool_is_below_sum() {
+        for pool in POOLS.iter().skip(1) {
+            let sum = pool.iter().sum::<u128>();
+            let mut previous = 0;
+            // a higher amplification flattens the curve towards the sum
+            for amp in AMPS {
+                let d = compute_d(*amp, pool).unwrap();
+                assert!(d < sum && d > previous, "amp {} pool {:?}: {}", amp, pool, d);
+                previous = d;
+            }
+        }
+    }
+
+    #[test]
+    fn compute_y_recovers_balance() {
+        for amp in AMPS {
+            for pool in POOLS {
+                let d = compute_d(*amp, pool).unwrap();
+                for (index, balance) in pool.iter().enumerate() {
+                    let y = compute_y(*amp, pool, index, d).unwrap();
+                    assert!(y.abs_diff(*balance) <= 1, "amp {} pool {:?}: {}", amp, pool, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_without_fees_keeps_invariant() {
+        for amp in AMPS {
+            for pool in POOLS {
+                let (i, j) = (0, pool.len() - 1);
+                for source_amount in [1, 13, pool[i] / 1_000, pool[i]] {
+                    let destination_amount =
+                        swap_without_fees(*amp, pool, i, j, source_amount).unwrap_or(0);
+                    assert!(destination_amount < pool[j]);
+                    let mut after = pool.to_vec();
+                    after[i] += source_amount;
+                    after[j] -= destination_amount;
+                    assert_share_value_not_decreased(*amp, (pool, 1), (&after, 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_near_peg() {
+        let pool = [1_000_000_000; 3];
+        let destination_amount = swap_without_fees(100, &pool, 0, 2, 1_000_000).unwrap();
+        assert!(destination_amount < 1_000_000 && destination_amount > 999_000);
+    }
+
+    #[test]
+    fn balanced_deposit_pays_no_fee() {
+        for amp in AMPS {
+            for pool in POOLS {
+                let amounts = pool.iter().map(|x| x / 10).collect::<Vec<u128>>();
+                let supply = 1_000_000;
+                let without_fee = deposit(*amp, pool, &amounts, supply, 0, 1).unwrap();
+                let with_fee = deposit(*amp, pool, &amounts, supply, 30, 10_000).unwrap();
+                assert!(without_fee.abs_diff(supply / 10) <= 1);
+                assert!(with_fee.abs_diff(without_fee) <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn imbalanced_deposit_keeps_share_value() {
+        for amp in AMPS {
+            for pool in POOLS {
+                let supply = pool.iter().sum::<u128>() / 3;
+                for amount in [13, pool[0] / 1_000, pool[0]] {
+                    let mut amounts = vec![0; pool.len()];
+                    amounts[0] = amount;
+                    let without_fee = deposit(*amp, pool, &amounts, supply, 0, 1).unwrap();
+                    let with_fee = deposit(*amp, pool, &amounts, supply, 30, 10_000).unwrap();
+                    assert!(with_fee <= without_fee);
+                    let mut after = pool.to_vec();
+                    after[0] += amount;
+                    assert_share_value_not_decreased(
+                        *amp,
+                        (pool, supply),
+                        (&after, supply + without_fee),
+                    );
+                }
+            }
+        }
+        // a deposit must add to the invariant
+        assert_eq!(deposit(100, &[1_000, 1_000], &[0, 0], 1_000, 0, 1), None);
+    }
+
+    #[test]
+    fn withdraw_one_keeps_share_value() {
+        for amp in AMPS {
+            for pool in POOLS {
+                let supply = pool.iter().sum::<u128>() / 3;
+                let index = pool.len() - 1;
+                for pool_tokens in [13, supply / 1_000, supply / 4] {
+                    let without_fee =
+                        withdraw_one(*amp, pool, pool_tokens, supply, index, 0, 1).unwrap_or(0);
+                    let with_fee = withdraw_one(*amp, pool, pool_tokens, supply, index, 30, 10_000)
+                        .unwrap_or(0);
+                    assert!(with_fee <= without_fee);
+                    let mut after = pool.to_vec();
+                    after[index] -= without_fee;
+                    assert_share_value_not_decreased(
+                        *amp,
+                        (pool, supply),
+                        (&after, supply - pool_tokens),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fee_to_pool_tokens_keeps_share_value() {
+        for amp in AMPS {
+            for pool in POOLS {
+                let supply = pool.iter().sum::<u128>() / 3;
+                for fee_amount in [1, 13, pool[1] / 1_000] {
+                    // the fee is already in the pool
+                    let mut before = pool.to_vec();
+                    before[1] -= fee_amount;
+                    let minted = fee_to_pool_tokens(*amp, pool, 1, fee_amount, supply).unwrap();
+                    assert_share_value_not_decreased(
+                        *amp,
+                        (&before, supply),
+                        (pool, supply + minted),
+                    );
+                }
+                assert_eq!(fee_to_pool_tokens(*amp, pool, 1, 0, supply), Some(0));
+            }
+        }
+    }
+}
@@ -97,4 +97,72 @@ pub enum SwapError {
     // The operation cannot be performed on the given curve
     #[msg("The operation cannot be performed on the given curve")]
     UnsupportedCurveOperation,
+    // The initial pool token supply does not exceed the locked minimum liquidity
+    #[msg("Initial pool token supply must exceed the locked minimum liquidity")]
+    InsufficientInitialLiquidity,
+    // The pool has no wrapped SOL side
+    #[msg("Neither side of the pool is wrapped SOL")]
+    ExpectedNativeMint,
+
+    // 30.
+    // Pool tokens other than the locked minimum liquidity are outstanding
+    #[msg("Pool still has outstanding pool tokens")]
+    PoolNotEmpty,
+    // The pool account layout version does not fit the instruction
+    #[msg("Pool account has an unexpected layout version, it may need migrate_account")]
+    UnsupportedAccountVersion,
+    // A deposit would take the reserves or the pool token supply over its cap
+    #[msg("Deposit exceeds the pool's reserve or supply cap")]
+    DepositCapExceeded,
+    // The pool is permissioned and the user has no allowlist entry
+    #[msg("User is not on the pool's allowlist")]
+    NotAllowlisted,
+    // Unstaking more pool tokens than are staked
+    #[msg("Insufficient staked pool tokens")]
+    InsufficientStake,
+
+    // 35.
+    // The lock's vesting ends before it unlocks
+    #[msg("Vesting must not end before the unlock time")]
+    InvalidLockSchedule,
+    // Nothing is unlocked beyond what was already withdrawn
+    #[msg("No locked pool tokens can be withdrawn yet")]
+    LiquidityStillLocked,
+    // Executing an order after its expiry
+    #[msg("Order has expired")]
+    OrderExpired,
+    // The pool's price doesn't give the order its minimum output
+    #[msg("Order's target price is not met")]
+    OrderPriceNotMet,
+    // Cranking a DCA schedule before its next tranche is due, or once done
+    #[msg("No DCA tranche is due")]
+    DcaNotDue,
+
+    // 40.
+    // The tranche's output is too far below the pool's TWAP
+    #[msg("Swap output is too far below the TWAP")]
+    TwapSlippageExceeded,
+    // A multi-asset pool given too few or too many tokens
+    #[msg("Multi-asset pools hold between 2 and 8 tokens")]
+    InvalidTokenCount,
+    // A trade moving the price too far from the slot's anchor price
+    #[msg("Trade moves the price beyond the slot's band")]
+    PriceBandExceeded,
+    // Withdrawing in the slot of a deposit while MEV protection is on
+    #[msg("Withdrawals are blocked in the slot of a deposit")]
+    SameSlotWithdrawal,
+    // Trading while the admin has the pool paused
+    #[msg("Pool is paused")]
+    PoolPaused,
+
+    // 45.
+    // A trade moving the price past the circuit breaker's threshold
+    #[msg("Trade moves the price too far from its moving average")]
+    CircuitBreakerTripped,
+    // The reserves' normalized value per pool token dropped over an operation
+    #[msg("Operation decreased the value of pool tokens beyond rounding")]
+    ShareValueDecreased,
+    // Skimming while a vault holds less than its tracked reserve
+    #[msg("Vault balance is below the pool's reserve, sync first")]
+    VaultBelowReserves,
 }
\ No newline at end of file
@@ -0,0 +1,387 @@
+use anchor_lang::prelude::*;
+
+/// Emitted when a pool is created and seeded with its initial liquidity
+#[event]
+pub struct InitPoolEvent {
+    pub amm: Pubkey,
+    /// Payer funding the initial liquidity
+    pub user: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    /// Pool tokens minted to the payer, excluding the locked minimum liquidity
+    pub pool_token_amount: u64,
+    pub pool_mint_supply: u64,
+}
+
+/// Emitted for every swap, including the swap leg of `zap_in`
+#[event]
+pub struct SwapEvent {
+    pub amm: Pubkey,
+    pub user: Pubkey,
+    pub source_mint: Pubkey,
+    pub destination_mint: Pubkey,
+    /// Source tokens taken from the user, fees included
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Source tokens kept by the pool for its liquidity providers
+    pub trade_fee: u64,
+    /// Source tokens charged for the owner, paid out as pool tokens
+    pub owner_fee: u64,
+    /// Pool tokens minted to the host out of the owner fee
+    pub host_fee: u64,
+    /// Reserves after the operation
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    /// Pool token supply after the operation
+    pub pool_mint_supply: u64,
+}
+
+/// Emitted for every deposit, single sided or not
+#[event]
+pub struct DepositEvent {
+    pub amm: Pubkey,
+    pub user: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    /// Pool tokens minted to the user
+    pub pool_token_amount: u64,
+    /// Reserves after the operation
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    /// Pool token supply after the operation
+    pub pool_mint_supply: u64,
+}
+
+/// Emitted for every withdrawal, single sided or not
+#[event]
+pub struct WithdrawEvent {
+    pub amm: Pubkey,
+    pub user: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    /// Pool tokens burned
+    pub pool_token_amount: u64,
+    /// Pool tokens transferred to the fee account
+    pub withdraw_fee: u64,
+    /// Reserves after the operation
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    /// Pool token supply after the operation
+    pub pool_mint_supply: u64,
+}
+
+/// Emitted when an empty pool is closed
+#[event]
+pub struct ClosePoolEvent {
+    pub amm: Pubkey,
+    pub receiver: Pubkey,
+    /// Vault balances left behind by the locked minimum liquidity
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+}
+
+/// Emitted when a pool is moved to a new curve and pool mint
+#[event]
+pub struct MigratePoolEvent {
+    pub amm: Pubkey,
+    pub old_pool_mint: Pubkey,
+    pub new_pool_mint: Pubkey,
+    pub old_supply: u64,
+    pub new_supply: u64,
+}
+
+/// Emitted when old pool tokens are converted after a migration
+#[event]
+pub struct MigrateLiquidityEvent {
+    pub amm: Pubkey,
+    pub user: Pubkey,
+    /// Old pool tokens burned
+    pub old_pool_token_amount: u64,
+    /// New pool tokens received
+    pub new_pool_token_amount: u64,
+}
+
+/// Emitted when the admin changes the deposit caps
+#[event]
+pub struct DepositCapsEvent {
+    pub amm: Pubkey,
+    pub max_reserve_a: u64,
+    pub max_reserve_b: u64,
+    pub max_pool_supply: u64,
+}
+
+/// Emitted when the admin hands the pool over to a new admin
+#[event]
+pub struct AdminEvent {
+    pub amm: Pubkey,
+    pub admin: Pubkey,
+}
+
+/// Emitted when the admin changes where skimmed tokens are sent
+#[event]
+pub struct SkimRecipientEvent {
+    pub amm: Pubkey,
+    pub skim_recipient: Pubkey,
+}
+
+/// Emitted when the admin changes the MEV protection band
+#[event]
+pub struct MevProtectionEvent {
+    pub amm: Pubkey,
+    pub mev_band_bps: u64,
+}
+
+/// Emitted when the admin configures the circuit breaker
+#[event]
+pub struct CircuitBreakerEvent {
+    pub amm: Pubkey,
+    pub circuit_breaker_bps: u64,
+}
+
+/// Emitted when the admin sets how long `pause_pool` pauses the pool for
+#[event]
+pub struct PauseDurationEvent {
+    pub amm: Pubkey,
+    pub pause_duration: i64,
+}
+
+/// Emitted when the admin pauses the pool
+#[event]
+pub struct PausePoolEvent {
+    pub amm: Pubkey,
+    pub paused_until: i64,
+}
+
+/// Emitted when the admin adds a user to or removes one from the allowlist
+#[event]
+pub struct AllowlistEvent {
+    pub amm: Pubkey,
+    pub user: Pubkey,
+    pub allowed: bool,
+}
+
+/// Emitted when the admin turns the allowlist check on or off
+#[event]
+pub struct PermissionedEvent {
+    pub amm: Pubkey,
+    pub permissioned: bool,
+}
+
+/// Emitted when the admin creates a farm for a pool
+#[event]
+pub struct CreateFarmEvent {
+    pub farm: Pubkey,
+    pub amm: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_per_second: u64,
+}
+
+/// Emitted when rewards are added to a farm
+#[event]
+pub struct FundRewardsEvent {
+    pub farm: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub reward_remaining: u64,
+}
+
+/// Emitted when pool tokens are staked in a farm
+#[event]
+pub struct StakeEvent {
+    pub farm: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    /// User's stake after the operation
+    pub user_staked: u64,
+    /// Farm's total stake after the operation
+    pub total_staked: u64,
+}
+
+/// Emitted when pool tokens are unstaked from a farm
+#[event]
+pub struct UnstakeEvent {
+    pub farm: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    /// User's stake after the operation
+    pub user_staked: u64,
+    /// Farm's total stake after the operation
+    pub total_staked: u64,
+}
+
+/// Emitted when a user claims farm rewards
+#[event]
+pub struct ClaimEvent {
+    pub farm: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted when pool tokens are locked
+#[event]
+pub struct LockEvent {
+    pub lock_receipt: Pubkey,
+    pub amm: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub unlock_ts: i64,
+    pub vesting_end_ts: i64,
+}
+
+/// Emitted when unlocked pool tokens are withdrawn from a lock
+#[event]
+pub struct WithdrawLockedEvent {
+    pub lock_receipt: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    /// Pool tokens still in the lock
+    pub remaining_amount: u64,
+}
+
+/// Emitted when a lock receipt changes owner
+#[event]
+pub struct TransferLockEvent {
+    pub lock_receipt: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+/// Emitted when a limit order is placed
+#[event]
+pub struct PlaceOrderEvent {
+    pub order: Pubkey,
+    pub amm: Pubkey,
+    pub owner: Pubkey,
+    pub source_mint: Pubkey,
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub bounty: u64,
+    pub expiry_ts: i64,
+}
+
+/// Emitted when a keeper executes a limit order, alongside its `SwapEvent`
+#[event]
+pub struct ExecuteOrderEvent {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub keeper: Pubkey,
+    pub amount_out: u64,
+    pub bounty: u64,
+}
+
+/// Emitted when the owner cancels a limit order
+#[event]
+pub struct CancelOrderEvent {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    /// Escrowed tokens refunded
+    pub amount: u64,
+}
+
+/// Emitted when a DCA schedule is created
+#[event]
+pub struct CreateDcaEvent {
+    pub dca: Pubkey,
+    pub amm: Pubkey,
+    pub owner: Pubkey,
+    pub source_mint: Pubkey,
+    pub amount: u64,
+    pub tranche_count: u64,
+    pub interval_secs: i64,
+}
+
+/// Emitted when a DCA tranche is swapped, alongside its `SwapEvent`
+#[event]
+pub struct CrankDcaEvent {
+    pub dca: Pubkey,
+    pub cranker: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub tranches_done: u64,
+}
+
+/// Emitted when the owner withdraws from a DCA schedule
+#[event]
+pub struct WithdrawDcaEvent {
+    pub dca: Pubkey,
+    pub owner: Pubkey,
+    /// Bought tokens withdrawn
+    pub output_amount: u64,
+    /// Unsold source tokens refunded when the schedule is closed
+    pub source_amount: u64,
+}
+
+/// Emitted when a multi-asset pool is created
+#[event]
+pub struct InitMultiPoolEvent {
+    pub multi_pool: Pubkey,
+    pub user: Pubkey,
+    pub mints: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    /// Pool tokens minted to the payer, excluding the locked minimum liquidity
+    pub pool_token_amount: u64,
+}
+
+/// Emitted for every swap in a multi-asset pool
+#[event]
+pub struct MultiSwapEvent {
+    pub multi_pool: Pubkey,
+    pub user: Pubkey,
+    pub source_mint: Pubkey,
+    pub destination_mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub trade_fee: u64,
+    pub owner_fee: u64,
+    /// Reserves after the operation
+    pub reserves: Vec<u64>,
+    pub pool_mint_supply: u64,
+}
+
+/// Emitted for every deposit into a multi-asset pool, with an amount for each
+/// of its tokens
+#[event]
+pub struct MultiDepositEvent {
+    pub multi_pool: Pubkey,
+    pub user: Pubkey,
+    pub amounts: Vec<u64>,
+    pub pool_token_amount: u64,
+    /// Reserves after the operation
+    pub reserves: Vec<u64>,
+    pub pool_mint_supply: u64,
+}
+
+/// Emitted for every withdrawal from a multi-asset pool, with an amount for
+/// each of its tokens
+#[event]
+pub struct MultiWithdrawEvent {
+    pub multi_pool: Pubkey,
+    pub user: Pubkey,
+    pub amounts: Vec<u64>,
+    /// Pool tokens burned
+    pub pool_token_amount: u64,
+    /// Pool tokens transferred to the fee account
+    pub withdraw_fee: u64,
+    /// Reserves after the operation
+    pub reserves: Vec<u64>,
+    pub pool_mint_supply: u64,
+}
+
+/// Emitted when `sync` adopts the vault balances as reserves
+#[event]
+pub struct SyncEvent {
+    pub amm: Pubkey,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+}
+
+/// Emitted when `skim` sends excess vault balances to the skim recipient
+#[event]
+pub struct SkimEvent {
+    pub amm: Pubkey,
+    pub recipient: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+}
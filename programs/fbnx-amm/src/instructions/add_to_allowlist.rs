@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use std::mem::size_of;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Allow `user` to swap and deposit in a permissioned pool
+pub fn handler(ctx: Context<AddToAllowlist>, user: Pubkey) -> Result<()> {
+    let entry = &mut ctx.accounts.allowlist_entry;
+    entry.amm = ctx.accounts.amm.key();
+    entry.user = user;
+
+    emit!(AllowlistEvent {
+        amm: ctx.accounts.amm.key(),
+        user,
+        allowed: true,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct AddToAllowlist<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        seeds = [b"allowlist".as_ref(),amm.key().as_ref(),user.as_ref()],
+        bump,
+        payer = admin,
+        space = 8 + size_of::<AllowlistEntry>()
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Cancel a limit order, expired or not, refunding the escrowed tokens and
+/// returning the bounty and rent to the owner
+pub fn handler(ctx: Context<CancelOrder>) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    let amount = ctx.accounts.escrow.amount;
+    if amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_refund_context()
+                .with_signer(&[&seeds[..]]),
+            amount,
+        )?;
+    }
+    token::close_account(
+        ctx.accounts
+            .into_close_escrow_context()
+            .with_signer(&[&seeds[..]]),
+    )?;
+
+    emit!(CancelOrderEvent {
+        order: ctx.accounts.order.key(),
+        owner: ctx.accounts.owner.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = amm,
+        has_one = owner,
+        has_one = escrow,
+        close = owner
+    )]
+    pub order: Box<Account<'info, Order>>,
+    #[account(mut)]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = refund.mint == order.source_mint @ SwapError::IncorrectSwapAccount,
+    )]
+    pub refund: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CancelOrder<'info> {
+    fn into_transfer_to_refund_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.escrow.to_account_info().clone(),
+            to: self.refund.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_close_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.escrow.to_account_info().clone(),
+            destination: self.owner.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::events::*;
+
+/// Pay out a user's accrued farm rewards
+pub fn handler(ctx: Context<Claim>) -> Result<()> {
+    let farm = &mut ctx.accounts.farm;
+    farm.update(Clock::get()?.unix_timestamp)?;
+
+    let user_stake = &mut ctx.accounts.user_stake;
+    user_stake.settle(farm)?;
+    let amount = user_stake.pending_reward;
+    user_stake.pending_reward = 0;
+
+    let (farm_amm, farm_reward_mint, farm_bump_seed) =
+        (farm.amm, farm.reward_mint, farm.bump_seed);
+    let seeds = &[
+        b"farm".as_ref(),
+        farm_amm.as_ref(),
+        farm_reward_mint.as_ref(),
+        &[farm_bump_seed][..],
+    ];
+    if amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_from_reward_vault_context()
+                .with_signer(&[&seeds[..]]),
+            amount,
+        )?;
+    }
+
+    emit!(ClaimEvent {
+        farm: ctx.accounts.farm.key(),
+        user: ctx.accounts.owner.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(
+        mut,
+        seeds = [b"farm".as_ref(),farm.amm.as_ref(),farm.reward_mint.as_ref()],
+        bump = farm.bump_seed,
+        has_one = reward_vault,
+     )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        seeds = [b"stake".as_ref(),farm.key().as_ref(),owner.key().as_ref()],
+        bump,
+        has_one = farm,
+        has_one = owner,
+    )]
+    pub user_stake: Box<Account<'info, UserStake>>,
+    pub owner: Signer<'info>,
+    #[account(mut,
+        constraint = destination.mint == farm.reward_mint,
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Claim<'info> {
+    fn into_transfer_from_reward_vault_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.reward_vault.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.farm.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Stop a DCA schedule, finished or not, sending the bought tokens and any
+/// unsold source tokens to the owner and closing its accounts
+pub fn handler(ctx: Context<CloseDca>) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    let output_amount = ctx.accounts.output_escrow.amount;
+    if output_amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_from_output_context()
+                .with_signer(&[&seeds[..]]),
+            output_amount,
+        )?;
+    }
+    let source_amount = ctx.accounts.source_escrow.amount;
+    if source_amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_from_source_context()
+                .with_signer(&[&seeds[..]]),
+            source_amount,
+        )?;
+    }
+    token::close_account(
+        ctx.accounts
+            .into_close_output_escrow_context()
+            .with_signer(&[&seeds[..]]),
+    )?;
+    token::close_account(
+        ctx.accounts
+            .into_close_source_escrow_context()
+            .with_signer(&[&seeds[..]]),
+    )?;
+
+    emit!(WithdrawDcaEvent {
+        dca: ctx.accounts.dca.key(),
+        owner: ctx.accounts.owner.key(),
+        output_amount,
+        source_amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseDca<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = amm,
+        has_one = owner,
+        has_one = source_escrow,
+        has_one = output_escrow,
+        close = owner
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    #[account(mut)]
+    pub source_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub output_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = source_refund.mint == source_escrow.mint @ SwapError::IncorrectSwapAccount,
+    )]
+    pub source_refund: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = destination.mint == output_escrow.mint @ SwapError::IncorrectSwapAccount,
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CloseDca<'info> {
+    fn into_transfer_from_output_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.output_escrow.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_from_source_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source_escrow.to_account_info().clone(),
+            to: self.source_refund.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_close_output_escrow_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.output_escrow.to_account_info().clone(),
+            destination: self.owner.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_close_source_escrow_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.source_escrow.to_account_info().clone(),
+            destination: self.owner.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
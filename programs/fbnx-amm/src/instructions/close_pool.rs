@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, Burn, CloseAccount, Mint, SetAuthority, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Tear down a pool whose only remaining pool tokens are the locked minimum
+/// liquidity.  The locked pool tokens are burned, the balances they were worth
+/// are sent to the receiver's token accounts, and the vaults, the locked
+/// liquidity account and the `Amm` are closed with their rent going to the
+/// receiver.  Token mints cannot be closed, so the pool mint is left behind
+/// with its mint authority removed.
+pub fn handler(ctx: Context<ClosePool>) -> Result<()> {
+    if ctx.accounts.pool_mint.supply != ctx.accounts.locked_liquidity.amount {
+        return Err(SwapError::PoolNotEmpty.into());
+    }
+
+    let amm = &ctx.accounts.amm;
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    let locked_amount = ctx.accounts.locked_liquidity.amount;
+    if locked_amount > 0 {
+        token::burn(
+            ctx.accounts
+                .into_burn_locked_liquidity_context()
+                .with_signer(&[&seeds[..]]),
+            locked_amount,
+        )?;
+    }
+
+    let token_a_amount = ctx.accounts.vault_token_a.amount;
+    let token_b_amount = ctx.accounts.vault_token_b.amount;
+    if token_a_amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_receiver_a_context()
+                .with_signer(&[&seeds[..]]),
+            token_a_amount,
+        )?;
+    }
+    if token_b_amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_receiver_b_context()
+                .with_signer(&[&seeds[..]]),
+            token_b_amount,
+        )?;
+    }
+
+    let vault_token_a = ctx.accounts.vault_token_a.to_account_info();
+    let vault_token_b = ctx.accounts.vault_token_b.to_account_info();
+    let locked_liquidity = ctx.accounts.locked_liquidity.to_account_info();
+    for account in [vault_token_a, vault_token_b, locked_liquidity] {
+        token::close_account(
+            ctx.accounts
+                .into_close_context(account)
+                .with_signer(&[&seeds[..]]),
+        )?;
+    }
+    token::set_authority(
+        ctx.accounts
+            .into_remove_mint_authority_context()
+            .with_signer(&[&seeds[..]]),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    emit!(ClosePoolEvent {
+        amm: amm_key,
+        receiver: ctx.accounts.receiver.key(),
+        token_a_amount,
+        token_b_amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+        close = receiver,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub admin: Signer<'info>,
+    /// CHECK: only receives the reclaimed rent
+    #[account(mut)]
+    pub receiver: AccountInfo<'info>,
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = receiver_token_a.mint == amm.token_a_mint @ SwapError::IncorrectSwapAccount,
+    )]
+    pub receiver_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = receiver_token_b.mint == amm.token_b_mint @ SwapError::IncorrectSwapAccount,
+    )]
+    pub receiver_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"locked_liquidity".as_ref(),amm.key().as_ref(),pool_mint.key().as_ref()],
+        bump,
+    )]
+    pub locked_liquidity: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClosePool<'info> {
+    fn into_burn_locked_liquidity_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        let cpi_accounts = Burn {
+            mint: self.pool_mint.to_account_info().clone(),
+            from: self.locked_liquidity.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_receiver_a_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_a.to_account_info().clone(),
+            to: self.receiver_token_a.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_receiver_b_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_b.to_account_info().clone(),
+            to: self.receiver_token_b.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_close_context(
+        &self,
+        account: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account,
+            destination: self.receiver.clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_remove_mint_authority_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, SetAuthority<'info>> {
+        let cpi_accounts = SetAuthority {
+            current_authority: self.authority.clone(),
+            account_or_mint: self.pool_mint.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
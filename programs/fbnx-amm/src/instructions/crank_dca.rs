@@ -0,0 +1,262 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+
+use crate::curve::calculator::TradeDirection;
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Swap the next due tranche of a DCA schedule into its output escrow.  Anyone
+/// can call this.  The tranche fails unless its output is within the
+/// schedule's `max_slippage_bps` of the pool TWAP since the previous tranche.
+pub fn handler(ctx: Context<CrankDca>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let dca = &ctx.accounts.dca;
+    if dca.tranches_done >= dca.tranche_count || now < dca.next_crank_ts {
+        return Err(SwapError::DcaNotDue.into());
+    }
+    check_allowlist(&ctx.accounts.amm, ctx.program_id, &dca.owner, ctx.remaining_accounts)?;
+    ctx.accounts.amm.check_not_paused()?;
+
+    let remaining_tranches = dca.tranche_count - dca.tranches_done;
+    let amount_in = ctx.accounts.source_escrow.amount / remaining_tranches;
+    if amount_in == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let amm = &mut ctx.accounts.amm;
+    amm.before_reserves_change()?;
+    let (trade_direction, price_cumulative) = if dca.source_mint == amm.token_a_mint {
+        (TradeDirection::AtoB, amm.price_a_cumulative)
+    } else {
+        (TradeDirection::BtoA, amm.price_b_cumulative)
+    };
+    let elapsed = u128::try_from(now - dca.last_crank_ts).map_err(|_| SwapError::ConversionFailure)?;
+    let twap = price_cumulative
+        .wrapping_sub(dca.price_cumulative_last)
+        .checked_div(elapsed)
+        .ok_or(SwapError::CalculationFailure)?;
+    let minimum_amount_out = u128::from(amount_in)
+        .checked_mul(twap)
+        .and_then(|x| x.checked_mul(u128::from(10_000 - dca.max_slippage_bps)))
+        .map(|x| (x >> PRICE_FRACTIONAL_BITS) / 10_000)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
+
+    let (swap_source_amount, swap_destination_amount) = match trade_direction {
+        TradeDirection::AtoB => (amm.reserve_a, amm.reserve_b),
+        TradeDirection::BtoA => (amm.reserve_b, amm.reserve_a),
+    };
+    let result = curve
+        .swap(
+            u128::from(amount_in),
+            u128::from(swap_source_amount),
+            u128::from(swap_destination_amount),
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+    if result.destination_amount_swapped < minimum_amount_out {
+        return Err(SwapError::TwapSlippageExceeded.into());
+    }
+
+    let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+        TradeDirection::AtoB => (
+            result.new_swap_source_amount,
+            result.new_swap_destination_amount,
+        ),
+        TradeDirection::BtoA => (
+            result.new_swap_destination_amount,
+            result.new_swap_source_amount,
+        ),
+    };
+    let pool_token_amount = curve
+        .withdraw_single_token_type_exact_out(
+            result.owner_fee,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            u128::from(ctx.accounts.pool_mint.supply),
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::FeeCalculationFailure)?;
+    // the owner fee is minted rounded up
+    let rounded_up_pool_tokens = u64::from(pool_token_amount > 0);
+
+    let amount_out = u64::try_from(result.destination_amount_swapped)
+        .map_err(|_| SwapError::ConversionFailure)?;
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    token::transfer(
+        ctx.accounts
+            .into_transfer_to_swap_source_context(trade_direction)
+            .with_signer(&[&seeds[..]]),
+        amount_in,
+    )?;
+    if pool_token_amount > 0 {
+        token::mint_to(
+            ctx.accounts
+                .into_mint_to_pool_context()
+                .with_signer(&[&seeds[..]]),
+            u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+        )?;
+    }
+    token::transfer(
+        ctx.accounts
+            .into_transfer_to_output_context(trade_direction)
+            .with_signer(&[&seeds[..]]),
+        amount_out,
+    )?;
+
+    match trade_direction {
+        TradeDirection::AtoB => {
+            ctx.accounts.amm.credit_reserves(amount_in, 0)?;
+            ctx.accounts.amm.debit_reserves(0, amount_out)?;
+        }
+        TradeDirection::BtoA => {
+            ctx.accounts.amm.credit_reserves(0, amount_in)?;
+            ctx.accounts.amm.debit_reserves(amount_out, 0)?;
+        }
+    }
+    ctx.accounts.amm.check_price_band()?;
+    ctx.accounts.amm.check_circuit_breaker()?;
+    ctx.accounts
+        .amm
+        .stats
+        .record_swap(trade_direction, &result, 0, Clock::get()?.slot);
+
+    let dca = &mut ctx.accounts.dca;
+    dca.tranches_done += 1;
+    dca.next_crank_ts = now
+        .checked_add(dca.interval_secs)
+        .ok_or(SwapError::CalculationFailure)?;
+    dca.price_cumulative_last = price_cumulative;
+    dca.last_crank_ts = now;
+    dca.total_in = dca
+        .total_in
+        .checked_add(amount_in)
+        .ok_or(SwapError::CalculationFailure)?;
+    dca.total_out = dca
+        .total_out
+        .checked_add(amount_out)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let (source_mint, destination_mint) = match trade_direction {
+        TradeDirection::AtoB => (ctx.accounts.amm.token_a_mint, ctx.accounts.amm.token_b_mint),
+        TradeDirection::BtoA => (ctx.accounts.amm.token_b_mint, ctx.accounts.amm.token_a_mint),
+    };
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(
+        &share_value,
+        ctx.accounts.pool_mint.supply,
+        rounded_up_pool_tokens,
+    )?;
+    emit!(SwapEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.dca.owner,
+        source_mint,
+        destination_mint,
+        amount_in,
+        amount_out,
+        trade_fee: u64::try_from(result.trade_fee).map_err(|_| SwapError::ConversionFailure)?,
+        owner_fee: u64::try_from(result.owner_fee).map_err(|_| SwapError::ConversionFailure)?,
+        host_fee: 0,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+    emit!(CrankDcaEvent {
+        dca: ctx.accounts.dca.key(),
+        cranker: ctx.accounts.cranker.key(),
+        amount_in,
+        amount_out,
+        tranches_done: ctx.accounts.dca.tranches_done,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CrankDca<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub cranker: Signer<'info>,
+    #[account(
+        mut,
+        has_one = amm,
+        has_one = source_escrow,
+        has_one = output_escrow,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    #[account(mut)]
+    pub source_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub output_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub swap_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub swap_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Safe
+    #[account(mut, address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
+    pub fee_account: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CrankDca<'info> {
+    fn into_transfer_to_swap_source_context(
+        &self,
+        trade_direction: TradeDirection,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let swap_source = match trade_direction {
+            TradeDirection::AtoB => self.swap_token_a.to_account_info(),
+            TradeDirection::BtoA => self.swap_token_b.to_account_info(),
+        };
+        let cpi_accounts = Transfer {
+            from: self.source_escrow.to_account_info().clone(),
+            to: swap_source,
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_output_context(
+        &self,
+        trade_direction: TradeDirection,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let swap_destination = match trade_direction {
+            TradeDirection::AtoB => self.swap_token_b.to_account_info(),
+            TradeDirection::BtoA => self.swap_token_a.to_account_info(),
+        };
+        let cpi_accounts = Transfer {
+            from: swap_destination,
+            to: self.output_escrow.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_pool_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.fee_account.clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, TokenAccount, Transfer, Token};
+use std::mem::size_of;
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Escrow `amount` of one of the pool's tokens to be swapped by `crank_dca` in
+/// `tranche_count` tranches, one every `interval_secs` starting one interval
+/// from now.  Each tranche must get within `max_slippage_bps` of the pool TWAP
+/// since the previous tranche.
+pub fn handler(
+    ctx: Context<CreateDca>,
+    amount: u64,
+    tranche_count: u64,
+    interval_secs: i64,
+    max_slippage_bps: u64,
+) -> Result<()> {
+    if tranche_count == 0 || amount < tranche_count || interval_secs <= 0 || max_slippage_bps > 10_000 {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let amm = &ctx.accounts.amm;
+    let source_mint = ctx.accounts.source_mint.key();
+    let output_mint = if source_mint == amm.token_a_mint {
+        amm.token_b_mint
+    } else if source_mint == amm.token_b_mint {
+        amm.token_a_mint
+    } else {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    };
+    if ctx.accounts.output_mint.key() != output_mint {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+
+    token::transfer(ctx.accounts.into_transfer_to_escrow_context(), amount)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let amm = &mut ctx.accounts.amm;
+    amm.before_reserves_change()?;
+    let price_cumulative = if source_mint == amm.token_a_mint {
+        amm.price_a_cumulative
+    } else {
+        amm.price_b_cumulative
+    };
+
+    let dca = &mut ctx.accounts.dca;
+    dca.amm = amm.key();
+    dca.owner = ctx.accounts.owner.key();
+    dca.source_mint = source_mint;
+    dca.source_escrow = ctx.accounts.source_escrow.key();
+    dca.output_escrow = ctx.accounts.output_escrow.key();
+    dca.tranche_count = tranche_count;
+    dca.tranches_done = 0;
+    dca.interval_secs = interval_secs;
+    dca.next_crank_ts = now
+        .checked_add(interval_secs)
+        .ok_or(SwapError::CalculationFailure)?;
+    dca.max_slippage_bps = max_slippage_bps;
+    dca.price_cumulative_last = price_cumulative;
+    dca.last_crank_ts = now;
+    dca.total_in = 0;
+    dca.total_out = 0;
+
+    emit!(CreateDcaEvent {
+        dca: dca.key(),
+        amm: dca.amm,
+        owner: dca.owner,
+        source_mint,
+        amount,
+        tranche_count,
+        interval_secs,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateDca<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub source_mint: Box<Account<'info, Mint>>,
+    pub output_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        has_one = owner,
+        constraint = source.mint == source_mint.key() @ SwapError::IncorrectSwapAccount,
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + size_of::<Dca>()
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    // tokens still to be sold, owned by the pool authority
+    #[account(
+        init,
+        seeds = [b"dca_source".as_ref(),dca.key().as_ref()],
+        bump,
+        payer = owner,
+        token::mint = source_mint,
+        token::authority = authority
+    )]
+    pub source_escrow: Box<Account<'info, TokenAccount>>,
+    // tokens bought, owned by the pool authority
+    #[account(
+        init,
+        seeds = [b"dca_output".as_ref(),dca.key().as_ref()],
+        bump,
+        payer = owner,
+        token::mint = output_mint,
+        token::authority = authority
+    )]
+    pub output_escrow: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateDca<'info> {
+    fn into_transfer_to_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.source_escrow.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount, Token};
+use std::mem::size_of;
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Create a farm paying `reward_mint` to stakers of the pool's pool tokens at
+/// `reward_per_second`, once it has been funded through `fund_rewards`.
+pub fn handler(ctx: Context<CreateFarm>, reward_per_second: u64) -> Result<()> {
+    let farm = &mut ctx.accounts.farm;
+    farm.amm = ctx.accounts.amm.key();
+    farm.pool_mint = ctx.accounts.pool_mint.key();
+    farm.reward_mint = ctx.accounts.reward_mint.key();
+    farm.reward_vault = ctx.accounts.reward_vault.key();
+    farm.stake_vault = ctx.accounts.stake_vault.key();
+    farm.reward_per_second = reward_per_second;
+    farm.reward_remaining = 0;
+    farm.acc_reward_per_share = 0;
+    farm.last_update_ts = Clock::get()?.unix_timestamp;
+    farm.total_staked = 0;
+    farm.bump_seed = *ctx
+        .bumps
+        .get("farm")
+        .ok_or(SwapError::InvalidProgramAddress)?;
+
+    emit!(CreateFarmEvent {
+        farm: farm.key(),
+        amm: farm.amm,
+        reward_mint: farm.reward_mint,
+        reward_per_second,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateFarm<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+        has_one = pool_mint,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub pool_mint: Box<Account<'info, Mint>>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"farm".as_ref(),amm.key().as_ref(),reward_mint.key().as_ref()],
+        bump,
+        payer = admin,
+        space = 8 + size_of::<Farm>()
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init,
+        seeds = [b"farm_rewards".as_ref(),farm.key().as_ref()],
+        bump,
+        payer = admin,
+        token::mint = reward_mint,
+        token::authority = farm
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [b"farm_stake".as_ref(),farm.key().as_ref()],
+        bump,
+        payer = admin,
+        token::mint = pool_mint,
+        token::authority = farm
+    )]
+    pub stake_vault: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
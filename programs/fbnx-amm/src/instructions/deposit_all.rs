@@ -1,22 +1,16 @@
 use anchor_lang::prelude::*;
 //use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+use std::mem::size_of;
 //pub mod curve;
 
 
-use crate::curve::{
-    base::{SwapCurve,CurveType},
-    calculator::{CurveCalculator, RoundDirection},
-    fees::CurveFees,
-};
+use crate::curve::calculator::RoundDirection;
 
 use crate::state::*;
 use crate::utils::*;
 use crate::error::SwapError;
-
-use crate::curve::{
-     constant_product::ConstantProductCurve,
-};
+use crate::events::*;
 
 pub fn handler(
     ctx: Context<DepositAllTokenTypes>,
@@ -26,53 +20,40 @@ pub fn handler(
 ) -> Result<()> {
     let amm = &mut ctx.accounts.amm;
 
-    let curve = build_curve(&amm.curve).unwrap();
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
     let calculator = curve.calculator;
     if !calculator.allows_deposits() {
         return Err(SwapError::UnsupportedCurveOperation.into());
     }
 
-    check_accounts(
-        amm,
-        ctx.program_id,
-        &amm.to_account_info(),
-        &ctx.accounts.pool_authority,
-        &ctx.accounts.vault_token_a.to_account_info(),
-        &ctx.accounts.vault_token_b.to_account_info(),
-        &ctx.accounts.pool_mint.to_account_info(),
-        &ctx.accounts.token_program.to_account_info(),
-        Some(&ctx.accounts.source_a_info.to_account_info()),
-        Some(&ctx.accounts.source_b_info.to_account_info()),
-        None,
-    )?;
+    check_allowlist(amm, ctx.program_id, ctx.accounts.owner.key, ctx.remaining_accounts)?;
 
-    let current_pool_mint_supply = u128::try_from(ctx.accounts.pool_mint.supply).unwrap();
-    let (pool_token_amount, pool_mint_supply) = if current_pool_mint_supply > 0 {
-        (
-            u128::try_from(pool_token_amount).unwrap(),
-            current_pool_mint_supply,
-        )
-    } else {
-        (calculator.new_pool_supply(), calculator.new_pool_supply())
-    };
+    // The locked minimum liquidity keeps the supply above zero once the pool
+    // is initialized, so a zero supply cannot be re-priced by a deposit.
+    let pool_mint_supply = u128::from(ctx.accounts.pool_mint.supply);
+    if pool_mint_supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+    let pool_token_amount = u128::from(pool_token_amount);
 
     let results = calculator
         .pool_tokens_to_trading_tokens(
             pool_token_amount,
             pool_mint_supply,
-            u128::try_from(ctx.accounts.vault_token_a.amount).unwrap(),
-            u128::try_from(ctx.accounts.vault_token_b.amount).unwrap(),
+            u128::from(amm.reserve_a),
+            u128::from(amm.reserve_b),
             RoundDirection::Ceiling,
         )
         .ok_or(SwapError::ZeroTradingTokens)?;
-    let token_a_amount = u64::try_from(results.token_a_amount).unwrap();
+    let token_a_amount = u64::try_from(results.token_a_amount).map_err(|_| SwapError::ConversionFailure)?;
     if token_a_amount > maximum_token_a_amount {
         return Err(SwapError::ExceededSlippage.into());
     }
     if token_a_amount == 0 {
         return Err(SwapError::ZeroTradingTokens.into());
     }
-    let token_b_amount = u64::try_from(results.token_b_amount).unwrap();
+    let token_b_amount = u64::try_from(results.token_b_amount).map_err(|_| SwapError::ConversionFailure)?;
     if token_b_amount > maximum_token_b_amount {
         return Err(SwapError::ExceededSlippage.into());
     }
@@ -80,21 +61,18 @@ pub fn handler(
         return Err(SwapError::ZeroTradingTokens.into());
     }
 
-    let pool_token_amount = u64::try_from(pool_token_amount).unwrap();
+    let pool_token_amount = u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?;
 
-    let seeds = &[&amm.to_account_info().key.to_bytes(), &[amm.bump_seed][..]];
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
 
     token::transfer(
-        ctx.accounts
-            .into_transfer_to_token_a_context()
-            .with_signer(&[&seeds[..]]),
+        ctx.accounts.into_transfer_to_token_a_context(),
         token_a_amount,
     )?;
 
     token::transfer(
-        ctx.accounts
-            .into_transfer_to_token_b_context()
-            .with_signer(&[&seeds[..]]),
+        ctx.accounts.into_transfer_to_token_b_context(),
         token_b_amount,
     )?;
 
@@ -102,9 +80,28 @@ pub fn handler(
         ctx.accounts
             .into_mint_to_context()
             .with_signer(&[&seeds[..]]),
-        u64::try_from(pool_token_amount).unwrap(),
+        u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
     )?;
 
+    ctx.accounts.amm.credit_reserves(token_a_amount, token_b_amount)?;
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(&share_value, ctx.accounts.pool_mint.supply, 0)?;
+    ctx.accounts.amm.check_deposit_caps(ctx.accounts.pool_mint.supply)?;
+    let amm_key = ctx.accounts.amm.key();
+    let owner_key = ctx.accounts.owner.key();
+    ctx.accounts.deposit_receipt.record(amm_key, owner_key)?;
+    emit!(DepositEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
     Ok(())
 }
 
@@ -112,8 +109,9 @@ pub fn handler(
 pub struct DepositAllTokenTypes<'info> {
     #[account(
         mut,
-        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_account.as_ref()],
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
         bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
      )]
     pub amm: Box<Account<'info, Amm>>,
     /// CHECK: Safe
@@ -132,17 +130,11 @@ pub struct DepositAllTokenTypes<'info> {
         has_one = owner
     )]
     pub source_b_info: Account<'info,TokenAccount>,
-    #[account(mut,
-        constraint=vault_token_a.owner == pool_authority.key(),
-    )]
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
     pub vault_token_a: Account<'info, TokenAccount>,
-    #[account(mut,
-        constraint=vault_token_b.owner == pool_authority.key(),
-    )]
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
     pub vault_token_b: Account<'info, TokenAccount>,
-    #[account(mut,
-        mint::authority = pool_authority,
-    )]
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
     pub pool_mint:Box< Account<'info, Mint>>,
     /// CHECK: Safe
     #[account(
@@ -154,6 +146,14 @@ pub struct DepositAllTokenTypes<'info> {
     pub destination: Box<Account<'info,TokenAccount>>,
     #[account(mut)]
     pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [b"deposit".as_ref(),amm.key().as_ref(),owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + size_of::<DepositReceipt>()
+    )]
+    pub deposit_receipt: Box<Account<'info, DepositReceipt>>,
     pub rent: Sysvar<'info, Rent>,
     pub token_program: Program<'info,Token>,
     pub system_program : Program<'info,System>
@@ -187,38 +187,3 @@ impl<'info> DepositAllTokenTypes<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 }
-/// Build Curve object and Fee object
-pub fn build_curve(curve_input: &CurveInput) -> Result<SwapCurve> {
-    let curve_type = CurveType::try_from(curve_input.curve_type).unwrap();
-    let culculator: Box<dyn CurveCalculator> = match curve_type {
-        CurveType::ConstantProduct => Box::new(ConstantProductCurve {}),
-        // CurveType::ConstantPrice => Box::new(ConstantPriceCurve {
-        //     token_b_price: curve_input.curve_parameters,
-        // }),
-        // CurveType::Stable => Box::new(StableCurve {
-        //     amp: curve_input.curve_parameters,
-        // }),
-        // CurveType::Offset => Box::new(OffsetCurve {
-        //     token_b_offset: curve_input.curve_parameters,
-        // }),
-    };
-    let curve = SwapCurve {
-        curve_type: curve_type,
-        calculator: culculator,
-    };
-    Ok(curve)
-}
-
-pub fn build_fees(fees_input: &FeesInput) -> Result<CurveFees> {
-    let fees = CurveFees {
-        trade_fee_numerator: fees_input.trade_fee_numerator,
-        trade_fee_denominator: fees_input.trade_fee_denominator,
-        owner_trade_fee_numerator: fees_input.owner_trade_fee_numerator,
-        owner_trade_fee_denominator: fees_input.owner_trade_fee_denominator,
-        owner_withdraw_fee_numerator: fees_input.owner_withdraw_fee_numerator,
-        owner_withdraw_fee_denominator: fees_input.owner_withdraw_fee_denominator,
-        host_fee_numerator: fees_input.host_fee_numerator,
-        host_fee_denominator: fees_input.host_fee_denominator,
-    };
-    Ok(fees)
-}
\ No newline at end of file
@@ -0,0 +1,237 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+use std::mem::size_of;
+
+use crate::curve::calculator::RoundDirection;
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Deposit both token types into a pool with a wrapped SOL side, paying the SOL
+/// side directly from the owner's lamports.  The SOL side's amount is wrapped
+/// into a temporary account, which is closed before returning so that its rent
+/// goes back to the owner.
+pub fn handler(
+    ctx: Context<DepositAllNative>,
+    pool_token_amount: u64,
+    maximum_token_a_amount: u64,
+    maximum_token_b_amount: u64,
+) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let calculator = curve.calculator;
+    if !calculator.allows_deposits() {
+        return Err(SwapError::UnsupportedCurveOperation.into());
+    }
+
+    let native_is_token_a = if ctx.accounts.vault_token_a.mint == spl_token::native_mint::id() {
+        true
+    } else if ctx.accounts.vault_token_b.mint == spl_token::native_mint::id() {
+        false
+    } else {
+        return Err(SwapError::ExpectedNativeMint.into());
+    };
+    let user_token_mint = if native_is_token_a {
+        ctx.accounts.vault_token_b.mint
+    } else {
+        ctx.accounts.vault_token_a.mint
+    };
+    if ctx.accounts.user_token.mint != user_token_mint {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+
+    let wsol_account = ctx.accounts.wsol_account.to_account_info();
+
+    check_allowlist(amm, ctx.program_id, ctx.accounts.owner.key, ctx.remaining_accounts)?;
+
+    let pool_mint_supply = u128::from(ctx.accounts.pool_mint.supply);
+    if pool_mint_supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+
+    let results = calculator
+        .pool_tokens_to_trading_tokens(
+            u128::from(pool_token_amount),
+            pool_mint_supply,
+            u128::from(amm.reserve_a),
+            u128::from(amm.reserve_b),
+            RoundDirection::Ceiling,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+    let token_a_amount =
+        u64::try_from(results.token_a_amount).map_err(|_| SwapError::ConversionFailure)?;
+    if token_a_amount > maximum_token_a_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if token_a_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+    let token_b_amount =
+        u64::try_from(results.token_b_amount).map_err(|_| SwapError::ConversionFailure)?;
+    if token_b_amount > maximum_token_b_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if token_b_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    let native_amount = if native_is_token_a {
+        token_a_amount
+    } else {
+        token_b_amount
+    };
+    wrap_native(
+        &ctx.accounts.owner.to_account_info(),
+        &wsol_account,
+        &ctx.accounts.system_program.to_account_info(),
+        native_amount,
+    )?;
+
+    token::transfer(
+        ctx.accounts.into_transfer_to_token_a_context(native_is_token_a),
+        token_a_amount,
+    )?;
+    token::transfer(
+        ctx.accounts.into_transfer_to_token_b_context(native_is_token_a),
+        token_b_amount,
+    )?;
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_context()
+            .with_signer(&[&seeds[..]]),
+        pool_token_amount,
+    )?;
+    unwrap_native(
+        &ctx.accounts.owner.to_account_info(),
+        &wsol_account,
+        &ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    ctx.accounts.amm.credit_reserves(token_a_amount, token_b_amount)?;
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(&share_value, ctx.accounts.pool_mint.supply, 0)?;
+    ctx.accounts.amm.check_deposit_caps(ctx.accounts.pool_mint.supply)?;
+    let amm_key = ctx.accounts.amm.key();
+    let owner_key = ctx.accounts.owner.key();
+    ctx.accounts.deposit_receipt.record(amm_key, owner_key)?;
+    emit!(DepositEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositAllNative<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [b"deposit".as_ref(),amm.key().as_ref(),owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + size_of::<DepositReceipt>()
+    )]
+    pub deposit_receipt: Box<Account<'info, DepositReceipt>>,
+    #[account(address = spl_token::native_mint::id())]
+    pub native_mint: Box<Account<'info, Mint>>,
+    // temporary wrapped SOL account, closed before the instruction returns
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"wsol".as_ref(), owner.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = owner
+    )]
+    pub wsol_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        has_one = owner
+    )]
+    pub user_token: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        token::mint = pool_mint,
+        token::authority = owner
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DepositAllNative<'info> {
+    fn into_transfer_to_token_a_context(
+        &self,
+        native_is_token_a: bool,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let source = if native_is_token_a {
+            self.wsol_account.to_account_info()
+        } else {
+            self.user_token.to_account_info()
+        };
+        let cpi_accounts = Transfer {
+            from: source,
+            to: self.vault_token_a.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_token_b_context(
+        &self,
+        native_is_token_a: bool,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let source = if native_is_token_a {
+            self.user_token.to_account_info()
+        } else {
+            self.wsol_account.to_account_info()
+        };
+        let cpi_accounts = Transfer {
+            from: source,
+            to: self.vault_token_b.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
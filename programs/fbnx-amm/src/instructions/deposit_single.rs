@@ -1,19 +1,13 @@
 use anchor_lang::prelude::*;
 //use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+use std::mem::size_of;
 
 use crate::utils::*;
-use crate::{curve::{
-    base::{SwapCurve,CurveType},
-    calculator::{CurveCalculator, TradeDirection},
-    fees::CurveFees,
-}, state::*};
+use crate::{curve::calculator::TradeDirection, state::*};
 
 use crate::error::SwapError;
-
-use crate::curve::{
-     constant_product::ConstantProductCurve,
-};
+use crate::events::*;
 
 pub fn handler(
     ctx: Context<DepositSingleTokenType>,
@@ -22,8 +16,9 @@ pub fn handler(
 ) -> Result<()> {
     let amm = &mut ctx.accounts.amm;
 
-    let curve = build_curve(&amm.curve).unwrap();
-    let fees = build_fees(&amm.fees).unwrap();
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
 
     let trade_direction = if ctx.accounts.source.mint == ctx.accounts.swap_token_a.mint {
         TradeDirection::AtoB
@@ -33,44 +28,29 @@ pub fn handler(
         return Err(SwapError::IncorrectSwapAccount.into());
     };
 
-    let source = ctx.accounts.source.to_account_info().clone();
-    let (source_a_info, source_b_info) = match trade_direction {
-        TradeDirection::AtoB => (Some(&source), None),
-        TradeDirection::BtoA => (None, Some(&source)),
-    };
-
-    check_accounts(
-        amm,
-        ctx.program_id,
-        &amm.to_account_info(),
-        &ctx.accounts.authority,
-        &ctx.accounts.swap_token_a.to_account_info(),
-        &ctx.accounts.swap_token_b.to_account_info(),
-        &ctx.accounts.pool_mint.to_account_info(),
-        &ctx.accounts.token_program,
-        source_a_info,
-        source_b_info,
-        None,
-    )?;
-
-    let pool_mint_supply = u128::try_from(ctx.accounts.pool_mint.supply).unwrap();
-    let pool_token_amount = if pool_mint_supply > 0 {
-        curve
-            .deposit_single_token_type(
-                u128::try_from(source_token_amount).unwrap(),
-                u128::try_from(ctx.accounts.swap_token_a.amount).unwrap(),
-                u128::try_from(ctx.accounts.swap_token_b.amount).unwrap(),
-                pool_mint_supply,
-                trade_direction,
-                &fees,
-            )
-            .ok_or(SwapError::ZeroTradingTokens)?
-    } else {
-        curve.calculator.new_pool_supply()
-    };
+    check_allowlist(amm, ctx.program_id, ctx.accounts.owner.key, ctx.remaining_accounts)?;
+    amm.check_not_paused()?;
 
-    let seeds = &[&amm.to_account_info().key.to_bytes(), &[amm.bump_seed][..]];
-    let pool_token_amount = u64::try_from(pool_token_amount).unwrap();
+    // The locked minimum liquidity keeps the supply above zero once the pool
+    // is initialized, so a zero supply cannot be re-priced by a deposit.
+    let pool_mint_supply = u128::from(ctx.accounts.pool_mint.supply);
+    if pool_mint_supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+    let pool_token_amount = curve
+        .deposit_single_token_type(
+            u128::from(source_token_amount),
+            u128::from(amm.reserve_a),
+            u128::from(amm.reserve_b),
+            pool_mint_supply,
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+    let pool_token_amount = u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?;
     if pool_token_amount < minimum_pool_token_amount {
         return Err(SwapError::ExceededSlippage.into());
     }
@@ -99,6 +79,34 @@ pub fn handler(
         pool_token_amount,
     )?;
 
+    match trade_direction {
+        TradeDirection::AtoB => ctx.accounts.amm.credit_reserves(source_token_amount, 0)?,
+        TradeDirection::BtoA => ctx.accounts.amm.credit_reserves(0, source_token_amount)?,
+    }
+    let (token_a_amount, token_b_amount) = match trade_direction {
+        TradeDirection::AtoB => (source_token_amount, 0),
+        TradeDirection::BtoA => (0, source_token_amount),
+    };
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(&share_value, ctx.accounts.pool_mint.supply, 0)?;
+    ctx.accounts.amm.check_deposit_caps(ctx.accounts.pool_mint.supply)?;
+    let amm_key = ctx.accounts.amm.key();
+    let owner_key = ctx.accounts.owner.key();
+    ctx.accounts.deposit_receipt.record(amm_key, owner_key)?;
+    ctx.accounts.amm.check_price_band()?;
+    ctx.accounts.amm.check_circuit_breaker()?;
+    emit!(DepositEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
     Ok(())
 }
 
@@ -106,8 +114,9 @@ pub fn handler(
 pub struct DepositSingleTokenType<'info> {
     #[account(
         mut,
-        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_account.as_ref()],
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
         bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
      )]
     pub amm: Box<Account<'info, Amm>>,
     /// CHECK: Safe
@@ -115,21 +124,23 @@ pub struct DepositSingleTokenType<'info> {
     pub authority: AccountInfo<'info>,
     #[account(mut)]
     pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [b"deposit".as_ref(),amm.key().as_ref(),owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + size_of::<DepositReceipt>()
+    )]
+    pub deposit_receipt: Box<Account<'info, DepositReceipt>>,
     #[account(mut,
         has_one = owner
     )]
     pub source: Account<'info, TokenAccount>,
-    #[account(mut,
-        constraint = swap_token_a.owner == authority.key(),
-    )]
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
     pub swap_token_a: Account<'info, TokenAccount>,
-    #[account(mut,
-        constraint = swap_token_b.owner == authority.key(),
-    )]
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
     pub swap_token_b: Account<'info, TokenAccount>,
-    #[account(mut,
-        constraint = destination.mint == pool_mint.key(),
-    )]
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
     pub pool_mint: Account<'info, Mint>,
     /// CHECK: Safe
     #[account(
@@ -145,7 +156,7 @@ pub struct DepositSingleTokenType<'info> {
 }
 
 impl<'info> DepositSingleTokenType<'info> {
-    fn into_transfer_to_token_a_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+    pub(crate) fn into_transfer_to_token_a_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
             from: self.source.to_account_info().clone(),
             to: self.swap_token_a.to_account_info().clone(),
@@ -154,7 +165,7 @@ impl<'info> DepositSingleTokenType<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 
-    fn into_transfer_to_token_b_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+    pub(crate) fn into_transfer_to_token_b_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
             from: self.source.to_account_info().clone(),
             to: self.swap_token_b.to_account_info().clone(),
@@ -163,7 +174,7 @@ impl<'info> DepositSingleTokenType<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 
-    fn into_mint_to_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+    pub(crate) fn into_mint_to_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
         let cpi_accounts = MintTo {
             mint: self.pool_mint.to_account_info().clone(),
             to: self.destination.to_account_info().clone(),
@@ -172,38 +183,3 @@ impl<'info> DepositSingleTokenType<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 }
-/// Build Curve object and Fee object
-pub fn build_curve(curve_input: &CurveInput) -> Result<SwapCurve> {
-    let curve_type = CurveType::try_from(curve_input.curve_type).unwrap();
-    let culculator: Box<dyn CurveCalculator> = match curve_type {
-        CurveType::ConstantProduct => Box::new(ConstantProductCurve {}),
-        // CurveType::ConstantPrice => Box::new(ConstantPriceCurve {
-        //     token_b_price: curve_input.curve_parameters,
-        // }),
-        // CurveType::Stable => Box::new(StableCurve {
-        //     amp: curve_input.curve_parameters,
-        // }),
-        // CurveType::Offset => Box::new(OffsetCurve {
-        //     token_b_offset: curve_input.curve_parameters,
-        // }),
-    };
-    let curve = SwapCurve {
-        curve_type: curve_type,
-        calculator: culculator,
-    };
-    Ok(curve)
-}
-
-pub fn build_fees(fees_input: &FeesInput) -> Result<CurveFees> {
-    let fees = CurveFees {
-        trade_fee_numerator: fees_input.trade_fee_numerator,
-        trade_fee_denominator: fees_input.trade_fee_denominator,
-        owner_trade_fee_numerator: fees_input.owner_trade_fee_numerator,
-        owner_trade_fee_denominator: fees_input.owner_trade_fee_denominator,
-        owner_withdraw_fee_numerator: fees_input.owner_withdraw_fee_numerator,
-        owner_withdraw_fee_denominator: fees_input.owner_withdraw_fee_denominator,
-        host_fee_numerator: fees_input.host_fee_numerator,
-        host_fee_denominator: fees_input.host_fee_denominator,
-    };
-    Ok(fees)
-}
\ No newline at end of file
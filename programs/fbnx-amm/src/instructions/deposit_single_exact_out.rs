@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::curve::calculator::TradeDirection;
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+use crate::utils::*;
+use crate::instructions::deposit_single::DepositSingleTokenType;
+
+/// Mint an exact amount of pool tokens in exchange for a single token type,
+/// capping the amount of source token taken.  Uses the same accounts as
+/// `deposit_single`.
+pub fn handler(
+    ctx: Context<DepositSingleTokenType>,
+    pool_token_amount: u64,
+    maximum_source_token_amount: u64,
+) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
+    if !curve.calculator.allows_deposits() {
+        return Err(SwapError::UnsupportedCurveOperation.into());
+    }
+
+    let trade_direction = if ctx.accounts.source.mint == ctx.accounts.swap_token_a.mint {
+        TradeDirection::AtoB
+    } else if ctx.accounts.source.mint == ctx.accounts.swap_token_b.mint {
+        TradeDirection::BtoA
+    } else {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    };
+
+    check_allowlist(amm, ctx.program_id, ctx.accounts.owner.key, ctx.remaining_accounts)?;
+    amm.check_not_paused()?;
+
+    let pool_mint_supply = u128::from(ctx.accounts.pool_mint.supply);
+    if pool_mint_supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+    if pool_token_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let source_token_amount = curve
+        .deposit_single_token_type_exact_out(
+            u128::from(pool_token_amount),
+            u128::from(amm.reserve_a),
+            u128::from(amm.reserve_b),
+            pool_mint_supply,
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+    let source_token_amount =
+        u64::try_from(source_token_amount).map_err(|_| SwapError::ConversionFailure)?;
+    if source_token_amount > maximum_source_token_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if source_token_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    match trade_direction {
+        TradeDirection::AtoB => {
+            token::transfer(
+                ctx.accounts.into_transfer_to_token_a_context(),
+                source_token_amount,
+            )?;
+        }
+        TradeDirection::BtoA => {
+            token::transfer(
+                ctx.accounts.into_transfer_to_token_b_context(),
+                source_token_amount,
+            )?;
+        }
+    }
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_context()
+            .with_signer(&[&seeds[..]]),
+        pool_token_amount,
+    )?;
+
+    match trade_direction {
+        TradeDirection::AtoB => ctx.accounts.amm.credit_reserves(source_token_amount, 0)?,
+        TradeDirection::BtoA => ctx.accounts.amm.credit_reserves(0, source_token_amount)?,
+    }
+    let (token_a_amount, token_b_amount) = match trade_direction {
+        TradeDirection::AtoB => (source_token_amount, 0),
+        TradeDirection::BtoA => (0, source_token_amount),
+    };
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(&share_value, ctx.accounts.pool_mint.supply, 0)?;
+    ctx.accounts.amm.check_deposit_caps(ctx.accounts.pool_mint.supply)?;
+    let amm_key = ctx.accounts.amm.key();
+    let owner_key = ctx.accounts.owner.key();
+    ctx.accounts.deposit_receipt.record(amm_key, owner_key)?;
+    ctx.accounts.amm.check_price_band()?;
+    ctx.accounts.amm.check_circuit_breaker()?;
+    emit!(DepositEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
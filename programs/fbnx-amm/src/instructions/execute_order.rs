@@ -0,0 +1,249 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, MintTo, TokenAccount, Transfer, Token};
+
+use crate::curve::calculator::TradeDirection;
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Execute a limit order against the pool's reserves, failing unless the swap
+/// gives at least the order's `minimum_amount_out`.  The keeper receives the
+/// order account's lamports, i.e. its bounty and rent, while the escrow's rent
+/// goes back to the owner.
+pub fn handler(ctx: Context<ExecuteOrder>) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+    let order = &ctx.accounts.order;
+
+    if order.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(SwapError::OrderExpired.into());
+    }
+    check_allowlist(amm, ctx.program_id, &order.owner, ctx.remaining_accounts)?;
+    amm.check_not_paused()?;
+
+    let trade_direction = if order.source_mint == amm.token_a_mint {
+        TradeDirection::AtoB
+    } else {
+        TradeDirection::BtoA
+    };
+
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
+
+    let (swap_source_amount, swap_destination_amount) = match trade_direction {
+        TradeDirection::AtoB => (amm.reserve_a, amm.reserve_b),
+        TradeDirection::BtoA => (amm.reserve_b, amm.reserve_a),
+    };
+    let result = curve
+        .swap(
+            u128::from(order.amount_in),
+            u128::from(swap_source_amount),
+            u128::from(swap_destination_amount),
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+    if result.destination_amount_swapped < u128::from(order.minimum_amount_out) {
+        return Err(SwapError::OrderPriceNotMet.into());
+    }
+
+    let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+        TradeDirection::AtoB => (
+            result.new_swap_source_amount,
+            result.new_swap_destination_amount,
+        ),
+        TradeDirection::BtoA => (
+            result.new_swap_destination_amount,
+            result.new_swap_source_amount,
+        ),
+    };
+    let pool_token_amount = curve
+        .withdraw_single_token_type_exact_out(
+            result.owner_fee,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            u128::from(ctx.accounts.pool_mint.supply),
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::FeeCalculationFailure)?;
+    // the owner fee is minted rounded up
+    let rounded_up_pool_tokens = u64::from(pool_token_amount > 0);
+
+    let amount_in = order.amount_in;
+    let amount_out = u64::try_from(result.destination_amount_swapped)
+        .map_err(|_| SwapError::ConversionFailure)?;
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    token::transfer(
+        ctx.accounts
+            .into_transfer_to_swap_source_context(trade_direction)
+            .with_signer(&[&seeds[..]]),
+        amount_in,
+    )?;
+    if pool_token_amount > 0 {
+        token::mint_to(
+            ctx.accounts
+                .into_mint_to_pool_context()
+                .with_signer(&[&seeds[..]]),
+            u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+        )?;
+    }
+    token::transfer(
+        ctx.accounts
+            .into_transfer_to_destination_context(trade_direction)
+            .with_signer(&[&seeds[..]]),
+        amount_out,
+    )?;
+    token::close_account(
+        ctx.accounts
+            .into_close_escrow_context()
+            .with_signer(&[&seeds[..]]),
+    )?;
+
+    match trade_direction {
+        TradeDirection::AtoB => {
+            ctx.accounts.amm.credit_reserves(amount_in, 0)?;
+            ctx.accounts.amm.debit_reserves(0, amount_out)?;
+        }
+        TradeDirection::BtoA => {
+            ctx.accounts.amm.credit_reserves(0, amount_in)?;
+            ctx.accounts.amm.debit_reserves(amount_out, 0)?;
+        }
+    }
+    ctx.accounts.amm.check_price_band()?;
+    ctx.accounts.amm.check_circuit_breaker()?;
+    ctx.accounts
+        .amm
+        .stats
+        .record_swap(trade_direction, &result, 0, Clock::get()?.slot);
+    let (source_mint, destination_mint) = match trade_direction {
+        TradeDirection::AtoB => (ctx.accounts.amm.token_a_mint, ctx.accounts.amm.token_b_mint),
+        TradeDirection::BtoA => (ctx.accounts.amm.token_b_mint, ctx.accounts.amm.token_a_mint),
+    };
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(
+        &share_value,
+        ctx.accounts.pool_mint.supply,
+        rounded_up_pool_tokens,
+    )?;
+    emit!(SwapEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.order.owner,
+        source_mint,
+        destination_mint,
+        amount_in,
+        amount_out,
+        trade_fee: u64::try_from(result.trade_fee).map_err(|_| SwapError::ConversionFailure)?,
+        owner_fee: u64::try_from(result.owner_fee).map_err(|_| SwapError::ConversionFailure)?,
+        host_fee: 0,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+    emit!(ExecuteOrderEvent {
+        order: ctx.accounts.order.key(),
+        owner: ctx.accounts.order.owner,
+        keeper: ctx.accounts.keeper.key(),
+        amount_out,
+        bounty: ctx.accounts.order.bounty,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// CHECK: order owner, receiving the escrow's rent
+    #[account(mut, address = order.owner)]
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        has_one = amm,
+        has_one = escrow,
+        has_one = destination,
+        close = keeper
+    )]
+    pub order: Box<Account<'info, Order>>,
+    #[account(mut)]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub swap_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub swap_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Safe
+    #[account(mut, address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
+    pub fee_account: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ExecuteOrder<'info> {
+    fn into_transfer_to_swap_source_context(
+        &self,
+        trade_direction: TradeDirection,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let swap_source = match trade_direction {
+            TradeDirection::AtoB => self.swap_token_a.to_account_info(),
+            TradeDirection::BtoA => self.swap_token_b.to_account_info(),
+        };
+        let cpi_accounts = Transfer {
+            from: self.escrow.to_account_info().clone(),
+            to: swap_source,
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_destination_context(
+        &self,
+        trade_direction: TradeDirection,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let swap_destination = match trade_direction {
+            TradeDirection::AtoB => self.swap_token_b.to_account_info(),
+            TradeDirection::BtoA => self.swap_token_a.to_account_info(),
+        };
+        let cpi_accounts = Transfer {
+            from: swap_destination,
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_pool_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.fee_account.clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_close_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.escrow.to_account_info().clone(),
+            destination: self.owner.clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Add rewards to a farm.  Anyone can fund a farm.
+pub fn handler(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+    token::transfer(ctx.accounts.into_transfer_to_reward_vault_context(), amount)?;
+
+    let farm = &mut ctx.accounts.farm;
+    farm.update(Clock::get()?.unix_timestamp)?;
+    farm.reward_remaining = farm
+        .reward_remaining
+        .checked_add(amount)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    emit!(FundRewardsEvent {
+        farm: farm.key(),
+        funder: ctx.accounts.funder.key(),
+        amount,
+        reward_remaining: farm.reward_remaining,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"farm".as_ref(),farm.amm.as_ref(),farm.reward_mint.as_ref()],
+        bump = farm.bump_seed,
+        has_one = reward_vault,
+     )]
+    pub farm: Box<Account<'info, Farm>>,
+    pub funder: Signer<'info>,
+    #[account(mut,
+        constraint = source.owner == funder.key(),
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FundRewards<'info> {
+    fn into_transfer_to_reward_vault_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.reward_vault.to_account_info().clone(),
+            authority: self.funder.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+
+use crate::curve::calculator::MINIMUM_LIQUIDITY;
+use crate::curve::stable_swap::{self, MAX_AMP, MAX_TOKENS_IN_MULTI_POOL};
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Create a StableSwap pool of 2 to 8 tokens, seeded with `initial_amounts`.
+/// For each token, the remaining accounts hold its mint, its vault and the
+/// payer's account funding it, in that order.  Vaults must already exist and
+/// be owned by the pool authority.  The pool mint gets `pool_decimals`.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitMultiPool<'info>>,
+    fees_input: FeesInput,
+    amp: u64,
+    _pool_decimals: u8,
+    initial_amounts: Vec<u64>,
+) -> Result<()> {
+    let token_count = initial_amounts.len();
+    if !(2..=MAX_TOKENS_IN_MULTI_POOL).contains(&token_count)
+        || ctx.remaining_accounts.len() != token_count * 3
+    {
+        return Err(SwapError::InvalidTokenCount.into());
+    }
+    if amp == 0 || amp > MAX_AMP {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if *ctx.accounts.pool_authority.key == ctx.accounts.destination.owner {
+        return Err(SwapError::InvalidOutputOwner.into());
+    }
+    if *ctx.accounts.pool_authority.key == ctx.accounts.fee_account.owner {
+        return Err(SwapError::InvalidOutputOwner.into());
+    }
+
+    let fees = build_fees(&fees_input)?;
+    if let Some(swap_constraints) = SWAP_CONSTRAINTS {
+        let owner_key = swap_constraints
+            .owner_key
+            .parse::<Pubkey>()
+            .map_err(|_| SwapError::InvalidOwner)?;
+        if ctx.accounts.fee_account.owner != owner_key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        swap_constraints.validate_fees(&fees)?;
+    }
+    fees.validate()?;
+
+    let mut mints = [Pubkey::default(); MAX_TOKENS_IN_MULTI_POOL];
+    let mut vaults = [Pubkey::default(); MAX_TOKENS_IN_MULTI_POOL];
+    let mut decimals = [0u8; MAX_TOKENS_IN_MULTI_POOL];
+    for (k, accounts) in ctx.remaining_accounts.chunks(3).enumerate() {
+        let mint = Account::<Mint>::try_from(&accounts[0])?;
+        let vault = Account::<TokenAccount>::try_from(&accounts[1])?;
+        if vault.mint != mint.key() {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if vault.owner != *ctx.accounts.pool_authority.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if vault.delegate.is_some() {
+            return Err(SwapError::InvalidDelegate.into());
+        }
+        if vault.close_authority.is_some() {
+            return Err(SwapError::InvalidCloseAuthority.into());
+        }
+        if mints[..k].contains(&mint.key()) {
+            return Err(SwapError::RepeatedMint.into());
+        }
+        if initial_amounts[k] == 0 {
+            return Err(SwapError::EmptySupply.into());
+        }
+        mints[k] = mint.key();
+        vaults[k] = vault.key();
+        decimals[k] = mint.decimals;
+    }
+    let max_decimals = decimals[..token_count].iter().copied().max().unwrap_or(0);
+    let mut precision_multipliers = [0u64; MAX_TOKENS_IN_MULTI_POOL];
+    for k in 0..token_count {
+        precision_multipliers[k] = 10u64
+            .checked_pow(u32::from(max_decimals - decimals[k]))
+            .ok_or(SwapError::CalculationFailure)?;
+    }
+
+    let multi_pool = &mut ctx.accounts.multi_pool;
+    multi_pool.bump_seed = *ctx.bumps.get("pool_authority").ok_or(SwapError::InvalidProgramAddress)?;
+    multi_pool.admin = *ctx.accounts.payer.key;
+    multi_pool.pool_mint = ctx.accounts.pool_mint.key();
+    multi_pool.pool_fee_account = ctx.accounts.fee_account.key();
+    multi_pool.fees = fees_input;
+    multi_pool.amp = amp;
+    multi_pool.token_count = u8::try_from(token_count).map_err(|_| SwapError::ConversionFailure)?;
+    multi_pool.mints = mints;
+    multi_pool.vaults = vaults;
+    multi_pool.reserves[..token_count].copy_from_slice(&initial_amounts);
+    multi_pool.precision_multipliers = precision_multipliers;
+
+    let initial_amount = stable_swap::compute_d(amp, &multi_pool.normalized_reserves()?)
+        .ok_or(SwapError::CalculationFailure)?;
+    let destination_amount = initial_amount
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .filter(|amount| *amount > 0)
+        .ok_or(SwapError::InsufficientInitialLiquidity)?;
+
+    for (k, accounts) in ctx.remaining_accounts.chunks(3).enumerate() {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_vault_context(accounts[2].clone(), accounts[1].clone()),
+            initial_amounts[k],
+        )?;
+    }
+
+    let multi_pool_key = ctx.accounts.multi_pool.key();
+    let seeds = &[
+        b"authority".as_ref(),
+        multi_pool_key.as_ref(),
+        &[ctx.accounts.multi_pool.bump_seed][..],
+    ];
+    // Permanently lock the minimum liquidity in the pool's own account
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_locked_liquidity_context()
+            .with_signer(&[&seeds[..]]),
+        u64::try_from(MINIMUM_LIQUIDITY).map_err(|_| SwapError::ConversionFailure)?,
+    )?;
+    let destination_amount =
+        u64::try_from(destination_amount).map_err(|_| SwapError::ConversionFailure)?;
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_context()
+            .with_signer(&[&seeds[..]]),
+        destination_amount,
+    )?;
+
+    emit!(InitMultiPoolEvent {
+        multi_pool: multi_pool_key,
+        user: ctx.accounts.payer.key(),
+        mints: mints[..token_count].to_vec(),
+        amounts: initial_amounts,
+        pool_token_amount: destination_amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(fees_input: FeesInput, amp: u64, pool_decimals: u8)]
+pub struct InitMultiPool<'info> {
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), multi_pool.key().as_ref()], bump)]
+    pub pool_authority: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MultiPool::LEN
+    )]
+    pub multi_pool: Box<Account<'info, MultiPool>>,
+    #[account(
+        init,
+        seeds = [b"pool_mint".as_ref(),multi_pool.key().as_ref()],
+        bump,
+        payer = payer,
+        mint::decimals = pool_decimals,
+        mint::authority = pool_authority
+    )]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        token::mint = pool_mint,
+        token::authority = payer
+    )]
+    pub fee_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        token::mint = pool_mint,
+        token::authority = payer
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    // pool tokens locked forever, owned by the pool authority
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"locked_liquidity".as_ref(),multi_pool.key().as_ref()],
+        bump,
+        token::mint = pool_mint,
+        token::authority = pool_authority
+    )]
+    pub locked_liquidity: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitMultiPool<'info> {
+    fn into_transfer_to_vault_context(
+        &self,
+        source: AccountInfo<'info>,
+        vault: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: source,
+            to: vault,
+            authority: self.payer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.pool_authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_locked_liquidity_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.locked_liquidity.to_account_info().clone(),
+            authority: self.pool_authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
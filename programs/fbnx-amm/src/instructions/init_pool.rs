@@ -1,22 +1,30 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, MintTo,Token, TokenAccount};
-use std::mem::size_of;
+use anchor_spl::token::{self, Mint, MintTo,Token, TokenAccount, Transfer};
 
 
+use crate::curve::calculator::{MAX_POOL_DECIMALS, MINIMUM_LIQUIDITY};
 use crate:: state::*;
 use crate::utils::*;
 use crate::error::SwapError;
+use crate::events::*;
 
 pub fn handler(
     ctx: Context<Initialize>,
     fees_input: FeesInput,
     curve_input: CurveInput,
+    pool_decimals: u8,
+    initial_token_a_amount: u64,
+    initial_token_b_amount: u64,
+    permissioned: bool,
 ) -> Result<()> {
     if ctx.accounts.amm.is_initialized {
         return Err(SwapError::AlreadyInUse.into());
     }
+    if pool_decimals > MAX_POOL_DECIMALS {
+        return Err(SwapError::InvalidInput.into());
+    }
 
     let (swap_authority, bump_seed) = Pubkey::find_program_address(
          &[b"authority".as_ref(),&ctx.accounts.amm.to_account_info().key.to_bytes()],
@@ -51,10 +59,10 @@ pub fn handler(
         return Err(SwapError::RepeatedMint.into());
     }
 
-    let curve = build_curve(&curve_input).unwrap();
+    let curve = build_curve(&curve_input)?;
     curve
         .calculator
-        .validate_supply(ctx.accounts.vault0.amount, ctx.accounts.vault1.amount)?;
+        .validate_supply(initial_token_a_amount, initial_token_b_amount)?;
     if ctx.accounts.vault0.delegate.is_some() {
         return Err(SwapError::InvalidDelegate.into());
     }
@@ -77,7 +85,7 @@ pub fn handler(
     if *ctx.accounts.pool_mint.to_account_info().key != ctx.accounts.fee_account.mint {
         return Err(SwapError::IncorrectPoolMint.into());
     }
-    let fees = build_fees(&fees_input).unwrap();
+    let fees = build_fees(&fees_input)?;
 
     if let Some(swap_constraints) = SWAP_CONSTRAINTS {
         let owner_key = swap_constraints
@@ -93,16 +101,46 @@ pub fn handler(
     fees.validate()?;
     curve.calculator.validate()?;
 
-    let initial_amount = curve.calculator.new_pool_supply();
+    let initial_amount = curve
+        .calculator
+        .new_pool_supply(
+            u128::from(initial_token_a_amount),
+            u128::from(initial_token_b_amount),
+            ctx.accounts.mint0.decimals,
+            ctx.accounts.mint1.decimals,
+            pool_decimals,
+        )
+        .ok_or(SwapError::CalculationFailure)?;
+    let destination_amount = initial_amount
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .filter(|amount| *amount > 0)
+        .ok_or(SwapError::InsufficientInitialLiquidity)?;
+
+    token::transfer(
+        ctx.accounts.into_transfer_to_vault0_context(),
+        initial_token_a_amount,
+    )?;
+    token::transfer(
+        ctx.accounts.into_transfer_to_vault1_context(),
+        initial_token_b_amount,
+    )?;
 
+    // Permanently lock the minimum liquidity in the pool's own account
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_locked_liquidity_context()
+            .with_signer(&[&seeds[..]]),
+        u64::try_from(MINIMUM_LIQUIDITY).map_err(|_| SwapError::ConversionFailure)?,
+    )?;
     token::mint_to(
         ctx.accounts
             .into_mint_to_context()
             .with_signer(&[&seeds[..]]),
-        u64::try_from(initial_amount).unwrap(),
+        u64::try_from(destination_amount).map_err(|_| SwapError::ConversionFailure)?,
     )?;
 
     let amm = &mut ctx.accounts.amm;
+    amm.version = AMM_VERSION;
     amm.is_initialized = true;
     amm.bump_seed = bump_seed;
     amm.token_program_id = *ctx.accounts.token_program.key;
@@ -114,11 +152,30 @@ pub fn handler(
     amm.pool_fee_account = *ctx.accounts.fee_account.to_account_info().key;
     amm.fees = fees_input;
     amm.curve = curve_input;
+    amm.reserve_a = initial_token_a_amount;
+    amm.reserve_b = initial_token_b_amount;
+    amm.skim_recipient = *ctx.accounts.payer.key;
+    amm.admin = *ctx.accounts.payer.key;
+    amm.permissioned = permissioned;
+    amm.last_price_update_ts = Clock::get()?.unix_timestamp;
+
+    ctx.accounts.pool_mint.reload()?;
+    emit!(InitPoolEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.payer.key(),
+        token_a_mint: ctx.accounts.amm.token_a_mint,
+        token_b_mint: ctx.accounts.amm.token_b_mint,
+        token_a_amount: initial_token_a_amount,
+        token_b_amount: initial_token_b_amount,
+        pool_token_amount: u64::try_from(destination_amount).map_err(|_| SwapError::ConversionFailure)?,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
 
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(fees_input: FeesInput, curve_input: CurveInput, pool_decimals: u8)]
 pub struct Initialize<'info> {
     /// CHECK: Safe
     #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
@@ -128,7 +185,7 @@ pub struct Initialize<'info> {
         seeds = [b"amm".as_ref(),mint0.key().as_ref(),mint1.key().as_ref()],
         bump,
         payer = payer,
-        space =  8 + size_of::<Amm>()        
+        space = 8 + Amm::LEN
     )]
     pub amm: Box<Account<'info, Amm>>,
     // pool mint : used to track relative contribution amount of LPs
@@ -137,7 +194,7 @@ pub struct Initialize<'info> {
         seeds = [b"pool_mint".as_ref(),amm.key().as_ref()],
         bump,  
         payer=payer,
-        mint::decimals = 2,
+        mint::decimals = pool_decimals,
         mint::authority = pool_authority
     )] 
     pub pool_mint: Box<Account<'info, Mint>>,
@@ -174,8 +231,30 @@ pub struct Initialize<'info> {
         token::authority = payer
     )]
     pub destination: Account<'info, TokenAccount>,//Token Associated Token Account for the pool mint
+    // pool tokens locked forever, owned by the pool authority, one account
+    // per pool mint so that `migrate_pool` can carry the lock to a new mint
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"locked_liquidity".as_ref(),amm.key().as_ref(),pool_mint.key().as_ref()],
+        bump,
+        token::mint = pool_mint,
+        token::authority = pool_authority
+    )]
+    pub locked_liquidity: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    // payer's accounts funding the initial liquidity
+    #[account(mut,
+        token::mint = mint0,
+        token::authority = payer
+    )]
+    pub source0: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        token::mint = mint1,
+        token::authority = payer
+    )]
+    pub source1: Box<Account<'info, TokenAccount>>,
     // pool for token_x -> token_y 
     pub mint0: Account<'info, Mint>,
     pub mint1: Account<'info, Mint>,
@@ -186,6 +265,24 @@ pub struct Initialize<'info> {
 }
 
 impl<'info> Initialize<'info> {
+    fn into_transfer_to_vault0_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source0.to_account_info().clone(),
+            to: self.vault0.to_account_info().clone(),
+            authority: self.payer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_vault1_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source1.to_account_info().clone(),
+            to: self.vault1.to_account_info().clone(),
+            authority: self.payer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
     fn into_mint_to_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
         let cpi_accounts = MintTo {
             mint: self.pool_mint.to_account_info().clone(),
@@ -194,4 +291,13 @@ impl<'info> Initialize<'info> {
         };
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
+
+    fn into_mint_to_locked_liquidity_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.locked_liquidity.to_account_info().clone(),
+            authority: self.pool_authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
 }
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, TokenAccount, Transfer, Token};
+use std::mem::size_of;
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Lock pool tokens in an escrow until `unlock_ts`, after which they unlock
+/// linearly until `vesting_end_ts`.  Passing a `vesting_end_ts` of 0 unlocks
+/// everything at `unlock_ts`.  The new lock receipt is owned by the signer.
+pub fn handler(
+    ctx: Context<LockLiquidity>,
+    amount: u64,
+    unlock_ts: i64,
+    vesting_end_ts: i64,
+) -> Result<()> {
+    if amount == 0 {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let vesting_end_ts = if vesting_end_ts == 0 {
+        unlock_ts
+    } else {
+        vesting_end_ts
+    };
+    if vesting_end_ts < unlock_ts {
+        return Err(SwapError::InvalidLockSchedule.into());
+    }
+
+    token::transfer(ctx.accounts.into_transfer_to_escrow_context(), amount)?;
+
+    let lock_receipt = &mut ctx.accounts.lock_receipt;
+    lock_receipt.amm = ctx.accounts.amm.key();
+    lock_receipt.owner = ctx.accounts.owner.key();
+    lock_receipt.pool_mint = ctx.accounts.pool_mint.key();
+    lock_receipt.escrow = ctx.accounts.escrow.key();
+    lock_receipt.amount = amount;
+    lock_receipt.withdrawn_amount = 0;
+    lock_receipt.unlock_ts = unlock_ts;
+    lock_receipt.vesting_end_ts = vesting_end_ts;
+
+    emit!(LockEvent {
+        lock_receipt: lock_receipt.key(),
+        amm: lock_receipt.amm,
+        owner: lock_receipt.owner,
+        amount,
+        unlock_ts,
+        vesting_end_ts,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LockLiquidity<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = pool_mint,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        has_one = owner,
+        constraint = source.mint == pool_mint.key() @ SwapError::IncorrectPoolMint,
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + size_of::<LockReceipt>()
+    )]
+    pub lock_receipt: Box<Account<'info, LockReceipt>>,
+    // locked pool tokens, owned by the pool authority
+    #[account(
+        init,
+        seeds = [b"lock_escrow".as_ref(),lock_receipt.key().as_ref()],
+        bump,
+        payer = owner,
+        token::mint = pool_mint,
+        token::authority = authority
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> LockLiquidity<'info> {
+    fn into_transfer_to_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.escrow.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::state::*;
+use crate::error::SwapError;
+
+/// Rewrite an `Amm` account from the unversioned layout, or an older versioned
+/// one, into the current one, resizing it and topping up its rent from the
+/// payer as needed.  The rewrite only drops unused fields and zeroes new ones,
+/// except that an unversioned pool takes its reserves from its vault balances
+/// and gets the owner of its fee account, who collects its fees, as admin and
+/// skim recipient.  None of that is up to the caller, so anyone can call this.
+/// The pool's empty locked liquidity account is created as well if it has
+/// none, so that the pool can be closed like any other.
+pub fn handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let amm_info = ctx.accounts.amm.to_account_info();
+    if amm_info.owner != ctx.program_id {
+        return Err(ProgramError::IncorrectProgramId.into());
+    }
+
+    let legacy = {
+        let data = amm_info.try_borrow_data()?;
+        if data.len() <= 8 || data[..8] != Amm::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        if data[8] >= AMM_VERSION {
+            return Err(SwapError::UnsupportedAccountVersion.into());
+        }
+        if data[8] == 0 {
+            Some(
+                LegacyAmm::deserialize(&mut &data[8..])
+                    .map_err(|_| ErrorCode::AccountDidNotDeserialize)?,
+            )
+        } else {
+            None
+        }
+    };
+
+    let new_len = 8 + Amm::LEN;
+    let minimum_balance = Rent::get()?.minimum_balance(new_len);
+    if amm_info.lamports() < minimum_balance {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: amm_info.clone(),
+                },
+            ),
+            minimum_balance - amm_info.lamports(),
+        )?;
+    }
+    amm_info.realloc(new_len, true)?;
+
+    let amm = {
+        let mut data = amm_info.try_borrow_mut_data()?;
+        match legacy {
+            Some(legacy) => legacy
+                .upgrade(
+                    ctx.accounts.vault_token_a.amount,
+                    ctx.accounts.vault_token_b.amount,
+                    ctx.accounts.fee_account.owner,
+                )
+                .try_serialize(&mut &mut data[..])?,
+            // versioned layouts only ever gained fields in the zeroed reserved
+            // space and past the end of the account
+            None => data[8] = AMM_VERSION,
+        }
+        Amm::try_deserialize(&mut &data[..])?
+    };
+    let (amm_key, _) = Pubkey::find_program_address(
+        &[b"amm".as_ref(), amm.token_a_mint.as_ref(), amm.token_b_mint.as_ref()],
+        ctx.program_id,
+    );
+    if amm_key != *amm_info.key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if amm.token_a_account != ctx.accounts.vault_token_a.key()
+        || amm.token_b_account != ctx.accounts.vault_token_b.key()
+    {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if amm.pool_mint != ctx.accounts.pool_mint.key() {
+        return Err(SwapError::IncorrectPoolMint.into());
+    }
+    if amm.pool_fee_account != ctx.accounts.fee_account.key() {
+        return Err(SwapError::IncorrectFeeAccount.into());
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: Deserialized manually, since its layout is outdated
+    #[account(mut)]
+    pub amm: UncheckedAccount<'info>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    // checked against the rewritten `Amm` in the handler
+    pub vault_token_a: Box<Account<'info, TokenAccount>>,
+    pub vault_token_b: Box<Account<'info, TokenAccount>>,
+    pub pool_mint: Box<Account<'info, Mint>>,
+    pub fee_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"locked_liquidity".as_ref(),amm.key().as_ref(),pool_mint.key().as_ref()],
+        bump,
+        token::mint = pool_mint,
+        token::authority = authority
+    )]
+    pub locked_liquidity: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
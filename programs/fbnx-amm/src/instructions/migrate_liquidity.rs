@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Exchange pool tokens of a pool's old pool mint for its share of the new pool
+/// tokens minted by `migrate_pool`.
+pub fn handler(ctx: Context<MigrateLiquidity>, pool_token_amount: u64) -> Result<()> {
+    let migration = &ctx.accounts.migration;
+
+    let new_pool_token_amount = u128::from(pool_token_amount)
+        .checked_mul(u128::from(migration.new_supply))
+        .and_then(|x| x.checked_div(u128::from(migration.old_supply)))
+        .ok_or(SwapError::CalculationFailure)?;
+    let new_pool_token_amount =
+        u64::try_from(new_pool_token_amount).map_err(|_| SwapError::ConversionFailure)?;
+    if new_pool_token_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let amm = &ctx.accounts.amm;
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    token::burn(ctx.accounts.into_burn_context(), pool_token_amount)?;
+    token::transfer(
+        ctx.accounts
+            .into_transfer_from_escrow_context()
+            .with_signer(&[&seeds[..]]),
+        new_pool_token_amount,
+    )?;
+
+    emit!(MigrateLiquidityEvent {
+        amm: amm_key,
+        user: ctx.accounts.owner.key(),
+        old_pool_token_amount: pool_token_amount,
+        new_pool_token_amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateLiquidity<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"migration".as_ref(),amm.key().as_ref(),old_pool_mint.key().as_ref()],
+        bump,
+        has_one = amm,
+        has_one = old_pool_mint,
+        has_one = new_pool_mint @ SwapError::IncorrectPoolMint,
+    )]
+    pub migration: Box<Account<'info, Migration>>,
+    #[account(mut)]
+    pub old_pool_mint: Box<Account<'info, Mint>>,
+    pub new_pool_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"migration_escrow".as_ref(),amm.key().as_ref(),old_pool_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        has_one = owner,
+        token::mint = old_pool_mint
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        token::mint = new_pool_mint,
+        token::authority = owner
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateLiquidity<'info> {
+    fn into_burn_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        let cpi_accounts = Burn {
+            mint: self.old_pool_mint.to_account_info().clone(),
+            from: self.source.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_from_escrow_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.escrow.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, TokenAccount, Token};
+use std::mem::size_of;
+use spl_math::precise_number::PreciseNumber;
+
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Move a pool to a new curve.  The vault balances stay with the pool, while
+/// its pool tokens are replaced by a new pool mint, so that every pool token is
+/// worth the same normalized value before and after: the old supply `S` is
+/// exchanged for `S * V' / V` new pool tokens, where `V` and `V'` are the
+/// normalized values of the reserves under the old and the new curve.  The
+/// locked minimum liquidity moves with the pool: its old pool tokens are burned
+/// and their share of the new ones is locked for the new pool mint.  The rest of
+/// the new pool tokens are minted into an escrow, from which
+/// `migrate_liquidity` pays out each holder's share against their burned old
+/// pool tokens.
+pub fn handler(ctx: Context<MigratePool>, curve_input: CurveInput) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+
+    let old_curve = build_curve(&amm.curve)?;
+    let new_curve = build_curve(&curve_input)?;
+    if let Some(swap_constraints) = SWAP_CONSTRAINTS {
+        swap_constraints.validate_curve(&new_curve)?;
+    }
+    new_curve.calculator.validate()?;
+
+    let old_value = old_curve
+        .calculator
+        .normalized_value(u128::from(amm.reserve_a), u128::from(amm.reserve_b))
+        .ok_or(SwapError::CalculationFailure)?;
+    let new_value = new_curve
+        .calculator
+        .normalized_value(u128::from(amm.reserve_a), u128::from(amm.reserve_b))
+        .ok_or(SwapError::CalculationFailure)?;
+    let old_supply = ctx.accounts.pool_mint.supply;
+    let new_supply = PreciseNumber::new(u128::from(old_supply))
+        .and_then(|supply| supply.checked_mul(&new_value))
+        .and_then(|value| value.checked_div(&old_value))
+        .and_then(|supply| supply.floor())
+        .and_then(|supply| supply.to_imprecise())
+        .ok_or(SwapError::CalculationFailure)?;
+    let new_supply = u64::try_from(new_supply).map_err(|_| SwapError::ConversionFailure)?;
+    if new_supply == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    // the locked share rounds down, so holders' escrow never gets less than
+    // their share of the new supply
+    let old_locked_amount = ctx.accounts.locked_liquidity.amount;
+    let new_locked_amount = u128::from(old_locked_amount)
+        .checked_mul(u128::from(new_supply))
+        .and_then(|x| x.checked_div(u128::from(old_supply)))
+        .ok_or(SwapError::CalculationFailure)?;
+    let new_locked_amount =
+        u64::try_from(new_locked_amount).map_err(|_| SwapError::ConversionFailure)?;
+    let escrow_amount = new_supply
+        .checked_sub(new_locked_amount)
+        .ok_or(SwapError::CalculationFailure)?;
+    let holders_supply = old_supply
+        .checked_sub(old_locked_amount)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    if old_locked_amount > 0 {
+        token::burn(
+            ctx.accounts
+                .into_burn_locked_liquidity_context()
+                .with_signer(&[&seeds[..]]),
+            old_locked_amount,
+        )?;
+    }
+    if new_locked_amount > 0 {
+        token::mint_to(
+            ctx.accounts
+                .into_mint_to_new_locked_liquidity_context()
+                .with_signer(&[&seeds[..]]),
+            new_locked_amount,
+        )?;
+    }
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_escrow_context()
+            .with_signer(&[&seeds[..]]),
+        escrow_amount,
+    )?;
+
+    let migration = &mut ctx.accounts.migration;
+    migration.amm = amm_key;
+    migration.old_pool_mint = ctx.accounts.pool_mint.key();
+    migration.new_pool_mint = ctx.accounts.new_pool_mint.key();
+    migration.old_supply = holders_supply;
+    migration.new_supply = escrow_amount;
+
+    let amm = &mut ctx.accounts.amm;
+    amm.curve = curve_input;
+    amm.pool_mint = ctx.accounts.new_pool_mint.key();
+    amm.pool_fee_account = ctx.accounts.new_fee_account.key();
+
+    emit!(MigratePoolEvent {
+        amm: amm_key,
+        old_pool_mint: ctx.accounts.pool_mint.key(),
+        new_pool_mint: ctx.accounts.new_pool_mint.key(),
+        old_supply,
+        new_supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+        has_one = pool_mint @ SwapError::IncorrectPoolMint,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"locked_liquidity".as_ref(),amm.key().as_ref(),pool_mint.key().as_ref()],
+        bump,
+    )]
+    pub locked_liquidity: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [b"pool_mint".as_ref(),amm.key().as_ref(),pool_mint.key().as_ref()],
+        bump,
+        payer = admin,
+        mint::decimals = pool_mint.decimals,
+        mint::authority = authority
+    )]
+    pub new_pool_mint: Box<Account<'info, Mint>>,
+    // the locked minimum liquidity, carried over to the new pool mint
+    #[account(
+        init,
+        seeds = [b"locked_liquidity".as_ref(),amm.key().as_ref(),new_pool_mint.key().as_ref()],
+        bump,
+        payer = admin,
+        token::mint = new_pool_mint,
+        token::authority = authority
+    )]
+    pub new_locked_liquidity: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [b"migration".as_ref(),amm.key().as_ref(),pool_mint.key().as_ref()],
+        bump,
+        payer = admin,
+        space = 8 + size_of::<Migration>()
+    )]
+    pub migration: Box<Account<'info, Migration>>,
+    // new pool tokens owed to holders of the old pool tokens
+    #[account(
+        init,
+        seeds = [b"migration_escrow".as_ref(),amm.key().as_ref(),pool_mint.key().as_ref()],
+        bump,
+        payer = admin,
+        token::mint = new_pool_mint,
+        token::authority = authority
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    #[account(address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
+    pub fee_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: owner of the current fee account, which keeps receiving the fees
+    #[account(address = fee_account.owner)]
+    pub fee_owner: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = admin,
+        token::mint = new_pool_mint,
+        token::authority = fee_owner
+    )]
+    pub new_fee_account: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigratePool<'info> {
+    fn into_burn_locked_liquidity_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        let cpi_accounts = Burn {
+            mint: self.pool_mint.to_account_info().clone(),
+            from: self.locked_liquidity.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_new_locked_liquidity_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.new_pool_mint.to_account_info().clone(),
+            to: self.new_locked_liquidity.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.new_pool_mint.to_account_info().clone(),
+            to: self.escrow.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
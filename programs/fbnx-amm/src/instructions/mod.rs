@@ -1,21 +1,148 @@
+pub mod add_to_allowlist;
+pub use add_to_allowlist::*;
+
+pub mod cancel_order;
+pub use cancel_order::*;
+
+pub mod claim;
+pub use claim::*;
+
+pub mod close_dca;
+pub use close_dca::*;
+
+pub mod close_pool;
+pub use close_pool::*;
+
+pub mod crank_dca;
+pub use crank_dca::*;
+
+pub mod create_dca;
+pub use create_dca::*;
+
+pub mod create_farm;
+pub use create_farm::*;
+
 pub mod deposit_all;
 pub use deposit_all::*;
 
+pub mod deposit_all_native;
+pub use deposit_all_native::*;
+
 pub mod deposit_single;
 pub use deposit_single::*;
 
+pub mod deposit_single_exact_out;
+
+pub mod execute_order;
+pub use execute_order::*;
+
+pub mod fund_rewards;
+pub use fund_rewards::*;
+
+pub mod init_multi_pool;
+pub use init_multi_pool::*;
+
 pub mod init_pool;
 pub use init_pool::*;
 
+pub mod lock_liquidity;
+pub use lock_liquidity::*;
+
+pub mod migrate_account;
+pub use migrate_account::*;
+
+pub mod migrate_liquidity;
+pub use migrate_liquidity::*;
+
+pub mod migrate_pool;
+pub use migrate_pool::*;
+
+pub mod multi_deposit_all;
+pub use multi_deposit_all::*;
+
+pub mod multi_deposit_single;
+pub use multi_deposit_single::*;
+
+pub mod multi_swap;
+pub use multi_swap::*;
+
+pub mod multi_withdraw_all;
+pub use multi_withdraw_all::*;
+
+pub mod multi_withdraw_single;
+pub use multi_withdraw_single::*;
+
+pub mod pause_pool;
+pub use pause_pool::*;
+
+pub mod place_order;
+pub use place_order::*;
+
+pub mod remove_from_allowlist;
+pub use remove_from_allowlist::*;
+
+pub mod set_admin;
+pub use set_admin::*;
+
+pub mod set_circuit_breaker;
+pub use set_circuit_breaker::*;
+
+pub mod set_deposit_caps;
+pub use set_deposit_caps::*;
+
+pub mod set_mev_protection;
+pub use set_mev_protection::*;
+
+pub mod set_pause_duration;
+pub use set_pause_duration::*;
+
+pub mod set_permissioned;
+pub use set_permissioned::*;
+
+pub mod set_skim_recipient;
+pub use set_skim_recipient::*;
+
+pub mod skim;
+pub use skim::*;
+
+pub mod stake;
+pub use stake::*;
+
 pub mod swap;
 pub use swap::*;
 
+pub mod swap_native;
+pub use swap_native::*;
+
+pub mod sync;
+pub use sync::*;
+
+pub mod transfer_lock;
+pub use transfer_lock::*;
+
+pub mod unstake;
+pub use unstake::*;
+
 pub mod withdraw_all;
 pub use withdraw_all::*;
 
+pub mod withdraw_all_native;
+pub use withdraw_all_native::*;
+
+pub mod withdraw_dca;
+pub use withdraw_dca::*;
+
+pub mod withdraw_locked;
+pub use withdraw_locked::*;
+
 pub mod withdraw_single;
 pub use withdraw_single::*;
 
+pub mod withdraw_single_exact_in;
+
+pub mod zap_in;
+pub use zap_in::*;
+
 pub mod utils;
 pub use utils::*;
 
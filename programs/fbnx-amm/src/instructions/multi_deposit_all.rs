@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+
+use crate::curve::calculator::RoundDirection;
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Deposit every token of a multi-asset pool in proportion to its reserves,
+/// for exactly `pool_token_amount` pool tokens.  For each token, the remaining
+/// accounts hold the owner's source account and the pool's vault, in that
+/// order.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, MultiDepositAll<'info>>,
+    pool_token_amount: u64,
+    maximum_amounts: Vec<u64>,
+) -> Result<()> {
+    let multi_pool = &ctx.accounts.multi_pool;
+    let token_count = usize::from(multi_pool.token_count);
+    if maximum_amounts.len() != token_count || ctx.remaining_accounts.len() != token_count * 2 {
+        return Err(SwapError::InvalidTokenCount.into());
+    }
+    let pool_mint_supply = ctx.accounts.pool_mint.supply;
+    let share_value = multi_pool.share_value(pool_mint_supply)?;
+
+    let mut amounts = Vec::with_capacity(token_count);
+    for (k, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+        if *accounts[1].key != multi_pool.vaults[k] {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let amount = multi_pool.proportional_amount(
+            k,
+            pool_token_amount,
+            pool_mint_supply,
+            RoundDirection::Ceiling,
+        )?;
+        if amount > maximum_amounts[k] {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        if amount == 0 {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+        amounts.push(amount);
+    }
+
+    let multi_pool_key = multi_pool.key();
+    let seeds = &[b"authority".as_ref(), multi_pool_key.as_ref(), &[multi_pool.bump_seed][..]];
+
+    for (k, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_vault_context(accounts[0].clone(), accounts[1].clone()),
+            amounts[k],
+        )?;
+    }
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_context()
+            .with_signer(&[&seeds[..]]),
+        pool_token_amount,
+    )?;
+
+    let multi_pool = &mut ctx.accounts.multi_pool;
+    for (k, amount) in amounts.iter().enumerate() {
+        multi_pool.credit_reserve(k, *amount)?;
+    }
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts
+        .multi_pool
+        .check_share_value(&share_value, ctx.accounts.pool_mint.supply)?;
+    emit!(MultiDepositEvent {
+        multi_pool: multi_pool_key,
+        user: ctx.accounts.owner.key(),
+        amounts,
+        pool_token_amount,
+        reserves: ctx.accounts.multi_pool.reserves[..token_count].to_vec(),
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MultiDepositAll<'info> {
+    #[account(mut)]
+    pub multi_pool: Box<Account<'info, MultiPool>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), multi_pool.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    #[account(mut, address = multi_pool.pool_mint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        constraint = destination.mint == pool_mint.key() @ SwapError::IncorrectPoolMint,
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MultiDepositAll<'info> {
+    fn into_transfer_to_vault_context(
+        &self,
+        source: AccountInfo<'info>,
+        vault: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: source,
+            to: vault,
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
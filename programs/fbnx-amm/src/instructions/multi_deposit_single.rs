@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+
+use crate::curve::stable_swap;
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Deposit a single token of a multi-asset pool.  The deposit pays the
+/// imbalance fee it causes, which stays in the pool.
+pub fn handler(
+    ctx: Context<MultiDepositSingle>,
+    source_token_amount: u64,
+    minimum_pool_token_amount: u64,
+) -> Result<()> {
+    let multi_pool = &ctx.accounts.multi_pool;
+    let index = multi_pool.token_index(&ctx.accounts.vault.key())?;
+    let token_count = usize::from(multi_pool.token_count);
+
+    let fees = build_fees(&multi_pool.fees)?;
+    let share_value = multi_pool.share_value(ctx.accounts.pool_mint.supply)?;
+    let mut amounts = vec![0u128; token_count];
+    amounts[index] = multi_pool.normalize(index, source_token_amount)?;
+    let pool_token_amount = stable_swap::deposit(
+        multi_pool.amp,
+        &multi_pool.normalized_reserves()?,
+        &amounts,
+        u128::from(ctx.accounts.pool_mint.supply),
+        u128::from(fees.trade_fee_numerator),
+        u128::from(fees.trade_fee_denominator),
+    )
+    .ok_or(SwapError::ZeroTradingTokens)?;
+    let pool_token_amount =
+        u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?;
+    if pool_token_amount < minimum_pool_token_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if pool_token_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let multi_pool_key = multi_pool.key();
+    let seeds = &[b"authority".as_ref(), multi_pool_key.as_ref(), &[multi_pool.bump_seed][..]];
+
+    token::transfer(ctx.accounts.into_transfer_to_vault_context(), source_token_amount)?;
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_context()
+            .with_signer(&[&seeds[..]]),
+        pool_token_amount,
+    )?;
+
+    ctx.accounts.multi_pool.credit_reserve(index, source_token_amount)?;
+
+    let mut amounts = vec![0u64; token_count];
+    amounts[index] = source_token_amount;
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts
+        .multi_pool
+        .check_share_value(&share_value, ctx.accounts.pool_mint.supply)?;
+    emit!(MultiDepositEvent {
+        multi_pool: multi_pool_key,
+        user: ctx.accounts.owner.key(),
+        amounts,
+        pool_token_amount,
+        reserves: ctx.accounts.multi_pool.reserves[..token_count].to_vec(),
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MultiDepositSingle<'info> {
+    #[account(mut)]
+    pub multi_pool: Box<Account<'info, MultiPool>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), multi_pool.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    #[account(mut, has_one = owner)]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = multi_pool.pool_mint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        constraint = destination.mint == pool_mint.key() @ SwapError::IncorrectPoolMint,
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MultiDepositSingle<'info> {
+    fn into_transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.vault.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+
+use crate::curve::stable_swap;
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Swap between any two tokens of a multi-asset pool.  The owner fee stays in
+/// the pool and is paid out as pool tokens.
+pub fn handler(ctx: Context<MultiSwap>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
+    let multi_pool = &ctx.accounts.multi_pool;
+    let i = multi_pool.token_index(&ctx.accounts.vault_source.key())?;
+    let j = multi_pool.token_index(&ctx.accounts.vault_destination.key())?;
+    if i == j {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if ctx.accounts.source.mint != multi_pool.mints[i]
+        || ctx.accounts.destination.mint != multi_pool.mints[j]
+    {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+
+    let fees = build_fees(&multi_pool.fees)?;
+    let share_value = multi_pool.share_value(ctx.accounts.pool_mint.supply)?;
+    let trade_fee = fees
+        .trading_fee(u128::from(amount_in))
+        .ok_or(SwapError::FeeCalculationFailure)?;
+    let owner_fee = fees
+        .owner_trading_fee(u128::from(amount_in))
+        .ok_or(SwapError::FeeCalculationFailure)?;
+    let trade_fee = u64::try_from(trade_fee).map_err(|_| SwapError::ConversionFailure)?;
+    let owner_fee = u64::try_from(owner_fee).map_err(|_| SwapError::ConversionFailure)?;
+    let amount_in_less_fees = amount_in
+        .checked_sub(trade_fee)
+        .and_then(|x| x.checked_sub(owner_fee))
+        .ok_or(SwapError::FeeCalculationFailure)?;
+
+    let balances = multi_pool.normalized_reserves()?;
+    let amount_out = stable_swap::swap_without_fees(
+        multi_pool.amp,
+        &balances,
+        i,
+        j,
+        multi_pool.normalize(i, amount_in_less_fees)?,
+    )
+    .ok_or(SwapError::ZeroTradingTokens)?;
+    let amount_out = multi_pool.denormalize(j, amount_out)?;
+    if amount_out < minimum_amount_out {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if amount_out == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let mut new_balances = balances;
+    new_balances[i] = new_balances[i]
+        .checked_add(multi_pool.normalize(i, amount_in)?)
+        .ok_or(SwapError::CalculationFailure)?;
+    new_balances[j] = new_balances[j]
+        .checked_sub(multi_pool.normalize(j, amount_out)?)
+        .ok_or(SwapError::CalculationFailure)?;
+    let pool_token_amount = stable_swap::fee_to_pool_tokens(
+        multi_pool.amp,
+        &new_balances,
+        i,
+        multi_pool.normalize(i, owner_fee)?,
+        u128::from(ctx.accounts.pool_mint.supply),
+    )
+    .ok_or(SwapError::FeeCalculationFailure)?;
+
+    let multi_pool_key = multi_pool.key();
+    let seeds = &[b"authority".as_ref(), multi_pool_key.as_ref(), &[multi_pool.bump_seed][..]];
+
+    token::transfer(ctx.accounts.into_transfer_to_vault_context(), amount_in)?;
+    if pool_token_amount > 0 {
+        token::mint_to(
+            ctx.accounts
+                .into_mint_to_pool_context()
+                .with_signer(&[&seeds[..]]),
+            u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+        )?;
+    }
+    token::transfer(
+        ctx.accounts
+            .into_transfer_to_destination_context()
+            .with_signer(&[&seeds[..]]),
+        amount_out,
+    )?;
+
+    let multi_pool = &mut ctx.accounts.multi_pool;
+    multi_pool.credit_reserve(i, amount_in)?;
+    multi_pool.debit_reserve(j, amount_out)?;
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts
+        .multi_pool
+        .check_share_value(&share_value, ctx.accounts.pool_mint.supply)?;
+    let multi_pool = &ctx.accounts.multi_pool;
+    emit!(MultiSwapEvent {
+        multi_pool: multi_pool.key(),
+        user: ctx.accounts.owner.key(),
+        source_mint: multi_pool.mints[i],
+        destination_mint: multi_pool.mints[j],
+        amount_in,
+        amount_out,
+        trade_fee,
+        owner_fee,
+        reserves: multi_pool.reserves[..usize::from(multi_pool.token_count)].to_vec(),
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MultiSwap<'info> {
+    #[account(mut)]
+    pub multi_pool: Box<Account<'info, MultiPool>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), multi_pool.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    #[account(mut, has_one = owner)]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault_source: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault_destination: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = multi_pool.pool_mint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Safe
+    #[account(mut, address = multi_pool.pool_fee_account)]
+    pub fee_account: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MultiSwap<'info> {
+    fn into_transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.vault_source.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_destination_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_destination.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_pool_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.fee_account.clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, TokenAccount, Transfer, Token};
+
+use crate::curve::calculator::RoundDirection;
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Burn `pool_token_amount` pool tokens for every token of a multi-asset pool
+/// in proportion to its reserves.  For each token, the remaining accounts hold
+/// the pool's vault and the owner's destination account, in that order.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, MultiWithdrawAll<'info>>,
+    pool_token_amount: u64,
+    minimum_amounts: Vec<u64>,
+) -> Result<()> {
+    let multi_pool = &ctx.accounts.multi_pool;
+    let token_count = usize::from(multi_pool.token_count);
+    if minimum_amounts.len() != token_count || ctx.remaining_accounts.len() != token_count * 2 {
+        return Err(SwapError::InvalidTokenCount.into());
+    }
+
+    let fees = build_fees(&multi_pool.fees)?;
+    let share_value = multi_pool.share_value(ctx.accounts.pool_mint.supply)?;
+    let withdraw_fee = if *ctx.accounts.fee_account.key == ctx.accounts.source.key() {
+        // withdrawing from the fee account, don't assess withdraw fee
+        0
+    } else {
+        let withdraw_fee = fees
+            .owner_withdraw_fee(u128::from(pool_token_amount))
+            .ok_or(SwapError::FeeCalculationFailure)?;
+        u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?
+    };
+    let pool_token_amount = pool_token_amount
+        .checked_sub(withdraw_fee)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let mut amounts = Vec::with_capacity(token_count);
+    for (k, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+        if *accounts[0].key != multi_pool.vaults[k] {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let amount = multi_pool.proportional_amount(
+            k,
+            pool_token_amount,
+            ctx.accounts.pool_mint.supply,
+            RoundDirection::Floor,
+        )?;
+        if amount < minimum_amounts[k] {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        amounts.push(amount);
+    }
+
+    let multi_pool_key = multi_pool.key();
+    let seeds = &[b"authority".as_ref(), multi_pool_key.as_ref(), &[multi_pool.bump_seed][..]];
+
+    if withdraw_fee > 0 {
+        token::transfer(ctx.accounts.into_transfer_to_fee_account_context(), withdraw_fee)?;
+    }
+    token::burn(ctx.accounts.into_burn_context(), pool_token_amount)?;
+    for (k, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+        if amounts[k] > 0 {
+            token::transfer(
+                ctx.accounts
+                    .into_transfer_from_vault_context(accounts[0].clone(), accounts[1].clone())
+                    .with_signer(&[&seeds[..]]),
+                amounts[k],
+            )?;
+        }
+    }
+
+    let multi_pool = &mut ctx.accounts.multi_pool;
+    for (k, amount) in amounts.iter().enumerate() {
+        multi_pool.debit_reserve(k, *amount)?;
+    }
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts
+        .multi_pool
+        .check_share_value(&share_value, ctx.accounts.pool_mint.supply)?;
+    emit!(MultiWithdrawEvent {
+        multi_pool: multi_pool_key,
+        user: ctx.accounts.owner.key(),
+        amounts,
+        pool_token_amount,
+        withdraw_fee,
+        reserves: ctx.accounts.multi_pool.reserves[..token_count].to_vec(),
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MultiWithdrawAll<'info> {
+    #[account(mut)]
+    pub multi_pool: Box<Account<'info, MultiPool>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), multi_pool.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    #[account(mut,
+        constraint = source.mint == pool_mint.key() @ SwapError::IncorrectPoolMint,
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = multi_pool.pool_mint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Safe
+    #[account(mut, address = multi_pool.pool_fee_account)]
+    pub fee_account: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MultiWithdrawAll<'info> {
+    fn into_transfer_to_fee_account_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.fee_account.clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_burn_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        let cpi_accounts = Burn {
+            mint: self.pool_mint.to_account_info().clone(),
+            from: self.source.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_from_vault_context(
+        &self,
+        vault: AccountInfo<'info>,
+        destination: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: vault,
+            to: destination,
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
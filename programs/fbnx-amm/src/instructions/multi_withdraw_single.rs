@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, TokenAccount, Transfer, Token};
+
+use crate::curve::stable_swap;
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Burn `pool_token_amount` pool tokens for a single token of a multi-asset
+/// pool.  The withdrawal pays the imbalance fee it causes, which stays in the
+/// pool.
+pub fn handler(
+    ctx: Context<MultiWithdrawSingle>,
+    pool_token_amount: u64,
+    minimum_token_amount: u64,
+) -> Result<()> {
+    let multi_pool = &ctx.accounts.multi_pool;
+    let index = multi_pool.token_index(&ctx.accounts.vault.key())?;
+    let token_count = usize::from(multi_pool.token_count);
+
+    let fees = build_fees(&multi_pool.fees)?;
+    let share_value = multi_pool.share_value(ctx.accounts.pool_mint.supply)?;
+    let withdraw_fee = if *ctx.accounts.fee_account.key == ctx.accounts.source.key() {
+        // withdrawing from the fee account, don't assess withdraw fee
+        0
+    } else {
+        let withdraw_fee = fees
+            .owner_withdraw_fee(u128::from(pool_token_amount))
+            .ok_or(SwapError::FeeCalculationFailure)?;
+        u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?
+    };
+    let pool_token_amount = pool_token_amount
+        .checked_sub(withdraw_fee)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let token_amount = stable_swap::withdraw_one(
+        multi_pool.amp,
+        &multi_pool.normalized_reserves()?,
+        u128::from(pool_token_amount),
+        u128::from(ctx.accounts.pool_mint.supply),
+        index,
+        u128::from(fees.trade_fee_numerator),
+        u128::from(fees.trade_fee_denominator),
+    )
+    .ok_or(SwapError::ZeroTradingTokens)?;
+    let token_amount = std::cmp::min(
+        multi_pool.reserves[index],
+        multi_pool.denormalize(index, token_amount)?,
+    );
+    if token_amount < minimum_token_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if token_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let multi_pool_key = multi_pool.key();
+    let seeds = &[b"authority".as_ref(), multi_pool_key.as_ref(), &[multi_pool.bump_seed][..]];
+
+    if withdraw_fee > 0 {
+        token::transfer(ctx.accounts.into_transfer_to_fee_account_context(), withdraw_fee)?;
+    }
+    token::burn(ctx.accounts.into_burn_context(), pool_token_amount)?;
+    token::transfer(
+        ctx.accounts
+            .into_transfer_from_vault_context()
+            .with_signer(&[&seeds[..]]),
+        token_amount,
+    )?;
+
+    ctx.accounts.multi_pool.debit_reserve(index, token_amount)?;
+
+    let mut amounts = vec![0u64; token_count];
+    amounts[index] = token_amount;
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts
+        .multi_pool
+        .check_share_value(&share_value, ctx.accounts.pool_mint.supply)?;
+    emit!(MultiWithdrawEvent {
+        multi_pool: multi_pool_key,
+        user: ctx.accounts.owner.key(),
+        amounts,
+        pool_token_amount,
+        withdraw_fee,
+        reserves: ctx.accounts.multi_pool.reserves[..token_count].to_vec(),
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MultiWithdrawSingle<'info> {
+    #[account(mut)]
+    pub multi_pool: Box<Account<'info, MultiPool>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), multi_pool.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    #[account(mut,
+        constraint = source.mint == pool_mint.key() @ SwapError::IncorrectPoolMint,
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = multi_pool.pool_mint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Safe
+    #[account(mut, address = multi_pool.pool_fee_account)]
+    pub fee_account: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MultiWithdrawSingle<'info> {
+    fn into_transfer_to_fee_account_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.fee_account.clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_burn_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        let cpi_accounts = Burn {
+            mint: self.pool_mint.to_account_info().clone(),
+            from: self.source.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_from_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
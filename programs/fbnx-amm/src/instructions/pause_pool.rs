@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Reject trades for the next `pause_duration` seconds, e.g. while a paired
+/// token is being exploited.  Calling it again restarts the pause, so a
+/// duration of 0 lifts it.
+pub fn handler(ctx: Context<PausePool>) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.paused_until = Clock::get()?
+        .unix_timestamp
+        .checked_add(amm.pause_duration)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    emit!(PausePoolEvent {
+        amm: amm.key(),
+        paused_until: amm.paused_until,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PausePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Mint, TokenAccount, Transfer, Token};
+use std::mem::size_of;
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Escrow `amount_in` of one of the pool's tokens as a limit order, to be
+/// swapped by a keeper for at least `minimum_amount_out` into `destination`.
+/// `bounty` lamports are held by the order for the executing keeper.  An
+/// `expiry_ts` of 0 never expires.
+pub fn handler(
+    ctx: Context<PlaceOrder>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    bounty: u64,
+    expiry_ts: i64,
+) -> Result<()> {
+    if amount_in == 0 || minimum_amount_out == 0 {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let amm = &ctx.accounts.amm;
+    let source_mint = ctx.accounts.source_mint.key();
+    let destination_mint = if source_mint == amm.token_a_mint {
+        amm.token_b_mint
+    } else if source_mint == amm.token_b_mint {
+        amm.token_a_mint
+    } else {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    };
+    if ctx.accounts.destination.mint != destination_mint {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if expiry_ts != 0 && expiry_ts <= Clock::get()?.unix_timestamp {
+        return Err(SwapError::OrderExpired.into());
+    }
+
+    token::transfer(ctx.accounts.into_transfer_to_escrow_context(), amount_in)?;
+    if bounty > 0 {
+        system_program::transfer(ctx.accounts.into_transfer_bounty_context(), bounty)?;
+    }
+
+    let order = &mut ctx.accounts.order;
+    order.amm = ctx.accounts.amm.key();
+    order.owner = ctx.accounts.owner.key();
+    order.source_mint = source_mint;
+    order.escrow = ctx.accounts.escrow.key();
+    order.destination = ctx.accounts.destination.key();
+    order.amount_in = amount_in;
+    order.minimum_amount_out = minimum_amount_out;
+    order.bounty = bounty;
+    order.expiry_ts = expiry_ts;
+
+    emit!(PlaceOrderEvent {
+        order: order.key(),
+        amm: order.amm,
+        owner: order.owner,
+        source_mint,
+        amount_in,
+        minimum_amount_out,
+        bounty,
+        expiry_ts,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PlaceOrder<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub source_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        has_one = owner,
+        constraint = source.mint == source_mint.key() @ SwapError::IncorrectSwapAccount,
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(has_one = owner)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + size_of::<Order>()
+    )]
+    pub order: Box<Account<'info, Order>>,
+    // escrowed tokens, owned by the pool authority
+    #[account(
+        init,
+        seeds = [b"order_escrow".as_ref(),order.key().as_ref()],
+        bump,
+        payer = owner,
+        token::mint = source_mint,
+        token::authority = authority
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceOrder<'info> {
+    fn into_transfer_to_escrow_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.escrow.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_bounty_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, system_program::Transfer<'info>> {
+        let cpi_accounts = system_program::Transfer {
+            from: self.owner.to_account_info().clone(),
+            to: self.order.to_account_info().clone(),
+        };
+        CpiContext::new(self.system_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Remove a user from a permissioned pool's allowlist, returning the entry's
+/// rent to the admin.  The user can still withdraw their liquidity.
+pub fn handler(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+    emit!(AllowlistEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.allowlist_entry.user,
+        allowed: false,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"allowlist".as_ref(),amm.key().as_ref(),allowlist_entry.user.as_ref()],
+        bump,
+        has_one = amm,
+        close = admin,
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+}
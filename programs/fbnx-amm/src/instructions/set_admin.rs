@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Hand the pool's administrative operations over to a new admin.  The new
+/// admin has to sign as well, so that the pool can't be handed to a key
+/// nobody controls.
+pub fn handler(ctx: Context<SetAdmin>) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.admin = ctx.accounts.new_admin.key();
+
+    emit!(AdminEvent {
+        amm: amm.key(),
+        admin: amm.admin,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    pub admin: Signer<'info>,
+    pub new_admin: Signer<'info>,
+}
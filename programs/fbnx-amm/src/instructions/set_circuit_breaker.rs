@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Set the deviation of the spot price from its moving average, in basis
+/// points, past which trades revert.  0 turns the circuit breaker off.
+pub fn handler(ctx: Context<SetCircuitBreaker>, circuit_breaker_bps: u64) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.circuit_breaker_bps = circuit_breaker_bps;
+
+    emit!(CircuitBreakerEvent {
+        amm: amm.key(),
+        circuit_breaker_bps,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Set the caps that deposits may take the reserves and the pool token supply
+/// to, 0 meaning no cap.  Lowering a cap below the current value only blocks
+/// further deposits.
+pub fn handler(
+    ctx: Context<SetDepositCaps>,
+    max_reserve_a: u64,
+    max_reserve_b: u64,
+    max_pool_supply: u64,
+) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.max_reserve_a = max_reserve_a;
+    amm.max_reserve_b = max_reserve_b;
+    amm.max_pool_supply = max_pool_supply;
+
+    emit!(DepositCapsEvent {
+        amm: amm.key(),
+        max_reserve_a,
+        max_reserve_b,
+        max_pool_supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetDepositCaps<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Set the band, in basis points around the price before a slot's first
+/// trade, that trades in that slot must keep the price within.  A non zero
+/// band also blocks withdrawals in the slot of a deposit.  0 turns MEV
+/// protection off.
+pub fn handler(ctx: Context<SetMevProtection>, mev_band_bps: u64) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.mev_band_bps = mev_band_bps;
+
+    emit!(MevProtectionEvent {
+        amm: amm.key(),
+        mev_band_bps,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMevProtection<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    pub admin: Signer<'info>,
+}
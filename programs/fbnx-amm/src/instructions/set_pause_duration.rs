@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Set the seconds `pause_pool` pauses the pool for.  A running pause keeps
+/// its end until the admin pauses again.
+pub fn handler(ctx: Context<SetPauseDuration>, pause_duration: i64) -> Result<()> {
+    if pause_duration < 0 {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let amm = &mut ctx.accounts.amm;
+    amm.pause_duration = pause_duration;
+
+    emit!(PauseDurationEvent {
+        amm: amm.key(),
+        pause_duration,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPauseDuration<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    pub admin: Signer<'info>,
+}
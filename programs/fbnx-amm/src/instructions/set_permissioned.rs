@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Turn the allowlist check on `swap` and the deposit instructions on or off.
+/// Allowlist entries are kept either way.
+pub fn handler(ctx: Context<SetPermissioned>, permissioned: bool) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.permissioned = permissioned;
+
+    emit!(PermissionedEvent {
+        amm: amm.key(),
+        permissioned,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPermissioned<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Set the owner of the token accounts that `skim` sends excess vault
+/// balances to.
+pub fn handler(ctx: Context<SetSkimRecipient>, skim_recipient: Pubkey) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.skim_recipient = skim_recipient;
+
+    emit!(SkimRecipientEvent {
+        amm: amm.key(),
+        skim_recipient,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetSkimRecipient<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        has_one = admin,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    pub admin: Signer<'info>,
+}
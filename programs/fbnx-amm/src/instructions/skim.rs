@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Send the part of each vault's balance that exceeds the tracked reserves,
+/// e.g. tokens sent directly to the vaults, to the pool's skim recipient.
+/// Anyone can call this, the recipient is set on the pool by its admin.
+/// A vault holding less than its reserve has nothing to skim, and must be
+/// synced first.
+pub fn handler(ctx: Context<Skim>) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+
+    let (excess_a, excess_b) =
+        amm.vault_excess(ctx.accounts.vault_token_a.amount, ctx.accounts.vault_token_b.amount)?;
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    if excess_a > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_recipient_a_context()
+                .with_signer(&[&seeds[..]]),
+            excess_a,
+        )?;
+    }
+    if excess_b > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_recipient_b_context()
+                .with_signer(&[&seeds[..]]),
+            excess_b,
+        )?;
+    }
+
+    emit!(SkimEvent {
+        amm: amm.key(),
+        recipient: amm.skim_recipient,
+        token_a_amount: excess_a,
+        token_b_amount: excess_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = recipient_token_a.owner == amm.skim_recipient @ SwapError::InvalidOutputOwner,
+    )]
+    pub recipient_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = recipient_token_b.owner == amm.skim_recipient @ SwapError::InvalidOutputOwner,
+    )]
+    pub recipient_token_b: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Skim<'info> {
+    fn into_transfer_to_recipient_a_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_a.to_account_info().clone(),
+            to: self.recipient_token_a.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_recipient_b_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_b.to_account_info().clone(),
+            to: self.recipient_token_b.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
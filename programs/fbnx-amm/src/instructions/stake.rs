@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+use std::mem::size_of;
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Stake pool tokens in a farm.  Rewards accrued so far are kept as pending
+/// until claimed.
+pub fn handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    token::transfer(ctx.accounts.into_transfer_to_stake_vault_context(), amount)?;
+
+    let farm = &mut ctx.accounts.farm;
+    farm.update(Clock::get()?.unix_timestamp)?;
+
+    let user_stake = &mut ctx.accounts.user_stake;
+    if user_stake.owner == Pubkey::default() {
+        user_stake.farm = farm.key();
+        user_stake.owner = ctx.accounts.owner.key();
+    }
+    user_stake.settle(farm)?;
+    let user_staked = user_stake
+        .amount
+        .checked_add(amount)
+        .ok_or(SwapError::CalculationFailure)?;
+    user_stake.set_amount(farm, user_staked)?;
+    farm.total_staked = farm
+        .total_staked
+        .checked_add(amount)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    emit!(StakeEvent {
+        farm: farm.key(),
+        user: ctx.accounts.owner.key(),
+        amount,
+        user_staked,
+        total_staked: farm.total_staked,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(
+        mut,
+        seeds = [b"farm".as_ref(),farm.amm.as_ref(),farm.reward_mint.as_ref()],
+        bump = farm.bump_seed,
+        has_one = stake_vault,
+     )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init_if_needed,
+        seeds = [b"stake".as_ref(),farm.key().as_ref(),owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + size_of::<UserStake>()
+    )]
+    pub user_stake: Box<Account<'info, UserStake>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut,
+        has_one = owner,
+        constraint = source.mint == farm.pool_mint @ SwapError::IncorrectPoolMint,
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub stake_vault: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Stake<'info> {
+    fn into_transfer_to_stake_vault_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: self.stake_vault.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
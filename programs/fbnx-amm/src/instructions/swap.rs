@@ -2,78 +2,40 @@ use anchor_lang::prelude::*;
 //use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
 use crate::state::*;
-use crate::curve::{
-    base::{SwapCurve, CurveType,},
-    calculator::{CurveCalculator, TradeDirection},
-    fees::CurveFees,
-};
+use crate::curve::calculator::TradeDirection;
 use crate::utils::*;
-use crate::curve::{
-     constant_product::ConstantProductCurve,
-};
 use crate::error::SwapError;
+use crate::events::*;
 
 pub fn handler(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
         let amm = &mut ctx.accounts.amm;
-        if amm.to_account_info().owner != ctx.program_id {
-            return Err(ProgramError::IncorrectProgramId.into());
-        }
+        check_allowlist(amm, ctx.program_id, ctx.accounts.owner.key, ctx.remaining_accounts)?;
+        amm.check_not_paused()?;
 
-        if *ctx.accounts.pool_authority.key
-            != authority_id(ctx.program_id, amm.to_account_info().key, amm.bump_seed)?
-        {
-            return Err(SwapError::InvalidProgramAddress.into());
-        }
-        if !(*ctx.accounts.swap_source.to_account_info().key == amm.token_a_account
-            || *ctx.accounts.swap_source.to_account_info().key == amm.token_b_account)
-        {
-            return Err(SwapError::IncorrectSwapAccount.into());
-        }
-        if !(*ctx.accounts.swap_destination.to_account_info().key == amm.token_a_account
-            || *ctx.accounts.swap_destination.to_account_info().key == amm.token_b_account)
-        {
-            return Err(SwapError::IncorrectSwapAccount.into());
-        }
-        if *ctx.accounts.swap_source.to_account_info().key
-            == *ctx.accounts.swap_destination.to_account_info().key
-        {
-            return Err(SwapError::InvalidInput.into());
-        }
-        if ctx.accounts.swap_source.to_account_info().key == ctx.accounts.vault_source_info.to_account_info().key {
-            return Err(SwapError::InvalidInput.into());
-        }
-        if ctx.accounts.swap_destination.to_account_info().key == ctx.accounts.vault_destination_info.to_account_info().key
-        {
-            return Err(SwapError::InvalidInput.into());
-        }
-        if *ctx.accounts.pool_mint.to_account_info().key != amm.pool_mint {
-            return Err(SwapError::IncorrectPoolMint.into());
-        }
-        if *ctx.accounts.fee_account.to_account_info().key != amm.pool_fee_account {
-            return Err(SwapError::IncorrectFeeAccount.into());
-        }
-        
-
-        let trade_direction =
-            if *ctx.accounts.swap_source.to_account_info().key == amm.token_a_account {
-                TradeDirection::AtoB
-            } else {
-                TradeDirection::BtoA
-            };
+        let trade_direction = if ctx.accounts.vault_source_info.key() == amm.token_a_account {
+            TradeDirection::AtoB
+        } else {
+            TradeDirection::BtoA
+        };
 
-        let curve = build_curve(&amm.curve).unwrap();
-        let fees = build_fees(&amm.fees).unwrap();
+        let curve = build_curve(&amm.curve)?;
+        let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+        let fees = build_fees(&amm.fees)?;
 
+        let (swap_source_amount, swap_destination_amount) = match trade_direction {
+            TradeDirection::AtoB => (amm.reserve_a, amm.reserve_b),
+            TradeDirection::BtoA => (amm.reserve_b, amm.reserve_a),
+        };
         let result = curve
             .swap(
-                u128::try_from(amount_in).unwrap(),
-                u128::try_from(ctx.accounts.swap_source.amount).unwrap(),
-                u128::try_from(ctx.accounts.swap_destination.amount).unwrap(),
+                u128::from(amount_in),
+                u128::from(swap_source_amount),
+                u128::from(swap_destination_amount),
                 trade_direction,
                 &fees,
             )
             .ok_or(SwapError::ZeroTradingTokens)?;
-        if result.destination_amount_swapped < u128::try_from(minimum_amount_out).unwrap() {
+        if result.destination_amount_swapped < u128::from(minimum_amount_out) {
             return Err(SwapError::ExceededSlippage.into());
         }
 
@@ -88,13 +50,12 @@ pub fn handler(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> R
             ),
         };
 
-        let seeds = &[&amm.to_account_info().key.to_bytes(), &[amm.bump_seed][..]];
+        let amm_key = amm.key();
+        let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
 
         token::transfer(
-            ctx.accounts
-                .into_transfer_to_swap_source_context()
-                .with_signer(&[&seeds[..]]),
-            u64::try_from(result.source_amount_swapped).unwrap(),
+            ctx.accounts.into_transfer_to_swap_source_context(),
+            u64::try_from(result.source_amount_swapped).map_err(|_| SwapError::ConversionFailure)?,
         )?;
 
         let mut pool_token_amount = curve
@@ -102,12 +63,15 @@ pub fn handler(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> R
                 result.owner_fee,
                 swap_token_a_amount,
                 swap_token_b_amount,
-                u128::try_from(ctx.accounts.pool_mint.supply).unwrap(),
+                u128::from(ctx.accounts.pool_mint.supply),
                 trade_direction,
                 &fees,
             )
             .ok_or(SwapError::FeeCalculationFailure)?;
+        // the owner fee is minted rounded up
+        let rounded_up_pool_tokens = u64::from(pool_token_amount > 0);
 
+        let mut host_fee_amount: u128 = 0;
         if pool_token_amount > 0 {
             // Allow error to fall through
             if *ctx.accounts.host_fee_account.key != Pubkey::new_from_array([0; 32]) {
@@ -118,6 +82,7 @@ pub fn handler(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> R
                 let host_fee = fees
                     .host_fee(pool_token_amount)
                     .ok_or(SwapError::FeeCalculationFailure)?;
+                host_fee_amount = host_fee;
                 if host_fee > 0 {
                     pool_token_amount = pool_token_amount
                         .checked_sub(host_fee)
@@ -126,7 +91,7 @@ pub fn handler(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> R
                         ctx.accounts
                             .into_mint_to_host_context()
                             .with_signer(&[&seeds[..]]),
-                        u64::try_from(host_fee).unwrap(),
+                        u64::try_from(host_fee).map_err(|_| SwapError::ConversionFailure)?,
                     )?;
                 }
             }
@@ -134,7 +99,7 @@ pub fn handler(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> R
                 ctx.accounts
                     .into_mint_to_pool_context()
                     .with_signer(&[&seeds[..]]),
-                u64::try_from(pool_token_amount).unwrap(),
+                u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
             )?;
         }
 
@@ -142,9 +107,52 @@ pub fn handler(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> R
             ctx.accounts
                 .into_transfer_to_destination_context()
                 .with_signer(&[&seeds[..]]),
-            u64::try_from(result.destination_amount_swapped).unwrap(),
+            u64::try_from(result.destination_amount_swapped).map_err(|_| SwapError::ConversionFailure)?,
         )?;
 
+        let source_amount_swapped = u64::try_from(result.source_amount_swapped).map_err(|_| SwapError::ConversionFailure)?;
+        let destination_amount_swapped = u64::try_from(result.destination_amount_swapped).map_err(|_| SwapError::ConversionFailure)?;
+        let amm = &mut ctx.accounts.amm;
+        match trade_direction {
+            TradeDirection::AtoB => {
+                amm.credit_reserves(source_amount_swapped, 0)?;
+                amm.debit_reserves(0, destination_amount_swapped)?;
+            }
+            TradeDirection::BtoA => {
+                amm.credit_reserves(0, source_amount_swapped)?;
+                amm.debit_reserves(destination_amount_swapped, 0)?;
+            }
+        }
+        amm.check_price_band()?;
+        amm.check_circuit_breaker()?;
+        amm.stats
+            .record_swap(trade_direction, &result, host_fee_amount, Clock::get()?.slot);
+        let (source_mint, destination_mint) = match trade_direction {
+            TradeDirection::AtoB => (amm.token_a_mint, amm.token_b_mint),
+            TradeDirection::BtoA => (amm.token_b_mint, amm.token_a_mint),
+        };
+
+        ctx.accounts.pool_mint.reload()?;
+        ctx.accounts.amm.check_share_value(
+            &share_value,
+            ctx.accounts.pool_mint.supply,
+            rounded_up_pool_tokens,
+        )?;
+        emit!(SwapEvent {
+            amm: ctx.accounts.amm.key(),
+            user: ctx.accounts.owner.key(),
+            source_mint,
+            destination_mint,
+            amount_in: source_amount_swapped,
+            amount_out: destination_amount_swapped,
+            trade_fee: u64::try_from(result.trade_fee).map_err(|_| SwapError::ConversionFailure)?,
+            owner_fee: u64::try_from(result.owner_fee).map_err(|_| SwapError::ConversionFailure)?,
+            host_fee: u64::try_from(host_fee_amount).map_err(|_| SwapError::ConversionFailure)?,
+            reserve_a: ctx.accounts.amm.reserve_a,
+            reserve_b: ctx.accounts.amm.reserve_b,
+            pool_mint_supply: ctx.accounts.pool_mint.supply,
+        });
+
         Ok(())
     }
 
@@ -153,28 +161,43 @@ pub struct Swap<'info> {
     /// CHECK: Safe
     #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
     pub pool_authority: AccountInfo<'info>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
+     )]
     pub amm: Box<Account<'info, Amm>>,
+    // pool vault receiving the input tokens
     #[account(
         mut,
-        constraint=vault_source_info.owner == pool_authority.key(),
+        constraint = vault_source_info.key() == amm.token_a_account
+            || vault_source_info.key() == amm.token_b_account @ SwapError::IncorrectSwapAccount,
     )]
     pub vault_source_info: Account<'info,TokenAccount>,
-    /// CHECK: Safe
+    // pool vault paying out the output tokens
     #[account(mut,
-        constraint=vault_destination_info.owner == pool_authority.key(),
+        constraint = vault_destination_info.key() == amm.token_a_account
+            || vault_destination_info.key() == amm.token_b_account @ SwapError::IncorrectSwapAccount,
+        constraint = vault_destination_info.key() != vault_source_info.key() @ SwapError::InvalidInput,
     )]
     pub vault_destination_info: Account<'info,TokenAccount>,
+    // user's account the input tokens are taken from
     #[account(mut,
-        has_one = owner
+        has_one = owner,
+        constraint = swap_source.mint == vault_source_info.mint @ SwapError::IncorrectSwapAccount,
     )]
     pub swap_source: Account<'info, TokenAccount>,
+    // account the output tokens are paid to, which may belong to someone
+    // other than the owner
     #[account(mut,
-        has_one = owner
+        constraint = swap_destination.mint == vault_destination_info.mint @ SwapError::IncorrectSwapAccount,
+        constraint = swap_destination.key() != vault_destination_info.key() @ SwapError::IncorrectSwapAccount,
     )]
     pub swap_destination: Account<'info, TokenAccount>,
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
     pub pool_mint: Box<Account<'info, Mint>>,
-    #[account(mut)]
+    #[account(mut, address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
     pub fee_account: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -188,8 +211,8 @@ impl<'info> Swap<'info> {
         &self,
     ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
-            from: self.vault_source_info.to_account_info().clone(),
-            to: self.swap_source.to_account_info().clone(),
+            from: self.swap_source.to_account_info().clone(),
+            to: self.vault_source_info.to_account_info().clone(),
             authority: self.owner.to_account_info().clone(),
         };
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
@@ -199,8 +222,8 @@ impl<'info> Swap<'info> {
         &self,
     ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
-            from: self.swap_destination.to_account_info().clone(),
-            to: self.vault_destination_info.to_account_info().clone(),
+            from: self.vault_destination_info.to_account_info().clone(),
+            to: self.swap_destination.to_account_info().clone(),
             authority: self.pool_authority.clone(),
         };
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
@@ -224,38 +247,3 @@ impl<'info> Swap<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 }
-/// Build Curve object and Fee object
-pub fn build_curve(curve_input: &CurveInput) -> Result<SwapCurve> {
-    let curve_type = CurveType::try_from(curve_input.curve_type).unwrap();
-    let culculator: Box<dyn CurveCalculator> = match curve_type {
-        CurveType::ConstantProduct => Box::new(ConstantProductCurve {}),
-        // CurveType::ConstantPrice => Box::new(ConstantPriceCurve {
-        //     token_b_price: curve_input.curve_parameters,
-        // }),
-        // CurveType::Stable => Box::new(StableCurve {
-        //     amp: curve_input.curve_parameters,
-        // }),
-        // CurveType::Offset => Box::new(OffsetCurve {
-        //     token_b_offset: curve_input.curve_parameters,
-        // }),
-    };
-    let curve = SwapCurve {
-        curve_type: curve_type,
-        calculator: culculator,
-    };
-    Ok(curve)
-}
-
-pub fn build_fees(fees_input: &FeesInput) -> Result<CurveFees> {
-    let fees = CurveFees {
-        trade_fee_numerator: fees_input.trade_fee_numerator,
-        trade_fee_denominator: fees_input.trade_fee_denominator,
-        owner_trade_fee_numerator: fees_input.owner_trade_fee_numerator,
-        owner_trade_fee_denominator: fees_input.owner_trade_fee_denominator,
-        owner_withdraw_fee_numerator: fees_input.owner_withdraw_fee_numerator,
-        owner_withdraw_fee_denominator: fees_input.owner_withdraw_fee_denominator,
-        host_fee_numerator: fees_input.host_fee_numerator,
-        host_fee_denominator: fees_input.host_fee_denominator,
-    };
-    Ok(fees)
-}
\ No newline at end of file
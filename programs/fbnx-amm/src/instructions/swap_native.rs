@@ -0,0 +1,277 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+
+use crate::curve::calculator::TradeDirection;
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Swap against a pool with a wrapped SOL side, using the owner's lamports
+/// directly.  With `wrap_source`, `amount_in` lamports are wrapped and swapped
+/// for `user_token`; otherwise `user_token` is swapped and the output is
+/// unwrapped to the owner.  The temporary wrapped SOL account is closed before
+/// returning, so any unswapped lamports and its rent go back to the owner.
+pub fn handler(
+    ctx: Context<SwapNative>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    wrap_source: bool,
+) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+
+    let native_is_token_a = if ctx.accounts.swap_token_a.mint == spl_token::native_mint::id() {
+        true
+    } else if ctx.accounts.swap_token_b.mint == spl_token::native_mint::id() {
+        false
+    } else {
+        return Err(SwapError::ExpectedNativeMint.into());
+    };
+    let trade_direction = if native_is_token_a == wrap_source {
+        TradeDirection::AtoB
+    } else {
+        TradeDirection::BtoA
+    };
+    let user_token_mint = if native_is_token_a {
+        ctx.accounts.swap_token_b.mint
+    } else {
+        ctx.accounts.swap_token_a.mint
+    };
+    if ctx.accounts.user_token.mint != user_token_mint {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+
+    let wsol_account = ctx.accounts.wsol_account.to_account_info();
+
+    check_allowlist(amm, ctx.program_id, ctx.accounts.owner.key, ctx.remaining_accounts)?;
+    amm.check_not_paused()?;
+
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    let (swap_source_amount, swap_destination_amount) = match trade_direction {
+        TradeDirection::AtoB => (amm.reserve_a, amm.reserve_b),
+        TradeDirection::BtoA => (amm.reserve_b, amm.reserve_a),
+    };
+    let result = curve
+        .swap(
+            u128::from(amount_in),
+            u128::from(swap_source_amount),
+            u128::from(swap_destination_amount),
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+    if result.destination_amount_swapped < u128::from(minimum_amount_out) {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+
+    let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+        TradeDirection::AtoB => (
+            result.new_swap_source_amount,
+            result.new_swap_destination_amount,
+        ),
+        TradeDirection::BtoA => (
+            result.new_swap_destination_amount,
+            result.new_swap_source_amount,
+        ),
+    };
+    let pool_token_amount = curve
+        .withdraw_single_token_type_exact_out(
+            result.owner_fee,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            u128::from(ctx.accounts.pool_mint.supply),
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::FeeCalculationFailure)?;
+    // the owner fee is minted rounded up
+    let rounded_up_pool_tokens = u64::from(pool_token_amount > 0);
+
+    let source_amount_swapped = u64::try_from(result.source_amount_swapped)
+        .map_err(|_| SwapError::ConversionFailure)?;
+    if wrap_source {
+        wrap_native(
+            &ctx.accounts.owner.to_account_info(),
+            &wsol_account,
+            &ctx.accounts.system_program.to_account_info(),
+            amount_in,
+        )?;
+    }
+    token::transfer(
+        ctx.accounts
+            .into_transfer_to_swap_source_context(trade_direction, wrap_source),
+        source_amount_swapped,
+    )?;
+
+    if pool_token_amount > 0 {
+        token::mint_to(
+            ctx.accounts
+                .into_mint_to_pool_context()
+                .with_signer(&[&seeds[..]]),
+            u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+        )?;
+    }
+    token::transfer(
+        ctx.accounts
+            .into_transfer_to_destination_context(trade_direction, wrap_source)
+            .with_signer(&[&seeds[..]]),
+        u64::try_from(result.destination_amount_swapped)
+            .map_err(|_| SwapError::ConversionFailure)?,
+    )?;
+    unwrap_native(
+        &ctx.accounts.owner.to_account_info(),
+        &wsol_account,
+        &ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    let destination_amount_swapped = u64::try_from(result.destination_amount_swapped)
+        .map_err(|_| SwapError::ConversionFailure)?;
+    match trade_direction {
+        TradeDirection::AtoB => {
+            ctx.accounts.amm.credit_reserves(source_amount_swapped, 0)?;
+            ctx.accounts.amm.debit_reserves(0, destination_amount_swapped)?;
+        }
+        TradeDirection::BtoA => {
+            ctx.accounts.amm.credit_reserves(0, source_amount_swapped)?;
+            ctx.accounts.amm.debit_reserves(destination_amount_swapped, 0)?;
+        }
+    }
+    ctx.accounts.amm.check_price_band()?;
+    ctx.accounts.amm.check_circuit_breaker()?;
+    ctx.accounts
+        .amm
+        .stats
+        .record_swap(trade_direction, &result, 0, Clock::get()?.slot);
+    let (source_mint, destination_mint) = match trade_direction {
+        TradeDirection::AtoB => (ctx.accounts.amm.token_a_mint, ctx.accounts.amm.token_b_mint),
+        TradeDirection::BtoA => (ctx.accounts.amm.token_b_mint, ctx.accounts.amm.token_a_mint),
+    };
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(
+        &share_value,
+        ctx.accounts.pool_mint.supply,
+        rounded_up_pool_tokens,
+    )?;
+    emit!(SwapEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        source_mint,
+        destination_mint,
+        amount_in: source_amount_swapped,
+        amount_out: destination_amount_swapped,
+        trade_fee: u64::try_from(result.trade_fee).map_err(|_| SwapError::ConversionFailure)?,
+        owner_fee: u64::try_from(result.owner_fee).map_err(|_| SwapError::ConversionFailure)?,
+        host_fee: 0,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwapNative<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(address = spl_token::native_mint::id())]
+    pub native_mint: Box<Account<'info, Mint>>,
+    // temporary wrapped SOL account, closed before the instruction returns
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"wsol".as_ref(), owner.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = owner
+    )]
+    pub wsol_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        has_one = owner
+    )]
+    pub user_token: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub swap_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub swap_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Safe
+    #[account(mut, address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
+    pub fee_account: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SwapNative<'info> {
+    fn into_transfer_to_swap_source_context(
+        &self,
+        trade_direction: TradeDirection,
+        wrap_source: bool,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let user_source = if wrap_source {
+            self.wsol_account.to_account_info()
+        } else {
+            self.user_token.to_account_info()
+        };
+        let swap_source = match trade_direction {
+            TradeDirection::AtoB => self.swap_token_a.to_account_info(),
+            TradeDirection::BtoA => self.swap_token_b.to_account_info(),
+        };
+        let cpi_accounts = Transfer {
+            from: user_source,
+            to: swap_source,
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_destination_context(
+        &self,
+        trade_direction: TradeDirection,
+        wrap_source: bool,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let swap_destination = match trade_direction {
+            TradeDirection::AtoB => self.swap_token_b.to_account_info(),
+            TradeDirection::BtoA => self.swap_token_a.to_account_info(),
+        };
+        let user_destination = if wrap_source {
+            self.user_token.to_account_info()
+        } else {
+            self.wsol_account.to_account_info()
+        };
+        let cpi_accounts = Transfer {
+            from: swap_destination,
+            to: user_destination,
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_pool_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.fee_account.clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Adopt the vaults' actual balances as the pool's reserves, folding any tokens
+/// sent directly to the vaults into the pool.  Anyone can call this, so the
+/// new reserves go through the same price checks as a trade: a donation
+/// can't move the price further than a swap could.
+pub fn handler(ctx: Context<SyncReserves>) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.before_reserves_change()?;
+    amm.reserve_a = ctx.accounts.vault_token_a.amount;
+    amm.reserve_b = ctx.accounts.vault_token_b.amount;
+    amm.check_price_band()?;
+    amm.check_circuit_breaker()?;
+
+    emit!(SyncEvent {
+        amm: amm.key(),
+        reserve_a: amm.reserve_a,
+        reserve_b: amm.reserve_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    #[account(address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_b: Box<Account<'info, TokenAccount>>,
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::events::*;
+
+/// Hand a lock receipt, and with it the right to withdraw the locked pool
+/// tokens, to `new_owner`
+pub fn handler(ctx: Context<TransferLock>, new_owner: Pubkey) -> Result<()> {
+    let lock_receipt = &mut ctx.accounts.lock_receipt;
+    let old_owner = lock_receipt.owner;
+    lock_receipt.owner = new_owner;
+
+    emit!(TransferLockEvent {
+        lock_receipt: lock_receipt.key(),
+        old_owner,
+        new_owner,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TransferLock<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, has_one = owner)]
+    pub lock_receipt: Box<Account<'info, LockReceipt>>,
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Withdraw staked pool tokens from a farm.  Rewards accrued so far are kept
+/// as pending until claimed.
+pub fn handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+    let farm = &mut ctx.accounts.farm;
+    farm.update(Clock::get()?.unix_timestamp)?;
+
+    let user_stake = &mut ctx.accounts.user_stake;
+    user_stake.settle(farm)?;
+    let user_staked = user_stake
+        .amount
+        .checked_sub(amount)
+        .ok_or(SwapError::InsufficientStake)?;
+    user_stake.set_amount(farm, user_staked)?;
+    farm.total_staked = farm
+        .total_staked
+        .checked_sub(amount)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let (farm_amm, farm_reward_mint, farm_bump_seed) =
+        (farm.amm, farm.reward_mint, farm.bump_seed);
+    let seeds = &[
+        b"farm".as_ref(),
+        farm_amm.as_ref(),
+        farm_reward_mint.as_ref(),
+        &[farm_bump_seed][..],
+    ];
+    token::transfer(
+        ctx.accounts
+            .into_transfer_from_stake_vault_context()
+            .with_signer(&[&seeds[..]]),
+        amount,
+    )?;
+
+    emit!(UnstakeEvent {
+        farm: ctx.accounts.farm.key(),
+        user: ctx.accounts.owner.key(),
+        amount,
+        user_staked,
+        total_staked: ctx.accounts.farm.total_staked,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(
+        mut,
+        seeds = [b"farm".as_ref(),farm.amm.as_ref(),farm.reward_mint.as_ref()],
+        bump = farm.bump_seed,
+        has_one = stake_vault,
+     )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        seeds = [b"stake".as_ref(),farm.key().as_ref(),owner.key().as_ref()],
+        bump,
+        has_one = farm,
+        has_one = owner,
+    )]
+    pub user_stake: Box<Account<'info, UserStake>>,
+    pub owner: Signer<'info>,
+    #[account(mut,
+        constraint = destination.mint == farm.pool_mint @ SwapError::IncorrectPoolMint,
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub stake_vault: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Unstake<'info> {
+    fn into_transfer_from_stake_vault_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.stake_vault.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.farm.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -1,63 +1,95 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, CloseAccount};
 use crate::curve::base::{SwapCurve, CurveType};
 use crate::curve::fees::CurveFees;
 use crate::error::SwapError;
-use crate::state::Amm;
+use crate::state::{Amm, DepositReceipt};
 
-#[allow(clippy::too_many_arguments)]
-pub fn check_accounts(
-    amm: &Amm,
+/// Checks that `user` may trade in the pool.  Permissioned pools expect the
+/// user's allowlist entry as the first remaining account.
+pub fn check_allowlist(
+    amm: &Account<Amm>,
     program_id: &Pubkey,
-    amm_account_info: &AccountInfo,
-    authority_info: &AccountInfo,
-    token_a_info: &AccountInfo,
-    token_b_info: &AccountInfo,
-    pool_mint_info: &AccountInfo,
-    token_program_info: &AccountInfo,
-    user_token_a_info: Option<&AccountInfo>,
-    user_token_b_info: Option<&AccountInfo>,
-    pool_fee_account_info: Option<&AccountInfo>,
+    user: &Pubkey,
+    remaining_accounts: &[AccountInfo],
 ) -> Result<()> {
-    if amm_account_info.owner != program_id {
-        return Err(ProgramError::IncorrectProgramId.into());
+    if !amm.permissioned {
+        return Ok(());
     }
-    if *authority_info.key != authority_id(program_id, amm_account_info.key, amm.bump_seed)? {
-        return Err(SwapError::InvalidProgramAddress.into());
-    }
-    if *token_a_info.key != amm.token_a_account {
-        return Err(SwapError::IncorrectSwapAccount.into());
-    }
-    if *token_b_info.key != amm.token_b_account {
-        return Err(SwapError::IncorrectSwapAccount.into());
-    }
-    if *pool_mint_info.key != amm.pool_mint {
-        return Err(SwapError::IncorrectPoolMint.into());
-    }
-    if *token_program_info.key != amm.token_program_id {
-        return Err(SwapError::IncorrectTokenProgramId.into());
-    }
-    if let Some(user_token_a_info) = user_token_a_info {
-        if token_a_info.key == user_token_a_info.key {
-            return Err(SwapError::InvalidInput.into());
-        }
+    let entry_info = remaining_accounts
+        .first()
+        .ok_or(SwapError::NotAllowlisted)?;
+    let (entry_key, _) = Pubkey::find_program_address(
+        &[b"allowlist".as_ref(), amm.key().as_ref(), user.as_ref()],
+        program_id,
+    );
+    if *entry_info.key != entry_key || entry_info.owner != program_id || entry_info.data_is_empty()
+    {
+        return Err(SwapError::NotAllowlisted.into());
     }
-    if let Some(user_token_b_info) = user_token_b_info {
-        if token_b_info.key == user_token_b_info.key {
-            return Err(SwapError::InvalidInput.into());
-        }
+    Ok(())
+}
+
+/// With MEV protection on, reject a withdrawal in the slot of the owner's
+/// latest deposit.  `receipt_info` is the owner's `DepositReceipt` address,
+/// which holds no account if the owner never deposited.
+pub fn check_withdrawal_slot(
+    amm: &Account<Amm>,
+    program_id: &Pubkey,
+    receipt_info: &AccountInfo,
+) -> Result<()> {
+    if amm.mev_band_bps == 0 || receipt_info.owner != program_id || receipt_info.data_is_empty() {
+        return Ok(());
     }
-    if let Some(pool_fee_account_info) = pool_fee_account_info {
-        if *pool_fee_account_info.key != amm.pool_fee_account {
-            return Err(SwapError::IncorrectFeeAccount.into());
-        }
+    let receipt = Account::<DepositReceipt>::try_from(receipt_info)?;
+    if receipt.slot == Clock::get()?.slot {
+        return Err(SwapError::SameSlotWithdrawal.into());
     }
     Ok(())
 }
 
-/// Calculates the authority id by generating a program address.
-pub fn authority_id(program_id: &Pubkey, my_info: &Pubkey, bump_seed: u8) -> Result<Pubkey> {
-    Pubkey::create_program_address(&[&my_info.to_bytes()[..32], &[bump_seed]], program_id)
-        .or(Err(SwapError::InvalidProgramAddress.into()))
+/// Wraps `lamports` from `owner` into the temporary wrapped SOL account by
+/// funding it and syncing its token balance.
+pub fn wrap_native<'info>(
+    owner: &AccountInfo<'info>,
+    wsol_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: owner.clone(),
+                to: wsol_account.clone(),
+            },
+        ),
+        lamports,
+    )?;
+    invoke(
+        &spl_token::instruction::sync_native(&spl_token::id(), wsol_account.key)?,
+        std::slice::from_ref(wsol_account),
+    )?;
+    Ok(())
+}
+
+/// Closes the temporary wrapped SOL account, unwrapping its whole balance and
+/// its rent back to `owner`.
+pub fn unwrap_native<'info>(
+    owner: &AccountInfo<'info>,
+    wsol_account: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    token::close_account(CpiContext::new(
+        token_program.clone(),
+        CloseAccount {
+            account: wsol_account.clone(),
+            destination: owner.clone(),
+            authority: owner.clone(),
+        },
+    ))
 }
 
 pub struct SwapConstraints<'a> {
@@ -2,18 +2,12 @@ use anchor_lang::prelude::*;
 //use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{self, Burn, Mint, TokenAccount, Transfer, Token};
 
-use crate::curve::{
-    base::{SwapCurve, CurveType},
-    calculator::{CurveCalculator, RoundDirection},
-    fees::CurveFees,
-};
+use crate::curve::calculator::RoundDirection;
 
 use crate::state::*;
 use crate::utils::*;
-use crate::curve::{
-     constant_product::ConstantProductCurve,
-};
 use crate::error::SwapError;
+use crate::events::*;
 
 pub fn handler(
     ctx: Context<WithdrawAllTokenTypes>,
@@ -23,78 +17,65 @@ pub fn handler(
 ) -> Result<()> {
     let amm = &mut ctx.accounts.amm;
 
-    let curve = build_curve(&amm.curve).unwrap();
-    let fees = build_fees(&amm.fees).unwrap();
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
 
     let calculator = curve.calculator;
     if !calculator.allows_deposits() {
         return Err(SwapError::UnsupportedCurveOperation.into());
     }
 
-    check_accounts(
-        amm,
-        ctx.program_id,
-        &amm.to_account_info(),
-        &ctx.accounts.authority,
-        &ctx.accounts.vault_token_a.to_account_info(),
-        &ctx.accounts.vault_token_b.to_account_info(),
-        &ctx.accounts.pool_mint.to_account_info(),
-        &ctx.accounts.token_program.to_account_info(),
-        Some(&ctx.accounts.dest_token_a_info.to_account_info()),
-        Some(&ctx.accounts.dest_token_b_info.to_account_info()),
-        Some(&ctx.accounts.fee_account),
-    )?;
-
     let withdraw_fee: u128 = if *ctx.accounts.fee_account.key == *ctx.accounts.source_info.to_account_info().key {
         // withdrawing from the fee account, don't assess withdraw fee
         0
     } else {
-        fees.owner_withdraw_fee(u128::try_from(pool_token_amount).unwrap())
+        fees.owner_withdraw_fee(u128::from(pool_token_amount))
             .ok_or(SwapError::FeeCalculationFailure)?
     };
-    let pool_token_amount = u128::try_from(pool_token_amount)
-        .unwrap()
+    let pool_token_amount = u128::from(pool_token_amount)
         .checked_sub(withdraw_fee)
         .ok_or(SwapError::CalculationFailure)?;
 
     let results = calculator
         .pool_tokens_to_trading_tokens(
             pool_token_amount,
-            u128::try_from(ctx.accounts.pool_mint.supply).unwrap(),
-            u128::try_from(ctx.accounts.vault_token_a.amount).unwrap(),
-            u128::try_from(ctx.accounts.vault_token_b.amount).unwrap(),
+            u128::from(ctx.accounts.pool_mint.supply),
+            u128::from(amm.reserve_a),
+            u128::from(amm.reserve_b),
             RoundDirection::Floor,
         )
         .ok_or(SwapError::ZeroTradingTokens)?;
 
-    let token_a_amount = u64::try_from(results.token_a_amount).unwrap();
-    let token_a_amount = std::cmp::min(ctx.accounts.vault_token_a.amount, token_a_amount);
+    let token_a_amount = u64::try_from(results.token_a_amount).map_err(|_| SwapError::ConversionFailure)?;
+    let token_a_amount = std::cmp::min(amm.reserve_a, token_a_amount);
     if token_a_amount < minimum_token_a_amount {
         return Err(SwapError::ExceededSlippage.into());
     }
-    if token_a_amount == 0 && ctx.accounts.vault_token_a.amount != 0 {
+    if token_a_amount == 0 && amm.reserve_a != 0 {
         return Err(SwapError::ZeroTradingTokens.into());
     }
-    let token_b_amount = u64::try_from(results.token_b_amount).unwrap();
-    let token_b_amount = std::cmp::min(ctx.accounts.vault_token_b.amount, token_b_amount);
+    let token_b_amount = u64::try_from(results.token_b_amount).map_err(|_| SwapError::ConversionFailure)?;
+    let token_b_amount = std::cmp::min(amm.reserve_b, token_b_amount);
     if token_b_amount < minimum_token_b_amount {
         return Err(SwapError::ExceededSlippage.into());
     }
-    if token_b_amount == 0 && ctx.accounts.vault_token_b.amount != 0 {
+    if token_b_amount == 0 && amm.reserve_b != 0 {
         return Err(SwapError::ZeroTradingTokens.into());
     }
 
-    let seeds = &[&amm.to_account_info().key.to_bytes(), &[amm.bump_seed][..]];
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
 
     if withdraw_fee > 0 {
         token::transfer(
             ctx.accounts.into_transfer_to_fee_account_context(),
-            u64::try_from(withdraw_fee).unwrap(),
+            u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?,
         )?;
     }
     token::burn(
         ctx.accounts.into_burn_context(),
-        u64::try_from(pool_token_amount).unwrap(),
+        u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
     )?;
 
     if token_a_amount > 0 {
@@ -110,9 +91,26 @@ pub fn handler(
             ctx.accounts
                 .into_transfer_to_token_b_context()
                 .with_signer(&[&seeds[..]]),
-            token_a_amount,
+            token_b_amount,
         )?;
     }
+
+    check_withdrawal_slot(&ctx.accounts.amm, ctx.program_id, &ctx.accounts.deposit_receipt)?;
+    ctx.accounts.amm.debit_reserves(token_a_amount, token_b_amount)?;
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(&share_value, ctx.accounts.pool_mint.supply, 0)?;
+    emit!(WithdrawEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount: u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+        withdraw_fee: u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
     Ok(())
 }
 
@@ -120,8 +118,9 @@ pub fn handler(
 pub struct WithdrawAllTokenTypes<'info> {
     #[account(
         mut,
-        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_account.as_ref()],
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
         bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
      )]
     pub amm: Box<Account<'info, Amm>>,
     /// CHECK: Safe
@@ -130,22 +129,19 @@ pub struct WithdrawAllTokenTypes<'info> {
     /// CHECK: Safe
     #[account(mut)]
     pub owner: Signer<'info>,
+    /// CHECK: the owner's `DepositReceipt`, read by `check_withdrawal_slot`
+    #[account(seeds = [b"deposit".as_ref(),amm.key().as_ref(),owner.key().as_ref()], bump)]
+    pub deposit_receipt: AccountInfo<'info>,
     /// CHECK: Safe
     #[account(mut,
         token::mint = pool_mint.key()
     )]
     pub source_info: Box<Account<'info,TokenAccount>>,
-    #[account(mut,
-        constraint=vault_token_a.owner == authority.key(),
-    )]
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
     pub vault_token_a: Account<'info, TokenAccount>,
-    #[account(mut,
-        constraint=vault_token_b.owner == authority.key(),
-    )]
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
     pub vault_token_b: Account<'info, TokenAccount>,
-    #[account(mut,
-        mint::authority = authority.key()
-    )]
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
     pub pool_mint: Box<Account<'info, Mint>>,
     /// CHECK: Safe
     #[account(mut,
@@ -158,7 +154,7 @@ pub struct WithdrawAllTokenTypes<'info> {
     )]
     pub dest_token_b_info: Account<'info,TokenAccount>,
     /// CHECK: Safe
-    #[account(mut)]
+    #[account(mut, address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
     pub fee_account: AccountInfo<'info>,
     /// CHECK: Safe
     pub token_program: Program<'info,Token>,
@@ -203,39 +199,3 @@ impl<'info> WithdrawAllTokenTypes<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 }
-
-/// Build Curve object and Fee object
-pub fn build_curve(curve_input: &CurveInput) -> Result<SwapCurve> {
-    let curve_type = CurveType::try_from(curve_input.curve_type).unwrap();
-    let culculator: Box<dyn CurveCalculator> = match curve_type {
-        CurveType::ConstantProduct => Box::new(ConstantProductCurve {}),
-        // CurveType::ConstantPrice => Box::new(ConstantPriceCurve {
-        //     token_b_price: curve_input.curve_parameters,
-        // }),
-        // CurveType::Stable => Box::new(StableCurve {
-        //     amp: curve_input.curve_parameters,
-        // }),
-        // CurveType::Offset => Box::new(OffsetCurve {
-        //     token_b_offset: curve_input.curve_parameters,
-        // }),
-    };
-    let curve = SwapCurve {
-        curve_type: curve_type,
-        calculator: culculator,
-    };
-    Ok(curve)
-}
-
-pub fn build_fees(fees_input: &FeesInput) -> Result<CurveFees> {
-    let fees = CurveFees {
-        trade_fee_numerator: fees_input.trade_fee_numerator,
-        trade_fee_denominator: fees_input.trade_fee_denominator,
-        owner_trade_fee_numerator: fees_input.owner_trade_fee_numerator,
-        owner_trade_fee_denominator: fees_input.owner_trade_fee_denominator,
-        owner_withdraw_fee_numerator: fees_input.owner_withdraw_fee_numerator,
-        owner_withdraw_fee_denominator: fees_input.owner_withdraw_fee_denominator,
-        host_fee_numerator: fees_input.host_fee_numerator,
-        host_fee_denominator: fees_input.host_fee_denominator,
-    };
-    Ok(fees)
-}
@@ -0,0 +1,245 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, TokenAccount, Transfer, Token};
+
+use crate::curve::calculator::RoundDirection;
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Withdraw both token types from a pool with a wrapped SOL side, receiving the
+/// SOL side as lamports.  The SOL side is withdrawn into a temporary wrapped SOL
+/// account, which is closed before returning to unwrap it to the owner.
+pub fn handler(
+    ctx: Context<WithdrawAllNative>,
+    pool_token_amount: u64,
+    minimum_token_a_amount: u64,
+    minimum_token_b_amount: u64,
+) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
+    let calculator = curve.calculator;
+
+    let native_is_token_a = if ctx.accounts.vault_token_a.mint == spl_token::native_mint::id() {
+        true
+    } else if ctx.accounts.vault_token_b.mint == spl_token::native_mint::id() {
+        false
+    } else {
+        return Err(SwapError::ExpectedNativeMint.into());
+    };
+    let user_token_mint = if native_is_token_a {
+        ctx.accounts.vault_token_b.mint
+    } else {
+        ctx.accounts.vault_token_a.mint
+    };
+    if ctx.accounts.user_token.mint != user_token_mint {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+
+    let wsol_account = ctx.accounts.wsol_account.to_account_info();
+
+    let withdraw_fee: u128 = if *ctx.accounts.fee_account.key == ctx.accounts.source_info.key() {
+        // withdrawing from the fee account, don't assess withdraw fee
+        0
+    } else {
+        fees.owner_withdraw_fee(u128::from(pool_token_amount))
+            .ok_or(SwapError::FeeCalculationFailure)?
+    };
+    let pool_token_amount = u128::from(pool_token_amount)
+        .checked_sub(withdraw_fee)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let results = calculator
+        .pool_tokens_to_trading_tokens(
+            pool_token_amount,
+            u128::from(ctx.accounts.pool_mint.supply),
+            u128::from(amm.reserve_a),
+            u128::from(amm.reserve_b),
+            RoundDirection::Floor,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+
+    let token_a_amount =
+        u64::try_from(results.token_a_amount).map_err(|_| SwapError::ConversionFailure)?;
+    let token_a_amount = std::cmp::min(amm.reserve_a, token_a_amount);
+    if token_a_amount < minimum_token_a_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if token_a_amount == 0 && amm.reserve_a != 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+    let token_b_amount =
+        u64::try_from(results.token_b_amount).map_err(|_| SwapError::ConversionFailure)?;
+    let token_b_amount = std::cmp::min(amm.reserve_b, token_b_amount);
+    if token_b_amount < minimum_token_b_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if token_b_amount == 0 && amm.reserve_b != 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    if withdraw_fee > 0 {
+        token::transfer(
+            ctx.accounts.into_transfer_to_fee_account_context(),
+            u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?,
+        )?;
+    }
+    token::burn(
+        ctx.accounts.into_burn_context(),
+        u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+    )?;
+
+    if token_a_amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_token_a_context(native_is_token_a)
+                .with_signer(&[&seeds[..]]),
+            token_a_amount,
+        )?;
+    }
+    if token_b_amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_to_token_b_context(native_is_token_a)
+                .with_signer(&[&seeds[..]]),
+            token_b_amount,
+        )?;
+    }
+    unwrap_native(
+        &ctx.accounts.owner.to_account_info(),
+        &wsol_account,
+        &ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    check_withdrawal_slot(&ctx.accounts.amm, ctx.program_id, &ctx.accounts.deposit_receipt)?;
+    ctx.accounts.amm.debit_reserves(token_a_amount, token_b_amount)?;
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(&share_value, ctx.accounts.pool_mint.supply, 0)?;
+    emit!(WithdrawEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount: u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+        withdraw_fee: u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAllNative<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: the owner's `DepositReceipt`, read by `check_withdrawal_slot`
+    #[account(seeds = [b"deposit".as_ref(),amm.key().as_ref(),owner.key().as_ref()], bump)]
+    pub deposit_receipt: AccountInfo<'info>,
+    #[account(mut,
+        token::mint = pool_mint.key()
+    )]
+    pub source_info: Box<Account<'info, TokenAccount>>,
+    #[account(address = spl_token::native_mint::id())]
+    pub native_mint: Box<Account<'info, Mint>>,
+    // temporary wrapped SOL account, closed before the instruction returns
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"wsol".as_ref(), owner.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = owner
+    )]
+    pub wsol_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        has_one = owner
+    )]
+    pub user_token: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub vault_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Safe
+    #[account(mut, address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
+    pub fee_account: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawAllNative<'info> {
+    fn into_transfer_to_fee_account_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source_info.to_account_info().clone(),
+            to: self.fee_account.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_burn_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        let cpi_accounts = Burn {
+            mint: self.pool_mint.to_account_info().clone(),
+            from: self.source_info.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_token_a_context(
+        &self,
+        native_is_token_a: bool,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let destination = if native_is_token_a {
+            self.wsol_account.to_account_info()
+        } else {
+            self.user_token.to_account_info()
+        };
+        let cpi_accounts = Transfer {
+            from: self.vault_token_a.to_account_info().clone(),
+            to: destination,
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_to_token_b_context(
+        &self,
+        native_is_token_a: bool,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let destination = if native_is_token_a {
+            self.user_token.to_account_info()
+        } else {
+            self.wsol_account.to_account_info()
+        };
+        let cpi_accounts = Transfer {
+            from: self.vault_token_b.to_account_info().clone(),
+            to: destination,
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Withdraw the tokens a DCA schedule has bought so far
+pub fn handler(ctx: Context<WithdrawDca>) -> Result<()> {
+    let amount = ctx.accounts.output_escrow.amount;
+    if amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let amm = &ctx.accounts.amm;
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    token::transfer(
+        ctx.accounts
+            .into_transfer_from_output_context()
+            .with_signer(&[&seeds[..]]),
+        amount,
+    )?;
+
+    emit!(WithdrawDcaEvent {
+        dca: ctx.accounts.dca.key(),
+        owner: ctx.accounts.owner.key(),
+        output_amount: amount,
+        source_amount: 0,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawDca<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    #[account(
+        has_one = amm,
+        has_one = owner,
+        has_one = output_escrow,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    #[account(mut)]
+    pub output_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = destination.mint == output_escrow.mint @ SwapError::IncorrectSwapAccount,
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawDca<'info> {
+    fn into_transfer_from_output_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.output_escrow.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
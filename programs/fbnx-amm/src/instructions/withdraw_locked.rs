@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+
+use crate::state::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Withdraw every unlocked pool token of a lock that has not been withdrawn yet
+pub fn handler(ctx: Context<WithdrawLocked>) -> Result<()> {
+    let lock_receipt = &mut ctx.accounts.lock_receipt;
+    let amount = lock_receipt
+        .unlocked_amount(Clock::get()?.unix_timestamp)?
+        .checked_sub(lock_receipt.withdrawn_amount)
+        .ok_or(SwapError::CalculationFailure)?;
+    if amount == 0 {
+        return Err(SwapError::LiquidityStillLocked.into());
+    }
+    lock_receipt.withdrawn_amount += amount;
+    let remaining_amount = lock_receipt.amount - lock_receipt.withdrawn_amount;
+
+    let amm = &ctx.accounts.amm;
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    token::transfer(
+        ctx.accounts
+            .into_transfer_from_escrow_context()
+            .with_signer(&[&seeds[..]]),
+        amount,
+    )?;
+
+    emit!(WithdrawLockedEvent {
+        lock_receipt: ctx.accounts.lock_receipt.key(),
+        owner: ctx.accounts.owner.key(),
+        amount,
+        remaining_amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLocked<'info> {
+    #[account(
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = amm,
+        has_one = owner,
+        has_one = escrow,
+    )]
+    pub lock_receipt: Box<Account<'info, LockReceipt>>,
+    #[account(mut)]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = destination.mint == lock_receipt.pool_mint @ SwapError::IncorrectPoolMint,
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawLocked<'info> {
+    fn into_transfer_from_escrow_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.escrow.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
@@ -2,26 +2,22 @@ use anchor_lang::prelude::*;
 //use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{self, Burn, Mint, TokenAccount, Transfer, Token};
 
-use crate::curve::{
-    base::{SwapCurve,CurveType},
-    calculator::{CurveCalculator,  TradeDirection},
-    fees::CurveFees,
-};
+use crate::curve::calculator::TradeDirection;
 use crate::state::*;
-use crate::curve::{
-     constant_product::ConstantProductCurve,
-};
-use crate::error::SwapError;
 use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
 pub fn handler(
     ctx: Context<WithdrawSingleTokenType>,
     destination_token_amount: u64,
     maximum_pool_token_amount: u64,
 ) -> Result<()> {
     let amm = &mut ctx.accounts.amm;
+    amm.check_not_paused()?;
 
-    let curve = build_curve(&amm.curve).unwrap();
-    let fees = build_fees(&amm.fees).unwrap();
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
 
     let trade_direction = if ctx.accounts.destination.mint == ctx.accounts.swap_token_a.mint {
         TradeDirection::AtoB
@@ -31,33 +27,13 @@ pub fn handler(
         return Err(SwapError::IncorrectSwapAccount.into());
     };
 
-    let destination = ctx.accounts.destination.to_account_info().clone();
-    let (destination_a_info, destination_b_info) = match trade_direction {
-        TradeDirection::AtoB => (Some(&destination), None),
-        TradeDirection::BtoA => (None, Some(&destination)),
-    };
-
-    check_accounts(
-        amm,
-        ctx.program_id,
-        &amm.to_account_info(),
-        &ctx.accounts.authority,
-        &ctx.accounts.swap_token_a.to_account_info(),
-        &ctx.accounts.swap_token_b.to_account_info(),
-        &ctx.accounts.pool_mint.to_account_info(),
-        &ctx.accounts.token_program,
-        destination_a_info,
-        destination_b_info,
-        Some(&ctx.accounts.fee_account.to_account_info()),
-    )?;
-
-    let pool_mint_supply = u128::try_from(ctx.accounts.pool_mint.supply).unwrap();
-    let swap_token_a_amount = u128::try_from(ctx.accounts.swap_token_a.amount).unwrap();
-    let swap_token_b_amount = u128::try_from(ctx.accounts.swap_token_b.amount).unwrap();
+    let pool_mint_supply = u128::from(ctx.accounts.pool_mint.supply);
+    let swap_token_a_amount = u128::from(amm.reserve_a);
+    let swap_token_b_amount = u128::from(amm.reserve_b);
 
     let burn_pool_token_amount = curve
         .withdraw_single_token_type_exact_out(
-            u128::try_from(destination_token_amount).unwrap(),
+            u128::from(destination_token_amount),
             swap_token_a_amount,
             swap_token_b_amount,
             pool_mint_supply,
@@ -78,24 +54,25 @@ pub fn handler(
         .checked_add(withdraw_fee)
         .ok_or(SwapError::CalculationFailure)?;
 
-    if u64::try_from(pool_token_amount).unwrap() > maximum_pool_token_amount {
+    if pool_token_amount > u128::from(maximum_pool_token_amount) {
         return Err(SwapError::ExceededSlippage.into());
     }
     if pool_token_amount == 0 {
         return Err(SwapError::ZeroTradingTokens.into());
     }
 
-    let seeds = &[&amm.to_account_info().key.to_bytes(), &[amm.bump_seed][..]];
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
 
     if withdraw_fee > 0 {
         token::transfer(
             ctx.accounts.into_transfer_to_fee_account_context(),
-            u64::try_from(withdraw_fee).unwrap(),
+            u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?,
         )?;
     }
     token::burn(
         ctx.accounts.into_burn_context(),
-        u64::try_from(burn_pool_token_amount).unwrap(),
+        u64::try_from(burn_pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
     )?;
 
     match trade_direction {
@@ -117,6 +94,32 @@ pub fn handler(
         }
     }
 
+    check_withdrawal_slot(&ctx.accounts.amm, ctx.program_id, &ctx.accounts.deposit_receipt)?;
+    match trade_direction {
+        TradeDirection::AtoB => ctx.accounts.amm.debit_reserves(destination_token_amount, 0)?,
+        TradeDirection::BtoA => ctx.accounts.amm.debit_reserves(0, destination_token_amount)?,
+    }
+    ctx.accounts.amm.check_price_band()?;
+    ctx.accounts.amm.check_circuit_breaker()?;
+    let (token_a_amount, token_b_amount) = match trade_direction {
+        TradeDirection::AtoB => (destination_token_amount, 0),
+        TradeDirection::BtoA => (0, destination_token_amount),
+    };
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(&share_value, ctx.accounts.pool_mint.supply, 0)?;
+    emit!(WithdrawEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount: u64::try_from(burn_pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+        withdraw_fee: u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
     Ok(())
 }
 
@@ -125,8 +128,9 @@ pub fn handler(
 pub struct WithdrawSingleTokenType<'info> {
     #[account(
         mut,
-        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_account.as_ref()],
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
         bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
      )]
     pub amm: Box<Account<'info, Amm>>,
     /// CHECK: Safe
@@ -134,35 +138,32 @@ pub struct WithdrawSingleTokenType<'info> {
     pub authority: AccountInfo<'info>,
     /// CHECK: Safe
     pub owner: Signer<'info>,
+    /// CHECK: the owner's `DepositReceipt`, read by `check_withdrawal_slot`
+    #[account(seeds = [b"deposit".as_ref(),amm.key().as_ref(),owner.key().as_ref()], bump)]
+    pub deposit_receipt: AccountInfo<'info>,
     #[account(mut,
         token::mint = pool_mint.key()
     )]
     pub source: Account<'info, TokenAccount>,
-    #[account(mut,
-        constraint=swap_token_a.owner == authority.key(),
-    )]
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
     pub swap_token_a: Account<'info, TokenAccount>,
-    #[account(mut,
-        constraint=swap_token_b.owner == authority.key(),
-    )]
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
     pub swap_token_b: Account<'info, TokenAccount>,
-    #[account(mut,
-        mint::authority = authority.key()
-    )]
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
     pub pool_mint: Account<'info, Mint>,
     #[account(mut,
         has_one = owner
     )]
     pub destination: Account<'info, TokenAccount>,
     /// CHECK: Safe
-    #[account(mut)]
+    #[account(mut, address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
     pub fee_account: AccountInfo<'info>,
     /// CHECK: Safe
     pub token_program: Program<'info,Token>,
 }
 
 impl<'info> WithdrawSingleTokenType<'info> {
-    fn into_transfer_to_fee_account_context(
+    pub(crate) fn into_transfer_to_fee_account_context(
         &self,
     ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
@@ -173,7 +174,7 @@ impl<'info> WithdrawSingleTokenType<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 
-    fn into_burn_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+    pub(crate) fn into_burn_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
         let cpi_accounts = Burn {
             mint: self.pool_mint.to_account_info().clone(),
             from: self.source.to_account_info().clone(),
@@ -182,7 +183,7 @@ impl<'info> WithdrawSingleTokenType<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 
-    fn into_transfer_from_token_a_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+    pub(crate) fn into_transfer_from_token_a_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
             from: self.swap_token_a.to_account_info().clone(),
             to: self.destination.to_account_info().clone(),
@@ -191,7 +192,7 @@ impl<'info> WithdrawSingleTokenType<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 
-    fn into_transfer_from_token_b_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+    pub(crate) fn into_transfer_from_token_b_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
             from: self.swap_token_b.to_account_info().clone(),
             to: self.destination.to_account_info().clone(),
@@ -200,38 +201,3 @@ impl<'info> WithdrawSingleTokenType<'info> {
         CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
     }
 }
-/// Build Curve object and Fee object
-pub fn build_curve(curve_input: &CurveInput) -> Result<SwapCurve> {
-    let curve_type = CurveType::try_from(curve_input.curve_type).unwrap();
-    let culculator: Box<dyn CurveCalculator> = match curve_type {
-        CurveType::ConstantProduct => Box::new(ConstantProductCurve {}),
-        // CurveType::ConstantPrice => Box::new(ConstantPriceCurve {
-        //     token_b_price: curve_input.curve_parameters,
-        // }),
-        // CurveType::Stable => Box::new(StableCurve {
-        //     amp: curve_input.curve_parameters,
-        // }),
-        // CurveType::Offset => Box::new(OffsetCurve {
-        //     token_b_offset: curve_input.curve_parameters,
-        // }),
-    };
-    let curve = SwapCurve {
-        curve_type: curve_type,
-        calculator: culculator,
-    };
-    Ok(curve)
-}
-
-pub fn build_fees(fees_input: &FeesInput) -> Result<CurveFees> {
-    let fees = CurveFees {
-        trade_fee_numerator: fees_input.trade_fee_numerator,
-        trade_fee_denominator: fees_input.trade_fee_denominator,
-        owner_trade_fee_numerator: fees_input.owner_trade_fee_numerator,
-        owner_trade_fee_denominator: fees_input.owner_trade_fee_denominator,
-        owner_withdraw_fee_numerator: fees_input.owner_withdraw_fee_numerator,
-        owner_withdraw_fee_denominator: fees_input.owner_withdraw_fee_denominator,
-        host_fee_numerator: fees_input.host_fee_numerator,
-        host_fee_denominator: fees_input.host_fee_denominator,
-    };
-    Ok(fees)
-}
\ No newline at end of file
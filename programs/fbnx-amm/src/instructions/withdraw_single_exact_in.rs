@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::curve::calculator::TradeDirection;
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+use crate::instructions::withdraw_single::WithdrawSingleTokenType;
+
+/// Burn an exact amount of pool tokens and withdraw as much of a single token
+/// type as they are worth.  Uses the same accounts as `withdraw_single`.
+pub fn handler(
+    ctx: Context<WithdrawSingleTokenType>,
+    pool_token_amount: u64,
+    minimum_destination_amount: u64,
+) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.check_not_paused()?;
+
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
+
+    let trade_direction = if ctx.accounts.destination.mint == ctx.accounts.swap_token_a.mint {
+        TradeDirection::AtoB
+    } else if ctx.accounts.destination.mint == ctx.accounts.swap_token_b.mint {
+        TradeDirection::BtoA
+    } else {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    };
+
+    let withdraw_fee: u128 =
+        if ctx.accounts.fee_account.key == ctx.accounts.source.to_account_info().key {
+            // withdrawing from the fee account, don't assess withdraw fee
+            0
+        } else {
+            fees.owner_withdraw_fee(u128::from(pool_token_amount))
+                .ok_or(SwapError::FeeCalculationFailure)?
+        };
+    let burn_pool_token_amount = u128::from(pool_token_amount)
+        .checked_sub(withdraw_fee)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let destination_token_amount = curve
+        .withdraw_single_token_type_exact_in(
+            burn_pool_token_amount,
+            u128::from(amm.reserve_a),
+            u128::from(amm.reserve_b),
+            u128::from(ctx.accounts.pool_mint.supply),
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+    let destination_token_amount =
+        u64::try_from(destination_token_amount).map_err(|_| SwapError::ConversionFailure)?;
+
+    if destination_token_amount < minimum_destination_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if destination_token_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    if withdraw_fee > 0 {
+        token::transfer(
+            ctx.accounts.into_transfer_to_fee_account_context(),
+            u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?,
+        )?;
+    }
+    token::burn(
+        ctx.accounts.into_burn_context(),
+        u64::try_from(burn_pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+    )?;
+
+    match trade_direction {
+        TradeDirection::AtoB => {
+            token::transfer(
+                ctx.accounts
+                    .into_transfer_from_token_a_context()
+                    .with_signer(&[&seeds[..]]),
+                destination_token_amount,
+            )?;
+        }
+        TradeDirection::BtoA => {
+            token::transfer(
+                ctx.accounts
+                    .into_transfer_from_token_b_context()
+                    .with_signer(&[&seeds[..]]),
+                destination_token_amount,
+            )?;
+        }
+    }
+
+    check_withdrawal_slot(&ctx.accounts.amm, ctx.program_id, &ctx.accounts.deposit_receipt)?;
+    match trade_direction {
+        TradeDirection::AtoB => ctx.accounts.amm.debit_reserves(destination_token_amount, 0)?,
+        TradeDirection::BtoA => ctx.accounts.amm.debit_reserves(0, destination_token_amount)?,
+    }
+    ctx.accounts.amm.check_price_band()?;
+    ctx.accounts.amm.check_circuit_breaker()?;
+    let (token_a_amount, token_b_amount) = match trade_direction {
+        TradeDirection::AtoB => (destination_token_amount, 0),
+        TradeDirection::BtoA => (0, destination_token_amount),
+    };
+
+    ctx.accounts.pool_mint.reload()?;
+    ctx.accounts.amm.check_share_value(&share_value, ctx.accounts.pool_mint.supply, 0)?;
+    emit!(WithdrawEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount: u64::try_from(burn_pool_token_amount).map_err(|_| SwapError::ConversionFailure)?,
+        withdraw_fee: u64::try_from(withdraw_fee).map_err(|_| SwapError::ConversionFailure)?,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,354 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer, Token};
+use std::mem::size_of;
+
+use crate::curve::{
+    base::CurveType,
+    calculator::{RoundDirection, TradeDirection},
+    constant_product::zap_in_swap_amount,
+};
+use crate::state::*;
+use crate::utils::*;
+use crate::error::SwapError;
+use crate::events::*;
+
+/// Deposit a single token type by swapping the optimal fraction of it for the
+/// other side, then depositing both sides at the pool ratio.  Whatever cannot be
+/// deposited is left with the user: unused source tokens are never taken, and
+/// leftover swap output is sent to `dust_destination`.
+pub fn handler(
+    ctx: Context<ZapIn>,
+    source_token_amount: u64,
+    minimum_pool_token_amount: u64,
+) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+
+    let curve = build_curve(&amm.curve)?;
+    let share_value = amm.share_value(ctx.accounts.pool_mint.supply)?;
+    let fees = build_fees(&amm.fees)?;
+    if curve.curve_type != CurveType::ConstantProduct {
+        return Err(SwapError::UnsupportedCurveOperation.into());
+    }
+    if !curve.calculator.allows_deposits() {
+        return Err(SwapError::UnsupportedCurveOperation.into());
+    }
+
+    let trade_direction = if ctx.accounts.source.mint == ctx.accounts.swap_token_a.mint
+        && ctx.accounts.dust_destination.mint == ctx.accounts.swap_token_b.mint
+    {
+        TradeDirection::AtoB
+    } else if ctx.accounts.source.mint == ctx.accounts.swap_token_b.mint
+        && ctx.accounts.dust_destination.mint == ctx.accounts.swap_token_a.mint
+    {
+        TradeDirection::BtoA
+    } else {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    };
+
+    check_allowlist(amm, ctx.program_id, ctx.accounts.owner.key, ctx.remaining_accounts)?;
+    amm.check_not_paused()?;
+
+    let pool_mint_supply = u128::from(ctx.accounts.pool_mint.supply);
+    if pool_mint_supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+    let (swap_source_amount, swap_destination_amount) = match trade_direction {
+        TradeDirection::AtoB => (u128::from(amm.reserve_a), u128::from(amm.reserve_b)),
+        TradeDirection::BtoA => (u128::from(amm.reserve_b), u128::from(amm.reserve_a)),
+    };
+
+    let (fee_numerator, fee_denominator) = fees
+        .total_trading_fee_fraction()
+        .ok_or(SwapError::FeeCalculationFailure)?;
+    let swap_amount = zap_in_swap_amount(
+        u128::from(source_token_amount),
+        swap_source_amount,
+        fee_numerator,
+        fee_denominator,
+    )
+    .ok_or(SwapError::CalculationFailure)?;
+
+    let result = curve
+        .swap(
+            swap_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+
+    let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+        TradeDirection::AtoB => (
+            result.new_swap_source_amount,
+            result.new_swap_destination_amount,
+        ),
+        TradeDirection::BtoA => (
+            result.new_swap_destination_amount,
+            result.new_swap_source_amount,
+        ),
+    };
+
+    // The owner's share of the swap fee is minted as pool tokens, exactly as
+    // in `swap`, before the deposit is priced against the new supply.
+    let owner_fee_pool_token_amount = curve
+        .withdraw_single_token_type_exact_out(
+            result.owner_fee,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_mint_supply,
+            trade_direction,
+            &fees,
+        )
+        .ok_or(SwapError::FeeCalculationFailure)?;
+    let pool_mint_supply = pool_mint_supply
+        .checked_add(owner_fee_pool_token_amount)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let remaining_source_amount = u128::from(source_token_amount)
+        .checked_sub(result.source_amount_swapped)
+        .ok_or(SwapError::CalculationFailure)?;
+    let pool_token_amount = std::cmp::min(
+        remaining_source_amount
+            .checked_mul(pool_mint_supply)
+            .and_then(|x| x.checked_div(result.new_swap_source_amount))
+            .ok_or(SwapError::CalculationFailure)?,
+        result
+            .destination_amount_swapped
+            .checked_mul(pool_mint_supply)
+            .and_then(|x| x.checked_div(result.new_swap_destination_amount))
+            .ok_or(SwapError::CalculationFailure)?,
+    );
+
+    let results = curve
+        .calculator
+        .pool_tokens_to_trading_tokens(
+            pool_token_amount,
+            pool_mint_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            RoundDirection::Ceiling,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+    let (deposit_source_amount, deposit_destination_amount) = match trade_direction {
+        TradeDirection::AtoB => (results.token_a_amount, results.token_b_amount),
+        TradeDirection::BtoA => (results.token_b_amount, results.token_a_amount),
+    };
+
+    let pool_token_amount =
+        u64::try_from(pool_token_amount).map_err(|_| SwapError::ConversionFailure)?;
+    if pool_token_amount < minimum_pool_token_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    if pool_token_amount == 0 || deposit_source_amount == 0 || deposit_destination_amount == 0 {
+        return Err(SwapError::ZeroTradingTokens.into());
+    }
+
+    let source_amount_used = result
+        .source_amount_swapped
+        .checked_add(deposit_source_amount)
+        .ok_or(SwapError::CalculationFailure)?;
+    let dust_amount = result
+        .destination_amount_swapped
+        .checked_sub(deposit_destination_amount)
+        .ok_or(SwapError::CalculationFailure)?;
+    let source_amount_used =
+        u64::try_from(source_amount_used).map_err(|_| SwapError::ConversionFailure)?;
+    let dust_amount = u64::try_from(dust_amount).map_err(|_| SwapError::ConversionFailure)?;
+    let owner_fee_pool_token_amount =
+        u64::try_from(owner_fee_pool_token_amount).map_err(|_| SwapError::ConversionFailure)?;
+
+    let amm_key = amm.key();
+    let seeds = &[b"authority".as_ref(), amm_key.as_ref(), &[amm.bump_seed][..]];
+
+    token::transfer(
+        ctx.accounts.into_transfer_to_swap_source_context(trade_direction),
+        source_amount_used,
+    )?;
+    if dust_amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .into_transfer_dust_context(trade_direction)
+                .with_signer(&[&seeds[..]]),
+            dust_amount,
+        )?;
+    }
+    if owner_fee_pool_token_amount > 0 {
+        token::mint_to(
+            ctx.accounts
+                .into_mint_to_fee_account_context()
+                .with_signer(&[&seeds[..]]),
+            owner_fee_pool_token_amount,
+        )?;
+    }
+    token::mint_to(
+        ctx.accounts
+            .into_mint_to_context()
+            .with_signer(&[&seeds[..]]),
+        pool_token_amount,
+    )?;
+
+    // net of the internal swap and the deposit, the source side gains
+    // everything taken from the user and the other side loses the dust
+    match trade_direction {
+        TradeDirection::AtoB => {
+            ctx.accounts.amm.credit_reserves(source_amount_used, 0)?;
+            ctx.accounts.amm.debit_reserves(0, dust_amount)?;
+        }
+        TradeDirection::BtoA => {
+            ctx.accounts.amm.credit_reserves(0, source_amount_used)?;
+            ctx.accounts.amm.debit_reserves(dust_amount, 0)?;
+        }
+    }
+    ctx.accounts
+        .amm
+        .stats
+        .record_swap(trade_direction, &result, 0, Clock::get()?.slot);
+    let (source_mint, destination_mint) = match trade_direction {
+        TradeDirection::AtoB => (ctx.accounts.amm.token_a_mint, ctx.accounts.amm.token_b_mint),
+        TradeDirection::BtoA => (ctx.accounts.amm.token_b_mint, ctx.accounts.amm.token_a_mint),
+    };
+
+    ctx.accounts.pool_mint.reload()?;
+    // the owner fee is minted rounded up
+    ctx.accounts.amm.check_share_value(
+        &share_value,
+        ctx.accounts.pool_mint.supply,
+        u64::from(owner_fee_pool_token_amount > 0),
+    )?;
+    ctx.accounts.amm.check_deposit_caps(ctx.accounts.pool_mint.supply)?;
+    let amm_key = ctx.accounts.amm.key();
+    let owner_key = ctx.accounts.owner.key();
+    ctx.accounts.deposit_receipt.record(amm_key, owner_key)?;
+    ctx.accounts.amm.check_price_band()?;
+    ctx.accounts.amm.check_circuit_breaker()?;
+    emit!(SwapEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        source_mint,
+        destination_mint,
+        amount_in: u64::try_from(result.source_amount_swapped).map_err(|_| SwapError::ConversionFailure)?,
+        amount_out: u64::try_from(result.destination_amount_swapped).map_err(|_| SwapError::ConversionFailure)?,
+        trade_fee: u64::try_from(result.trade_fee).map_err(|_| SwapError::ConversionFailure)?,
+        owner_fee: u64::try_from(result.owner_fee).map_err(|_| SwapError::ConversionFailure)?,
+        host_fee: 0,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+    emit!(DepositEvent {
+        amm: ctx.accounts.amm.key(),
+        user: ctx.accounts.owner.key(),
+        token_a_amount: u64::try_from(results.token_a_amount).map_err(|_| SwapError::ConversionFailure)?,
+        token_b_amount: u64::try_from(results.token_b_amount).map_err(|_| SwapError::ConversionFailure)?,
+        pool_token_amount,
+        reserve_a: ctx.accounts.amm.reserve_a,
+        reserve_b: ctx.accounts.amm.reserve_b,
+        pool_mint_supply: ctx.accounts.pool_mint.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ZapIn<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm".as_ref(),amm.token_a_mint.as_ref(),amm.token_b_mint.as_ref()],
+        bump,
+        constraint = amm.version == AMM_VERSION @ SwapError::UnsupportedAccountVersion,
+     )]
+    pub amm: Box<Account<'info, Amm>>,
+    /// CHECK: Safe
+    #[account(seeds=[b"authority".as_ref(), amm.key().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [b"deposit".as_ref(),amm.key().as_ref(),owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + size_of::<DepositReceipt>()
+    )]
+    pub deposit_receipt: Box<Account<'info, DepositReceipt>>,
+    #[account(mut,
+        has_one = owner
+    )]
+    pub source: Account<'info, TokenAccount>,
+    #[account(mut,
+        has_one = owner
+    )]
+    pub dust_destination: Account<'info, TokenAccount>,
+    #[account(mut, address = amm.token_a_account @ SwapError::IncorrectSwapAccount)]
+    pub swap_token_a: Account<'info, TokenAccount>,
+    #[account(mut, address = amm.token_b_account @ SwapError::IncorrectSwapAccount)]
+    pub swap_token_b: Account<'info, TokenAccount>,
+    #[account(mut, address = amm.pool_mint @ SwapError::IncorrectPoolMint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Safe
+    #[account(mut, address = amm.pool_fee_account @ SwapError::IncorrectFeeAccount)]
+    pub fee_account: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        token::mint = pool_mint,
+        token::authority = owner
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ZapIn<'info> {
+    fn into_transfer_to_swap_source_context(
+        &self,
+        trade_direction: TradeDirection,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let swap_source = match trade_direction {
+            TradeDirection::AtoB => &self.swap_token_a,
+            TradeDirection::BtoA => &self.swap_token_b,
+        };
+        let cpi_accounts = Transfer {
+            from: self.source.to_account_info().clone(),
+            to: swap_source.to_account_info().clone(),
+            authority: self.owner.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_transfer_dust_context(
+        &self,
+        trade_direction: TradeDirection,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let swap_destination = match trade_direction {
+            TradeDirection::AtoB => &self.swap_token_b,
+            TradeDirection::BtoA => &self.swap_token_a,
+        };
+        let cpi_accounts = Transfer {
+            from: swap_destination.to_account_info().clone(),
+            to: self.dust_destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_fee_account_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.fee_account.clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+
+    fn into_mint_to_context(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.pool_mint.to_account_info().clone(),
+            to: self.destination.to_account_info().clone(),
+            authority: self.authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info().clone(), cpi_accounts)
+    }
+}
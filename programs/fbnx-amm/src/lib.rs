@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 pub mod error; 
+pub mod events;
 pub mod state; 
 pub mod instructions;
 pub mod curve;
@@ -15,8 +16,12 @@ declare_id!("Bq1VBjFa8nfDaiuPfbb63LnYqTQJtg4ZAnPKf6NMRSat");
 pub mod fbnx_amm {
     use super::*;
 
-    pub fn init_pool(ctx: Context<Initialize>,fees_input:FeesInput,curve_input:CurveInput) -> Result<()> {
-        init_pool::handler(ctx,fees_input,curve_input)?;
+    pub fn init_pool(ctx: Context<Initialize>,fees_input:FeesInput,curve_input:CurveInput,
+        pool_decimals: u8,
+        initial_token_a_amount: u64,
+        initial_token_b_amount: u64,
+        permissioned: bool,) -> Result<()> {
+        init_pool::handler(ctx,fees_input,curve_input,pool_decimals,initial_token_a_amount,initial_token_b_amount,permissioned)?;
         Ok(())
     }
 
@@ -35,8 +40,15 @@ pub mod fbnx_amm {
 
     pub fn deposit_single(ctx: Context<DepositSingleTokenType>,
         source_token_amount: u64,
-        maximum_pool_token_amount: u64,) -> Result<()> {
-        deposit_single::handler(ctx,source_token_amount,maximum_pool_token_amount)?;
+        minimum_pool_token_amount: u64,) -> Result<()> {
+        deposit_single::handler(ctx,source_token_amount,minimum_pool_token_amount)?;
+        Ok(())
+    }
+
+    pub fn deposit_single_exact_out(ctx: Context<DepositSingleTokenType>,
+        pool_token_amount: u64,
+        maximum_source_token_amount: u64,) -> Result<()> {
+        deposit_single_exact_out::handler(ctx,pool_token_amount,maximum_source_token_amount)?;
         Ok(())
     }
 
@@ -47,6 +59,13 @@ pub mod fbnx_amm {
         Ok(())
     }
 
+    pub fn withdraw_single_exact_in(ctx: Context<WithdrawSingleTokenType>,
+        pool_token_amount: u64,
+        minimum_destination_amount: u64,) -> Result<()> {
+        withdraw_single_exact_in::handler(ctx,pool_token_amount,minimum_destination_amount)?;
+        Ok(())
+    }
+
     pub fn withdraw_all(ctx: Context<WithdrawAllTokenTypes>,
         pool_token_amount: u64,
         minimum_token_a_amount: u64,
@@ -55,6 +74,247 @@ pub mod fbnx_amm {
         Ok(())
     }
 
+    pub fn swap_native(ctx: Context<SwapNative>,amount_in : u64,minimum_amount_out : u64,wrap_source: bool) -> Result<()> {
+        swap_native::handler(ctx,amount_in,minimum_amount_out,wrap_source)?;
+        Ok(())
+    }
+
+    pub fn deposit_all_native(ctx: Context<DepositAllNative>,
+        pool_token_amount: u64,
+        maximum_token_a_amount: u64,
+        maximum_token_b_amount: u64,) -> Result<()> {
+        deposit_all_native::handler(ctx,pool_token_amount,maximum_token_a_amount,maximum_token_b_amount)?;
+        Ok(())
+    }
+
+    pub fn withdraw_all_native(ctx: Context<WithdrawAllNative>,
+        pool_token_amount: u64,
+        minimum_token_a_amount: u64,
+        minimum_token_b_amount: u64,) -> Result<()> {
+        withdraw_all_native::handler(ctx,pool_token_amount,minimum_token_a_amount,minimum_token_b_amount)?;
+        Ok(())
+    }
+
+    pub fn zap_in(ctx: Context<ZapIn>,
+        source_token_amount: u64,
+        minimum_pool_token_amount: u64,) -> Result<()> {
+        zap_in::handler(ctx,source_token_amount,minimum_pool_token_amount)?;
+        Ok(())
+    }
+
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        sync::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn skim(ctx: Context<Skim>) -> Result<()> {
+        skim::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+        close_pool::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>, curve_input: CurveInput) -> Result<()> {
+        migrate_pool::handler(ctx, curve_input)?;
+        Ok(())
+    }
+
+    pub fn migrate_liquidity(ctx: Context<MigrateLiquidity>, pool_token_amount: u64) -> Result<()> {
+        migrate_liquidity::handler(ctx, pool_token_amount)?;
+        Ok(())
+    }
+
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        migrate_account::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, user: Pubkey) -> Result<()> {
+        add_to_allowlist::handler(ctx, user)?;
+        Ok(())
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        remove_from_allowlist::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_permissioned(ctx: Context<SetPermissioned>, permissioned: bool) -> Result<()> {
+        set_permissioned::handler(ctx, permissioned)?;
+        Ok(())
+    }
+
+    pub fn create_farm(ctx: Context<CreateFarm>, reward_per_second: u64) -> Result<()> {
+        create_farm::handler(ctx, reward_per_second)?;
+        Ok(())
+    }
+
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+        fund_rewards::handler(ctx, amount)?;
+        Ok(())
+    }
+
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        stake::handler(ctx, amount)?;
+        Ok(())
+    }
+
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        unstake::handler(ctx, amount)?;
+        Ok(())
+    }
+
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        claim::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn lock_liquidity(ctx: Context<LockLiquidity>,
+        amount: u64,
+        unlock_ts: i64,
+        vesting_end_ts: i64,) -> Result<()> {
+        lock_liquidity::handler(ctx,amount,unlock_ts,vesting_end_ts)?;
+        Ok(())
+    }
+
+    pub fn withdraw_locked(ctx: Context<WithdrawLocked>) -> Result<()> {
+        withdraw_locked::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn transfer_lock(ctx: Context<TransferLock>, new_owner: Pubkey) -> Result<()> {
+        transfer_lock::handler(ctx, new_owner)?;
+        Ok(())
+    }
+
+    pub fn place_order(ctx: Context<PlaceOrder>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        bounty: u64,
+        expiry_ts: i64,) -> Result<()> {
+        place_order::handler(ctx,amount_in,minimum_amount_out,bounty,expiry_ts)?;
+        Ok(())
+    }
+
+    pub fn execute_order(ctx: Context<ExecuteOrder>) -> Result<()> {
+        execute_order::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        cancel_order::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn create_dca(ctx: Context<CreateDca>,
+        amount: u64,
+        tranche_count: u64,
+        interval_secs: i64,
+        max_slippage_bps: u64,) -> Result<()> {
+        create_dca::handler(ctx,amount,tranche_count,interval_secs,max_slippage_bps)?;
+        Ok(())
+    }
+
+    pub fn crank_dca(ctx: Context<CrankDca>) -> Result<()> {
+        crank_dca::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn withdraw_dca(ctx: Context<WithdrawDca>) -> Result<()> {
+        withdraw_dca::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn close_dca(ctx: Context<CloseDca>) -> Result<()> {
+        close_dca::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn init_multi_pool<'info>(ctx: Context<'_, '_, '_, 'info, InitMultiPool<'info>>,
+        fees_input: FeesInput,
+        amp: u64,
+        pool_decimals: u8,
+        initial_amounts: Vec<u64>,) -> Result<()> {
+        init_multi_pool::handler(ctx,fees_input,amp,pool_decimals,initial_amounts)?;
+        Ok(())
+    }
+
+    pub fn multi_swap(ctx: Context<MultiSwap>,
+        amount_in: u64,
+        minimum_amount_out: u64,) -> Result<()> {
+        multi_swap::handler(ctx,amount_in,minimum_amount_out)?;
+        Ok(())
+    }
+
+    pub fn multi_deposit_all<'info>(ctx: Context<'_, '_, '_, 'info, MultiDepositAll<'info>>,
+        pool_token_amount: u64,
+        maximum_amounts: Vec<u64>,) -> Result<()> {
+        multi_deposit_all::handler(ctx,pool_token_amount,maximum_amounts)?;
+        Ok(())
+    }
+
+    pub fn multi_withdraw_all<'info>(ctx: Context<'_, '_, '_, 'info, MultiWithdrawAll<'info>>,
+        pool_token_amount: u64,
+        minimum_amounts: Vec<u64>,) -> Result<()> {
+        multi_withdraw_all::handler(ctx,pool_token_amount,minimum_amounts)?;
+        Ok(())
+    }
+
+    pub fn multi_deposit_single(ctx: Context<MultiDepositSingle>,
+        source_token_amount: u64,
+        minimum_pool_token_amount: u64,) -> Result<()> {
+        multi_deposit_single::handler(ctx,source_token_amount,minimum_pool_token_amount)?;
+        Ok(())
+    }
+
+    pub fn multi_withdraw_single(ctx: Context<MultiWithdrawSingle>,
+        pool_token_amount: u64,
+        minimum_token_amount: u64,) -> Result<()> {
+        multi_withdraw_single::handler(ctx,pool_token_amount,minimum_token_amount)?;
+        Ok(())
+    }
+
+    pub fn set_circuit_breaker(ctx: Context<SetCircuitBreaker>, circuit_breaker_bps: u64) -> Result<()> {
+        set_circuit_breaker::handler(ctx, circuit_breaker_bps)?;
+        Ok(())
+    }
+
+    pub fn set_pause_duration(ctx: Context<SetPauseDuration>, pause_duration: i64) -> Result<()> {
+        set_pause_duration::handler(ctx, pause_duration)?;
+        Ok(())
+    }
+
+    pub fn pause_pool(ctx: Context<PausePool>) -> Result<()> {
+        pause_pool::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_mev_protection(ctx: Context<SetMevProtection>, mev_band_bps: u64) -> Result<()> {
+        set_mev_protection::handler(ctx, mev_band_bps)?;
+        Ok(())
+    }
+
+    pub fn set_deposit_caps(ctx: Context<SetDepositCaps>,
+        max_reserve_a: u64,
+        max_reserve_b: u64,
+        max_pool_supply: u64,) -> Result<()> {
+        set_deposit_caps::handler(ctx,max_reserve_a,max_reserve_b,max_pool_supply)?;
+        Ok(())
+    }
+
+    pub fn set_admin(ctx: Context<SetAdmin>) -> Result<()> {
+        set_admin::handler(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_skim_recipient(ctx: Context<SetSkimRecipient>, skim_recipient: Pubkey) -> Result<()> {
+        set_skim_recipient::handler(ctx, skim_recipient)?;
+        Ok(())
+    }
+
 
 }
 
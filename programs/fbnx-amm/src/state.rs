@@ -1,14 +1,19 @@
 use anchor_lang::prelude::*;
 
-use crate::curve::{base::{CurveType, SwapCurve}, calculator::CurveCalculator, constant_product::ConstantProductCurve, fees::CurveFees};
+use crate::curve::{base::{CurveType, SwapCurve, SwapResult}, calculator::{CurveCalculator, RoundDirection, TradeDirection}, constant_product::ConstantProductCurve, fees::CurveFees, stable::StableCurve};
+use crate::curve::stable_swap::{self, MAX_TOKENS_IN_MULTI_POOL};
+use spl_math::precise_number::PreciseNumber;
+use crate::error::SwapError;
+
+/// Current layout version of `Amm` accounts
+pub const AMM_VERSION: u8 = 2;
 
 #[account]
 pub struct Amm {
-    pub initializer_key: Pubkey,
-    pub initializer_deposit_token_account: Pubkey,
-    pub initializer_receive_token_account: Pubkey,
-    pub initializer_amount: u64,
-    pub taker_amount: u64,
+    /// Layout version of the account.  Accounts created before the layout was
+    /// versioned read as version 0 and must go through `migrate_account`, as
+    /// must those of older versions.
+    pub version: u8,
     /// Is the swap initialized, with data written to it
     pub is_initialized: bool,
     /// Bump seed used to generate the program address / authority
@@ -31,6 +36,369 @@ pub struct Amm {
     pub fees: FeesInput,
     /// Curve associated with swap
     pub curve: CurveInput,
+    /// Token A reserve used for pricing, which excludes tokens sent directly
+    /// to the vault until `sync` is called
+    pub reserve_a: u64,
+    /// Token B reserve used for pricing
+    pub reserve_b: u64,
+    /// Owner of the token accounts that `skim` sends excess vault balances to,
+    /// the pool creator until the admin calls `set_skim_recipient`
+    pub skim_recipient: Pubkey,
+    /// Account allowed to perform administrative operations on the pool, the
+    /// pool creator until it is handed over with `set_admin`
+    pub admin: Pubkey,
+    /// Cumulative trading statistics
+    pub stats: PoolStats,
+    /// Maximum token A reserve deposits may reach, 0 for none
+    pub max_reserve_a: u64,
+    /// Maximum token B reserve deposits may reach, 0 for none
+    pub max_reserve_b: u64,
+    /// Maximum pool token supply deposits may reach, 0 for none
+    pub max_pool_supply: u64,
+    /// Only allowlisted users may swap or deposit.  Set at `init_pool` and
+    /// by the admin through `set_permissioned`
+    pub permissioned: bool,
+    /// Time-weighted sum of the price of token A in token B, as a
+    /// `PRICE_FRACTIONAL_BITS` fixed point number.  Wraps on overflow, so only
+    /// differences between two readings are meaningful.
+    pub price_a_cumulative: u128,
+    /// Time-weighted sum of the price of token B in token A
+    pub price_b_cumulative: u128,
+    /// Time the cumulative prices were last brought up to date
+    pub last_price_update_ts: i64,
+    /// Largest move of the spot price away from the slot's anchor price that
+    /// trades may cause, in basis points.  0 disables MEV protection.
+    pub mev_band_bps: u64,
+    /// Slot `anchor_price` was recorded in
+    pub anchor_slot: u64,
+    /// Spot price of token A in token B before the slot's first trade, as a
+    /// `PRICE_FRACTIONAL_BITS` fixed point number
+    pub anchor_price: u128,
+    /// Largest deviation of the spot price from `ema_price` that trades may
+    /// cause, in basis points.  Trades going further revert.  0 disables the
+    /// circuit breaker.
+    pub circuit_breaker_bps: u64,
+    /// Seconds the pool stays paused when the admin calls `pause_pool`
+    pub pause_duration: i64,
+    /// Time until which trades are rejected, after the admin paused the pool
+    pub paused_until: i64,
+    /// Exponential moving average of the price of token A in token B, as a
+    /// `PRICE_FRACTIONAL_BITS` fixed point number
+    pub ema_price: u128,
+    /// Zeroed space at the end of the layout.  New fields are carved out of it
+    /// and `migrate_account` grows the account for any that don't fit, so that
+    /// existing accounts read every new field as zero.
+    pub reserved: [u64; 8],
+}
+
+/// Fractional bits of the fixed point prices accumulated by `Amm`
+pub const PRICE_FRACTIONAL_BITS: u32 = 32;
+
+/// Seconds after which a new price fully replaces `Amm::ema_price`.  Shorter
+/// stretches move the average by their share of the window.
+pub const EMA_WINDOW_SECS: i64 = 600;
+
+/// Price of one base token in quote tokens, as a `PRICE_FRACTIONAL_BITS`
+/// fixed point number
+pub fn spot_price(base_reserve: u64, quote_reserve: u64) -> u128 {
+    (u128::from(quote_reserve) << PRICE_FRACTIONAL_BITS) / u128::from(base_reserve)
+}
+
+/// Normalized value of a pool's reserves along with its pool token supply,
+/// taken before an operation to check the value per pool token after it
+pub struct ShareValue {
+    pub value: PreciseNumber,
+    pub supply: u64,
+}
+
+impl ShareValue {
+    /// Check that `value` backing `pool_mint_supply` pool tokens after an
+    /// operation is worth no less per pool token than before it.  Computing
+    /// the value may round it down by one unit, and pool tokens minted rounded
+    /// up may each be one too many.
+    fn check(
+        &self,
+        value: PreciseNumber,
+        pool_mint_supply: u64,
+        rounded_up_pool_tokens: u64,
+    ) -> Result<()> {
+        if self.supply == 0 || pool_mint_supply == 0 {
+            return Ok(());
+        }
+        let one = PreciseNumber::new(1).ok_or(SwapError::CalculationFailure)?;
+        // value_after / supply_after >= value_before / supply_before
+        let value_after = value
+            .checked_add(&one)
+            .and_then(|x| x.checked_mul(&PreciseNumber::new(u128::from(self.supply))?))
+            .ok_or(SwapError::CalculationFailure)?;
+        let value_before = PreciseNumber::new(u128::from(
+            pool_mint_supply.saturating_sub(rounded_up_pool_tokens),
+        ))
+        .and_then(|x| self.value.checked_mul(&x))
+        .ok_or(SwapError::CalculationFailure)?;
+        if value_after.less_than(&value_before) {
+            return Err(SwapError::ShareValueDecreased.into());
+        }
+        Ok(())
+    }
+}
+
+/// Unversioned `Amm` layout of the pools created before the layout was
+/// versioned, only read by `migrate_account`.  Those pools never set
+/// `initializer_key`, whose first byte is where `version` now lives.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyAmm {
+    pub initializer_key: Pubkey,
+    pub initializer_deposit_token_account: Pubkey,
+    pub initializer_receive_token_account: Pubkey,
+    pub initializer_amount: u64,
+    pub taker_amount: u64,
+    pub is_initialized: bool,
+    pub bump_seed: u8,
+    pub token_program_id: Pubkey,
+    pub token_a_account: Pubkey,
+    pub token_b_account: Pubkey,
+    pub pool_mint: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub pool_fee_account: Pubkey,
+    pub fees: FeesInput,
+    pub curve: CurveInput,
+}
+
+impl LegacyAmm {
+    /// Rewrite into the current layout, dropping the unused fields.  Legacy
+    /// pools priced off their vault balances and had no admin, so the caller
+    /// passes the vault balances as the reserves and the account to administer
+    /// the pool.
+    pub fn upgrade(self, reserve_a: u64, reserve_b: u64, admin: Pubkey) -> Amm {
+        Amm {
+            version: AMM_VERSION,
+            is_initialized: self.is_initialized,
+            bump_seed: self.bump_seed,
+            token_program_id: self.token_program_id,
+            token_a_account: self.token_a_account,
+            token_b_account: self.token_b_account,
+            pool_mint: self.pool_mint,
+            token_a_mint: self.token_a_mint,
+            token_b_mint: self.token_b_mint,
+            pool_fee_account: self.pool_fee_account,
+            fees: self.fees,
+            curve: self.curve,
+            reserve_a,
+            reserve_b,
+            skim_recipient: admin,
+            admin,
+            stats: PoolStats::default(),
+            max_reserve_a: 0,
+            max_reserve_b: 0,
+            max_pool_supply: 0,
+            permissioned: false,
+            price_a_cumulative: 0,
+            price_b_cumulative: 0,
+            last_price_update_ts: 0,
+            mev_band_bps: 0,
+            anchor_slot: 0,
+            anchor_price: 0,
+            circuit_breaker_bps: 0,
+            pause_duration: 0,
+            paused_until: 0,
+            ema_price: 0,
+            reserved: [0; 8],
+        }
+    }
+}
+
+impl Amm {
+    /// Serialized size of the account, without the discriminator
+    pub const LEN: usize = 1 + 1 + 1 + 32 * 7 + FeesInput::LEN + CurveInput::LEN + 8 * 2 + 32 * 2
+        + PoolStats::LEN + 8 * 3 + 1 + 16 * 2 + 8 + 8 * 2 + 16 + 8 * 3 + 16 + 8 * 8;
+
+    /// Accumulate the current prices over the time since the last update.
+    /// Only called through `before_reserves_change`, so that each price is
+    /// weighted by how long it held and the EMA price folds in the same
+    /// stretch.
+    fn update_price_cumulative(&mut self, now: i64) {
+        // pools that never accumulated start from now
+        if self.last_price_update_ts != 0 && self.reserve_a != 0 && self.reserve_b != 0 {
+            let elapsed = now.saturating_sub(self.last_price_update_ts).max(0) as u128;
+            self.price_a_cumulative = self
+                .price_a_cumulative
+                .wrapping_add(spot_price(self.reserve_a, self.reserve_b).wrapping_mul(elapsed));
+            self.price_b_cumulative = self
+                .price_b_cumulative
+                .wrapping_add(spot_price(self.reserve_b, self.reserve_a).wrapping_mul(elapsed));
+        }
+        self.last_price_update_ts = now;
+    }
+
+    /// The EMA price brought up to `now`, weighting the current price by the
+    /// time it held since the last update
+    pub fn ema_price_at(&self, now: i64) -> u128 {
+        if self.reserve_a == 0 || self.reserve_b == 0 {
+            return self.ema_price;
+        }
+        let price = spot_price(self.reserve_a, self.reserve_b);
+        // pools that never averaged start from the current price
+        if self.ema_price == 0 || self.last_price_update_ts == 0 {
+            return price;
+        }
+        let elapsed = now
+            .saturating_sub(self.last_price_update_ts)
+            .clamp(0, EMA_WINDOW_SECS) as u128;
+        let window = EMA_WINDOW_SECS as u128;
+        (self.ema_price * (window - elapsed) + price * elapsed) / window
+    }
+
+    /// Reject trades while the pool is paused
+    pub fn check_not_paused(&self) -> Result<()> {
+        if Clock::get()?.unix_timestamp < self.paused_until {
+            return Err(SwapError::PoolPaused.into());
+        }
+        Ok(())
+    }
+
+    /// Whether a trade leaving the given reserves moves the spot price further
+    /// from the EMA price than the circuit breaker allows
+    pub fn exceeds_circuit_breaker(&self, reserve_a: u64, reserve_b: u64) -> Result<bool> {
+        if self.circuit_breaker_bps == 0 || reserve_a == 0 || reserve_b == 0 {
+            return Ok(false);
+        }
+        let ema_price = self.ema_price_at(Clock::get()?.unix_timestamp);
+        if ema_price == 0 {
+            return Ok(false);
+        }
+        let deviation_bps = spot_price(reserve_a, reserve_b)
+            .abs_diff(ema_price)
+            .checked_mul(10_000)
+            .map(|x| x / ema_price)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(deviation_bps > u128::from(self.circuit_breaker_bps))
+    }
+
+    /// Reject a trade that left the reserves beyond the circuit breaker's
+    /// threshold, after the reserves are updated
+    pub fn check_circuit_breaker(&self) -> Result<()> {
+        if self.exceeds_circuit_breaker(self.reserve_a, self.reserve_b)? {
+            return Err(SwapError::CircuitBreakerTripped.into());
+        }
+        Ok(())
+    }
+
+    /// Value of the tracked reserves under the pool's curve, as given by
+    /// `CurveCalculator::normalized_value`
+    pub fn normalized_value(&self) -> Result<PreciseNumber> {
+        let curve = build_curve(&self.curve)?;
+        Ok(curve
+            .calculator
+            .normalized_value(u128::from(self.reserve_a), u128::from(self.reserve_b))
+            .ok_or(SwapError::CalculationFailure)?)
+    }
+
+    /// Snapshot the value backing the given pool token supply, before an
+    /// operation
+    pub fn share_value(&self, pool_mint_supply: u64) -> Result<ShareValue> {
+        Ok(ShareValue {
+            value: self.normalized_value()?,
+            supply: pool_mint_supply,
+        })
+    }
+
+    /// Check that the value per pool token didn't drop since `before`, after
+    /// an operation.  Callers pass the count of pool tokens minted rounded up,
+    /// as for the owner fee on trades, 0 for operations that only round in the
+    /// pool's favour.
+    pub fn check_share_value(
+        &self,
+        before: &ShareValue,
+        pool_mint_supply: u64,
+        rounded_up_pool_tokens: u64,
+    ) -> Result<()> {
+        before.check(self.normalized_value()?, pool_mint_supply, rounded_up_pool_tokens)
+    }
+
+    /// Bring the EMA price, the cumulative prices and the slot's anchor price
+    /// up to date, before the reserves change or the cumulative prices are read
+    pub fn before_reserves_change(&mut self) -> Result<()> {
+        let clock = Clock::get()?;
+        self.ema_price = self.ema_price_at(clock.unix_timestamp);
+        self.update_price_cumulative(clock.unix_timestamp);
+        if self.anchor_slot != clock.slot && self.reserve_a != 0 && self.reserve_b != 0 {
+            self.anchor_slot = clock.slot;
+            self.anchor_price = spot_price(self.reserve_a, self.reserve_b);
+        }
+        Ok(())
+    }
+
+    /// With MEV protection on, check that the spot price stays within the
+    /// band around the slot's anchor price, after a trade
+    pub fn check_price_band(&self) -> Result<()> {
+        if self.mev_band_bps == 0 || self.anchor_price == 0 || self.reserve_a == 0 {
+            return Ok(());
+        }
+        let price = spot_price(self.reserve_a, self.reserve_b);
+        let deviation_bps = price
+            .abs_diff(self.anchor_price)
+            .checked_mul(10_000)
+            .map(|x| x / self.anchor_price)
+            .ok_or(SwapError::CalculationFailure)?;
+        if deviation_bps > u128::from(self.mev_band_bps) {
+            return Err(SwapError::PriceBandExceeded.into());
+        }
+        Ok(())
+    }
+
+    /// Part of each vault balance above its tracked reserve, which `skim`
+    /// pays out.  A vault below its reserve must be synced first.
+    pub fn vault_excess(&self, vault_a: u64, vault_b: u64) -> Result<(u64, u64)> {
+        let excess_a = vault_a
+            .checked_sub(self.reserve_a)
+            .ok_or(SwapError::VaultBelowReserves)?;
+        let excess_b = vault_b
+            .checked_sub(self.reserve_b)
+            .ok_or(SwapError::VaultBelowReserves)?;
+        Ok((excess_a, excess_b))
+    }
+
+    /// Add tokens moved into the vaults to the tracked reserves
+    pub fn credit_reserves(&mut self, token_a_amount: u64, token_b_amount: u64) -> Result<()> {
+        self.before_reserves_change()?;
+        self.reserve_a = self
+            .reserve_a
+            .checked_add(token_a_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.reserve_b = self
+            .reserve_b
+            .checked_add(token_b_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Check the reserves and the given pool token supply against the deposit
+    /// caps, after a deposit
+    pub fn check_deposit_caps(&self, pool_mint_supply: u64) -> Result<()> {
+        if (self.max_reserve_a != 0 && self.reserve_a > self.max_reserve_a)
+            || (self.max_reserve_b != 0 && self.reserve_b > self.max_reserve_b)
+            || (self.max_pool_supply != 0 && pool_mint_supply > self.max_pool_supply)
+        {
+            return Err(SwapError::DepositCapExceeded.into());
+        }
+        Ok(())
+    }
+
+    /// Remove tokens moved out of the vaults from the tracked reserves
+    pub fn debit_reserves(&mut self, token_a_amount: u64, token_b_amount: u64) -> Result<()> {
+        self.before_reserves_change()?;
+        self.reserve_a = self
+            .reserve_a
+            .checked_sub(token_a_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.reserve_b = self
+            .reserve_b
+            .checked_sub(token_b_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy,AnchorSerialize,AnchorDeserialize,Default)]
@@ -45,23 +413,467 @@ pub struct FeesInput {
     pub host_fee_denominator: u64,
 }
 
+/// Marks `user` as allowed to swap and deposit in a permissioned pool.  Lives
+/// at the PDA `["allowlist", amm, user]`.
+#[account]
+pub struct AllowlistEntry {
+    pub amm: Pubkey,
+    pub user: Pubkey,
+}
+
+/// Slot of a user's latest deposit into a pool, in which the same user's
+/// withdrawals are blocked while MEV protection is on.  Lives at the PDA
+/// `["deposit", amm, owner]`.
+#[account]
+pub struct DepositReceipt {
+    pub amm: Pubkey,
+    pub owner: Pubkey,
+    pub slot: u64,
+}
+
+impl DepositReceipt {
+    /// Remember the slot of a deposit by `owner` into `amm`
+    pub fn record(&mut self, amm: Pubkey, owner: Pubkey) -> Result<()> {
+        self.amm = amm;
+        self.owner = owner;
+        self.slot = Clock::get()?.slot;
+        Ok(())
+    }
+}
+
+/// Scale of `Farm::acc_reward_per_share`
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Liquidity mining farm paying one reward mint to stakers of a pool's pool
+/// tokens.  Lives at the PDA `["farm", amm, reward_mint]`, which is also the
+/// authority of its vaults.
+#[account]
+pub struct Farm {
+    pub amm: Pubkey,
+    /// Pool token mint that is staked
+    pub pool_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    /// Token account holding the funded rewards
+    pub reward_vault: Pubkey,
+    /// Token account holding the staked pool tokens
+    pub stake_vault: Pubkey,
+    /// Rewards emitted per second while anything is staked
+    pub reward_per_second: u64,
+    /// Funded rewards that have not been emitted yet
+    pub reward_remaining: u64,
+    /// Rewards emitted per staked pool token, scaled by `REWARD_PRECISION`
+    pub acc_reward_per_share: u128,
+    /// Time up to which rewards have been emitted
+    pub last_update_ts: i64,
+    pub total_staked: u64,
+    pub bump_seed: u8,
+}
+
+impl Farm {
+    /// Emit the rewards accrued since the last update, never more than what
+    /// has been funded.  Nothing is emitted while nothing is staked.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        if now <= self.last_update_ts {
+            return Ok(());
+        }
+        if self.total_staked > 0 {
+            let elapsed = u64::try_from(now - self.last_update_ts)
+                .map_err(|_| SwapError::ConversionFailure)?;
+            let reward = elapsed
+                .checked_mul(self.reward_per_second)
+                .map_or(self.reward_remaining, |reward| {
+                    std::cmp::min(reward, self.reward_remaining)
+                });
+            self.acc_reward_per_share = u128::from(reward)
+                .checked_mul(REWARD_PRECISION)
+                .and_then(|x| x.checked_div(u128::from(self.total_staked)))
+                .and_then(|x| x.checked_add(self.acc_reward_per_share))
+                .ok_or(SwapError::CalculationFailure)?;
+            self.reward_remaining -= reward;
+        }
+        self.last_update_ts = now;
+        Ok(())
+    }
+
+    /// Rewards accrued by `amount` staked pool tokens since the farm started
+    pub fn accrued_reward(&self, amount: u64) -> Result<u128> {
+        u128::from(amount)
+            .checked_mul(self.acc_reward_per_share)
+            .map(|x| x / REWARD_PRECISION)
+            .ok_or_else(|| SwapError::CalculationFailure.into())
+    }
+}
+
+/// A user's stake in a farm.  Lives at the PDA `["stake", farm, owner]`.
+#[account]
+pub struct UserStake {
+    pub farm: Pubkey,
+    pub owner: Pubkey,
+    /// Staked pool tokens
+    pub amount: u64,
+    /// Rewards accrued by `amount` before it was last changed, which are not
+    /// owed to the user
+    pub reward_debt: u128,
+    /// Rewards owed to the user and not yet claimed
+    pub pending_reward: u64,
+}
+
+impl UserStake {
+    /// Move the rewards accrued since the last change into `pending_reward`.
+    /// Must be called after `Farm::update` and before changing `amount`.
+    pub fn settle(&mut self, farm: &Farm) -> Result<()> {
+        let reward = farm
+            .accrued_reward(self.amount)?
+            .checked_sub(self.reward_debt)
+            .ok_or(SwapError::CalculationFailure)?;
+        let reward = u64::try_from(reward).map_err(|_| SwapError::ConversionFailure)?;
+        self.pending_reward = self
+            .pending_reward
+            .checked_add(reward)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.reward_debt = farm.accrued_reward(self.amount)?;
+        Ok(())
+    }
+
+    /// Change the staked amount, after `settle`
+    pub fn set_amount(&mut self, farm: &Farm, amount: u64) -> Result<()> {
+        self.amount = amount;
+        self.reward_debt = farm.accrued_reward(amount)?;
+        Ok(())
+    }
+}
+
+/// Receipt for pool tokens locked in an escrow, owned by whoever may withdraw
+/// them once unlocked.  Ownership can be transferred.
+#[account]
+pub struct LockReceipt {
+    pub amm: Pubkey,
+    pub owner: Pubkey,
+    pub pool_mint: Pubkey,
+    /// Token account holding the locked pool tokens
+    pub escrow: Pubkey,
+    /// Pool tokens locked
+    pub amount: u64,
+    /// Pool tokens withdrawn so far
+    pub withdrawn_amount: u64,
+    /// Time before which nothing can be withdrawn
+    pub unlock_ts: i64,
+    /// Time at which everything is unlocked.  Between `unlock_ts` and this,
+    /// the pool tokens unlock linearly.  Equal to `unlock_ts` without vesting.
+    pub vesting_end_ts: i64,
+}
+
+impl LockReceipt {
+    /// Pool tokens unlocked at `now`, including those already withdrawn
+    pub fn unlocked_amount(&self, now: i64) -> Result<u64> {
+        if now < self.unlock_ts {
+            return Ok(0);
+        }
+        if now >= self.vesting_end_ts {
+            return Ok(self.amount);
+        }
+        let unlocked = i128::from(self.amount)
+            .checked_mul(i128::from(now - self.unlock_ts))
+            .and_then(|x| x.checked_div(i128::from(self.vesting_end_ts - self.unlock_ts)))
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(u64::try_from(unlocked).map_err(|_| SwapError::ConversionFailure)?)
+    }
+}
+
+/// Resting limit order, swapping `amount_in` of `source_mint` for at least
+/// `minimum_amount_out` once the pool's price allows it.  The order account
+/// holds the keeper's bounty in lamports on top of its rent.
+#[account]
+pub struct Order {
+    pub amm: Pubkey,
+    pub owner: Pubkey,
+    /// Mint of the escrowed tokens
+    pub source_mint: Pubkey,
+    /// Token account holding the escrowed tokens
+    pub escrow: Pubkey,
+    /// Owner's token account receiving the output
+    pub destination: Pubkey,
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    /// Lamports paid to the keeper executing the order
+    pub bounty: u64,
+    /// Time after which the order can't be executed, 0 for never
+    pub expiry_ts: i64,
+}
+
+impl Order {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry_ts != 0 && now > self.expiry_ts
+    }
+}
+
+/// Dollar-cost averaging schedule, swapping the escrowed source tokens in
+/// `tranche_count` equal tranches, one per `interval_secs`
+#[account]
+pub struct Dca {
+    pub amm: Pubkey,
+    pub owner: Pubkey,
+    /// Mint of the tokens being sold
+    pub source_mint: Pubkey,
+    /// Token account holding the tokens still to be sold
+    pub source_escrow: Pubkey,
+    /// Token account accumulating the bought tokens until withdrawn
+    pub output_escrow: Pubkey,
+    pub tranche_count: u64,
+    pub tranches_done: u64,
+    pub interval_secs: i64,
+    /// Time from which the next tranche can be cranked
+    pub next_crank_ts: i64,
+    /// Largest shortfall of a tranche's output against the pool TWAP since the
+    /// previous tranche, in basis points.  Must also cover the trading fees.
+    pub max_slippage_bps: u64,
+    /// Pool's cumulative price for the source token at `last_crank_ts`
+    pub price_cumulative_last: u128,
+    /// Time of the previous tranche, or of the schedule's creation
+    pub last_crank_ts: i64,
+    /// Source tokens sold so far
+    pub total_in: u64,
+    /// Tokens bought so far, withdrawn or not
+    pub total_out: u64,
+}
+
+/// Pool of 2 to `MAX_TOKENS_IN_MULTI_POOL` tokens priced on the StableSwap
+/// invariant.  Only the first `token_count` entries of each array are used.
+#[account]
+pub struct MultiPool {
+    /// Bump seed of the pool authority
+    pub bump_seed: u8,
+    /// Account allowed to perform administrative operations on the pool
+    pub admin: Pubkey,
+    /// Address of pool token mint
+    pub pool_mint: Pubkey,
+    /// Address of pool fee account
+    pub pool_fee_account: Pubkey,
+    /// Fees associated with swap
+    pub fees: FeesInput,
+    /// Amplification coefficient of the invariant
+    pub amp: u64,
+    /// Number of tokens in the pool
+    pub token_count: u8,
+    pub mints: [Pubkey; MAX_TOKENS_IN_MULTI_POOL],
+    /// Token accounts holding each token, owned by the pool authority
+    pub vaults: [Pubkey; MAX_TOKENS_IN_MULTI_POOL],
+    /// Reserves used for pricing
+    pub reserves: [u64; MAX_TOKENS_IN_MULTI_POOL],
+    /// Factor bringing each token's amounts to the precision of the token
+    /// with the most decimals
+    pub precision_multipliers: [u64; MAX_TOKENS_IN_MULTI_POOL],
+}
+
+impl MultiPool {
+    /// Serialized size of the account, without the discriminator
+    pub const LEN: usize = 1 + 32 * 3 + FeesInput::LEN + 8 + 1
+        + (32 * 2 + 8 * 2) * MAX_TOKENS_IN_MULTI_POOL;
+
+    /// Index of the token held in `vault`
+    pub fn token_index(&self, vault: &Pubkey) -> Result<usize> {
+        let index = self.vaults[..usize::from(self.token_count)]
+            .iter()
+            .position(|v| v == vault)
+            .ok_or(SwapError::IncorrectSwapAccount)?;
+        Ok(index)
+    }
+
+    /// Bring an amount of the token at `index` to the common precision
+    pub fn normalize(&self, index: usize, amount: u64) -> Result<u128> {
+        let amount = u128::from(amount)
+            .checked_mul(u128::from(self.precision_multipliers[index]))
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(amount)
+    }
+
+    /// Bring an amount at the common precision back to the token at `index`,
+    /// rounding down
+    pub fn denormalize(&self, index: usize, amount: u128) -> Result<u64> {
+        let amount = amount / u128::from(self.precision_multipliers[index]);
+        Ok(u64::try_from(amount).map_err(|_| SwapError::ConversionFailure)?)
+    }
+
+    /// Amount of the token at `index` backing `pool_tokens` out of
+    /// `pool_supply`
+    pub fn proportional_amount(
+        &self,
+        index: usize,
+        pool_tokens: u64,
+        pool_supply: u64,
+        round_direction: RoundDirection,
+    ) -> Result<u64> {
+        if pool_supply == 0 {
+            return Err(SwapError::EmptySupply.into());
+        }
+        let numerator = u128::from(self.reserves[index])
+            .checked_mul(u128::from(pool_tokens))
+            .ok_or(SwapError::CalculationFailure)?;
+        let amount = match round_direction {
+            RoundDirection::Floor => numerator / u128::from(pool_supply),
+            RoundDirection::Ceiling => numerator
+                .checked_add(u128::from(pool_supply) - 1)
+                .ok_or(SwapError::CalculationFailure)?
+                / u128::from(pool_supply),
+        };
+        Ok(u64::try_from(amount).map_err(|_| SwapError::ConversionFailure)?)
+    }
+
+    /// Reserves at the common precision
+    pub fn normalized_reserves(&self) -> Result<Vec<u128>> {
+        let mut reserves = Vec::with_capacity(usize::from(self.token_count));
+        for k in 0..usize::from(self.token_count) {
+            reserves.push(self.normalize(k, self.reserves[k])?);
+        }
+        Ok(reserves)
+    }
+
+    /// StableSwap invariant `D` of the reserves, the value backing the pool
+    /// tokens
+    pub fn invariant(&self) -> Result<PreciseNumber> {
+        let d = stable_swap::compute_d(self.amp, &self.normalized_reserves()?)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(PreciseNumber::new(d).ok_or(SwapError::CalculationFailure)?)
+    }
+
+    /// Snapshot the invariant backing the given pool token supply, before an
+    /// operation
+    pub fn share_value(&self, pool_mint_supply: u64) -> Result<ShareValue> {
+        Ok(ShareValue {
+            value: self.invariant()?,
+            supply: pool_mint_supply,
+        })
+    }
+
+    /// Check that the invariant per pool token didn't drop since `before`,
+    /// after an operation.  Multi-asset pools round every pool token amount
+    /// down, so only the invariant's own rounding is forgiven.
+    pub fn check_share_value(&self, before: &ShareValue, pool_mint_supply: u64) -> Result<()> {
+        before.check(self.invariant()?, pool_mint_supply, 0)
+    }
+
+    /// Add tokens moved into the vault at `index` to its reserve
+    pub fn credit_reserve(&mut self, index: usize, amount: u64) -> Result<()> {
+        self.reserves[index] = self.reserves[index]
+            .checked_add(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Remove tokens moved out of the vault at `index` from its reserve
+    pub fn debit_reserve(&mut self, index: usize, amount: u64) -> Result<()> {
+        self.reserves[index] = self.reserves[index]
+            .checked_sub(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+}
+
+/// Conversion of a pool's old pool tokens into the pool tokens minted by
+/// `migrate_pool`
+#[account]
+pub struct Migration {
+    /// Pool that was migrated
+    pub amm: Pubkey,
+    /// Pool token mint before the migration
+    pub old_pool_mint: Pubkey,
+    /// Pool token mint after the migration
+    pub new_pool_mint: Pubkey,
+    /// Old pool tokens that can be exchanged, the old supply less the locked
+    /// minimum liquidity
+    pub old_supply: u64,
+    /// New pool tokens in escrow for them
+    pub new_supply: u64,
+}
+
+/// Cumulative trading statistics of a pool, updated by every swap
+#[derive(Clone, Copy,AnchorSerialize,AnchorDeserialize,Default)]
+pub struct PoolStats {
+    /// Total token A swapped through the pool, in either direction
+    pub volume_a: u128,
+    /// Total token B swapped through the pool, in either direction
+    pub volume_b: u128,
+    /// Trade fees kept by the pool, in token A
+    pub trade_fee_a: u128,
+    /// Trade fees kept by the pool, in token B
+    pub trade_fee_b: u128,
+    /// Owner trade fees, in token A
+    pub owner_fee_a: u128,
+    /// Owner trade fees, in token B
+    pub owner_fee_b: u128,
+    /// Pool tokens minted to hosts out of the owner fees
+    pub host_fee: u128,
+    /// Number of swaps
+    pub swap_count: u64,
+    /// Slot of the last swap
+    pub last_trade_slot: u64,
+}
+
+impl PoolStats {
+    /// Serialized size
+    pub const LEN: usize = 16 * 7 + 8 * 2;
+
+    /// Add a swap to the statistics.  Fees are charged in the source token.
+    /// The counters saturate rather than fail the swap once they overflow.
+    pub fn record_swap(
+        &mut self,
+        trade_direction: TradeDirection,
+        result: &SwapResult,
+        host_fee: u128,
+        slot: u64,
+    ) {
+        let (volume_source, volume_destination, trade_fee, owner_fee) = match trade_direction {
+            TradeDirection::AtoB => (
+                &mut self.volume_a,
+                &mut self.volume_b,
+                &mut self.trade_fee_a,
+                &mut self.owner_fee_a,
+            ),
+            TradeDirection::BtoA => (
+                &mut self.volume_b,
+                &mut self.volume_a,
+                &mut self.trade_fee_b,
+                &mut self.owner_fee_b,
+            ),
+        };
+        *volume_source = volume_source.saturating_add(result.source_amount_swapped);
+        *volume_destination = volume_destination.saturating_add(result.destination_amount_swapped);
+        *trade_fee = trade_fee.saturating_add(result.trade_fee);
+        *owner_fee = owner_fee.saturating_add(result.owner_fee);
+        self.host_fee = self.host_fee.saturating_add(host_fee);
+        self.swap_count = self.swap_count.saturating_add(1);
+        self.last_trade_slot = slot;
+    }
+}
+
 #[derive(Clone, Copy,AnchorSerialize,AnchorDeserialize,Default)]
 pub struct CurveInput {
     pub curve_type: u8,
     pub curve_parameters: u64,
 }
 
+impl FeesInput {
+    /// Serialized size
+    pub const LEN: usize = 8 * 8;
+}
+
+impl CurveInput {
+    /// Serialized size
+    pub const LEN: usize = 1 + 8;
+}
+
 /// Build Curve object and Fee object
 pub fn build_curve(curve_input: &CurveInput) -> Result<SwapCurve> {
-    let curve_type = CurveType::try_from(curve_input.curve_type).unwrap();
+    let curve_type = CurveType::try_from(curve_input.curve_type)
+        .map_err(|_| SwapError::UnsupportedCurveType)?;
     let culculator: Box<dyn CurveCalculator> = match curve_type {
         CurveType::ConstantProduct => Box::new(ConstantProductCurve {}),
         // CurveType::ConstantPrice => Box::new(ConstantPriceCurve {
         //     token_b_price: curve_input.curve_parameters,
         // }),
-        // CurveType::Stable => Box::new(StableCurve {
-        //     amp: curve_input.curve_parameters,
-        // }),
+        CurveType::Stable => Box::new(StableCurve {
+            amp: curve_input.curve_parameters,
+        }),
         // CurveType::Offset => Box::new(OffsetCurve {
         //     token_b_offset: curve_input.curve_parameters,
         // }),
@@ -85,4 +897,400 @@ pub fn build_fees(fees_input: &FeesInput) -> Result<CurveFees> {
         host_fee_denominator: fees_input.host_fee_denominator,
     };
     Ok(fees)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_amm() -> LegacyAmm {
+        LegacyAmm {
+            initializer_key: Pubkey::default(),
+            initializer_deposit_token_account: Pubkey::default(),
+            initializer_receive_token_account: Pubkey::default(),
+            initializer_amount: 0,
+            taker_amount: 0,
+            is_initialized: true,
+            bump_seed: 254,
+            token_program_id: Pubkey::new_unique(),
+            token_a_account: Pubkey::new_unique(),
+            token_b_account: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            pool_fee_account: Pubkey::new_unique(),
+            fees: FeesInput {
+                trade_fee_numerator: 25,
+                trade_fee_denominator: 10_000,
+                ..FeesInput::default()
+            },
+            curve: CurveInput::default(),
+        }
+    }
+
+    #[test]
+    fn legacy_amm_layout() {
+        // the unversioned `Amm`: three keys and two amounts of an escrow
+        // that were never set, then the pool fields up to the curve
+        let legacy = legacy_amm();
+        let data = legacy.try_to_vec().unwrap();
+        assert_eq!(
+            data.len(),
+            32 * 3 + 8 * 2 + 1 + 1 + 32 * 7 + FeesInput::LEN + CurveInput::LEN
+        );
+        // `version` reads the first byte of the unset `initializer_key`
+        assert_eq!(data[0], 0);
+        assert_eq!(data[112..114], [1, 254]);
+        assert_eq!(data[114..146], legacy.token_program_id.to_bytes());
+        assert_eq!(data[306..338], legacy.pool_fee_account.to_bytes());
+        assert_eq!(data[338..346], 25u64.to_le_bytes());
+    }
+
+    #[test]
+    fn upgrade_legacy_amm() {
+        let legacy = legacy_amm();
+        let pool_mint = legacy.pool_mint;
+        let admin = Pubkey::new_unique();
+        let amm = legacy.upgrade(1_000, 2_000, admin);
+
+        let mut data = Vec::new();
+        amm.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + Amm::LEN);
+        let amm = Amm::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(amm.version, AMM_VERSION);
+        assert_eq!(amm.bump_seed, 254);
+        assert_eq!(amm.pool_mint, pool_mint);
+        assert_eq!(amm.fees.trade_fee_denominator, 10_000);
+        assert_eq!((amm.reserve_a, amm.reserve_b), (1_000, 2_000));
+        assert_eq!(amm.admin, admin);
+        assert_eq!(amm.skim_recipient, admin);
+    }
+
+    fn farm(reward_per_second: u64, reward_remaining: u64) -> Farm {
+        Farm {
+            amm: Pubkey::default(),
+            pool_mint: Pubkey::default(),
+            reward_mint: Pubkey::default(),
+            reward_vault: Pubkey::default(),
+            stake_vault: Pubkey::default(),
+            reward_per_second,
+            reward_remaining,
+            acc_reward_per_share: 0,
+            last_update_ts: 0,
+            total_staked: 0,
+            bump_seed: 0,
+        }
+    }
+
+    fn stake(farm: &mut Farm, user_stake: &mut UserStake, now: i64, amount: u64) {
+        farm.update(now).unwrap();
+        user_stake.settle(farm).unwrap();
+        user_stake.set_amount(farm, user_stake.amount + amount).unwrap();
+        farm.total_staked += amount;
+    }
+
+    fn user_stake() -> UserStake {
+        UserStake {
+            farm: Pubkey::default(),
+            owner: Pubkey::default(),
+            amount: 0,
+            reward_debt: 0,
+            pending_reward: 0,
+        }
+    }
+
+    #[test]
+    fn farm_splits_rewards_by_stake_and_time() {
+        let mut farm = farm(10, 1_000_000);
+        let (mut alice, mut bob) = (user_stake(), user_stake());
+        // nothing is emitted before anyone stakes
+        stake(&mut farm, &mut alice, 100, 1_000);
+        assert_eq!(farm.reward_remaining, 1_000_000);
+        // alice alone for 10 seconds, then a quarter of the stake for 20
+        stake(&mut farm, &mut bob, 110, 3_000);
+        farm.update(130).unwrap();
+        alice.settle(&farm).unwrap();
+        bob.settle(&farm).unwrap();
+        assert_eq!(alice.pending_reward, 100 + 50);
+        assert_eq!(bob.pending_reward, 150);
+        assert_eq!(farm.reward_remaining, 1_000_000 - 300);
+    }
+
+    #[test]
+    fn farm_emits_no_more_than_funded() {
+        let mut farm = farm(10, 250);
+        let mut alice = user_stake();
+        stake(&mut farm, &mut alice, 0, 7);
+        farm.update(1_000).unwrap();
+        alice.settle(&farm).unwrap();
+        assert_eq!(farm.reward_remaining, 0);
+        // rounding down per share leaves at most one token per staked token
+        assert!(alice.pending_reward <= 250 && alice.pending_reward >= 250 - 7);
+        // the clock going backwards emits nothing
+        farm.update(500).unwrap();
+        assert_eq!(farm.last_update_ts, 1_000);
+    }
+
+    #[test]
+    fn lock_receipt_vests_linearly() {
+        let receipt = LockReceipt {
+            amm: Pubkey::default(),
+            owner: Pubkey::default(),
+            pool_mint: Pubkey::default(),
+            escrow: Pubkey::default(),
+            amount: 1_000,
+            withdrawn_amount: 0,
+            unlock_ts: 100,
+            vesting_end_ts: 200,
+        };
+        assert_eq!(receipt.unlocked_amount(99).unwrap(), 0);
+        assert_eq!(receipt.unlocked_amount(100).unwrap(), 0);
+        assert_eq!(receipt.unlocked_amount(133).unwrap(), 330);
+        assert_eq!(receipt.unlocked_amount(199).unwrap(), 990);
+        assert_eq!(receipt.unlocked_amount(200).unwrap(), 1_000);
+        assert_eq!(receipt.unlocked_amount(i64::MAX).unwrap(), 1_000);
+
+        // without vesting everything unlocks at once
+        let cliff = LockReceipt {
+            vesting_end_ts: 100,
+            ..receipt
+        };
+        assert_eq!(cliff.unlocked_amount(99).unwrap(), 0);
+        assert_eq!(cliff.unlocked_amount(100).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn order_expiry() {
+        let order = |expiry_ts| Order {
+            amm: Pubkey::default(),
+            owner: Pubkey::default(),
+            source_mint: Pubkey::default(),
+            escrow: Pubkey::default(),
+            destination: Pubkey::default(),
+            amount_in: 1,
+            minimum_amount_out: 1,
+            bounty: 0,
+            expiry_ts,
+        };
+        assert!(!order(0).is_expired(i64::MAX));
+        assert!(!order(100).is_expired(100));
+        assert!(order(100).is_expired(101));
+    }
+
+    #[test]
+    fn spot_price_is_quote_per_base() {
+        assert_eq!(spot_price(1_000, 2_000), 2 << PRICE_FRACTIONAL_BITS);
+        assert_eq!(spot_price(2_000, 1_000), 1 << (PRICE_FRACTIONAL_BITS - 1));
+    }
+
+    #[test]
+    fn price_cumulative_weights_prices_by_time() {
+        let mut amm = legacy_amm().upgrade(1_000, 2_000, Pubkey::default());
+        // a pool that never accumulated starts from now
+        amm.update_price_cumulative(100);
+        assert_eq!((amm.price_a_cumulative, amm.last_price_update_ts), (0, 100));
+
+        // 2 B per A for 10 seconds, then 8 B per A for 30 seconds
+        amm.update_price_cumulative(110);
+        let reading = amm.price_a_cumulative;
+        amm.reserve_a = 500;
+        amm.reserve_b = 4_000;
+        amm.update_price_cumulative(140);
+        assert_eq!(amm.price_a_cumulative / 40, ((2 * 10 + 8 * 30) << PRICE_FRACTIONAL_BITS) / 40);
+        assert_eq!(
+            amm.price_b_cumulative,
+            (1 << PRICE_FRACTIONAL_BITS) / 2 * 10 + (1 << PRICE_FRACTIONAL_BITS) / 8 * 30
+        );
+        // the TWAP between two readings is the price that held in between
+        assert_eq!(
+            amm.price_a_cumulative.wrapping_sub(reading) / 30,
+            8 << PRICE_FRACTIONAL_BITS
+        );
+
+        // readings stay comparable across a wrap
+        amm.price_a_cumulative = u128::MAX;
+        let reading = amm.price_a_cumulative;
+        amm.update_price_cumulative(150);
+        assert_eq!(
+            amm.price_a_cumulative.wrapping_sub(reading),
+            (8 << PRICE_FRACTIONAL_BITS) * 10
+        );
+    }
+
+    #[test]
+    fn ema_price_moves_by_share_of_window() {
+        let mut amm = legacy_amm().upgrade(1_000, 1_000, Pubkey::default());
+        // without an average yet, the spot price is the average
+        assert_eq!(amm.ema_price_at(0), 1 << PRICE_FRACTIONAL_BITS);
+
+        amm.ema_price = 1 << PRICE_FRACTIONAL_BITS;
+        amm.last_price_update_ts = 1_000;
+        amm.reserve_b = 3_000;
+        assert_eq!(amm.ema_price_at(1_000), 1 << PRICE_FRACTIONAL_BITS);
+        assert_eq!(amm.ema_price_at(1_000 + EMA_WINDOW_SECS / 2), 2 << PRICE_FRACTIONAL_BITS);
+        assert_eq!(amm.ema_price_at(1_000 + EMA_WINDOW_SECS * 5), 3 << PRICE_FRACTIONAL_BITS);
+    }
+
+    #[test]
+    fn price_band_bounds_moves_from_anchor_price() {
+        let mut amm = legacy_amm().upgrade(1_000_000, 1_000_000, Pubkey::default());
+        amm.anchor_price = spot_price(amm.reserve_a, amm.reserve_b);
+        // off by default
+        amm.reserve_b = 2_000_000;
+        assert!(amm.check_price_band().is_ok());
+
+        amm.mev_band_bps = 100;
+        assert!(amm.check_price_band().is_err());
+        amm.reserve_b = 1_010_000;
+        assert!(amm.check_price_band().is_ok());
+        amm.reserve_b = 1_010_200;
+        assert!(amm.check_price_band().is_err());
+        amm.reserve_b = 990_000;
+        assert!(amm.check_price_band().is_ok());
+        amm.reserve_b = 989_000;
+        assert!(amm.check_price_band().is_err());
+    }
+
+    #[test]
+    fn share_value_allows_only_rounding() {
+        // 1_000 of value per pool token, so that a pool token is more than the
+        // unit of value the check forgives
+        let mut amm = legacy_amm().upgrade(1_000_000, 1_000_000, Pubkey::default());
+        let before = amm.share_value(1_000).unwrap();
+        let check = |amm: &Amm, supply, rounded_up| {
+            amm.check_share_value(&before, supply, rounded_up)
+                .map_err(|err| match err {
+                    Error::AnchorError(err) => {
+                        err.error_code_number == u32::from(SwapError::ShareValueDecreased)
+                    }
+                    _ => false,
+                })
+        };
+
+        // a proportional deposit
+        amm.reserve_a = 1_100_000;
+        amm.reserve_b = 1_100_000;
+        assert_eq!(check(&amm, 1_100, 0), Ok(()));
+        // one pool token too many is only forgiven when minted rounded up
+        assert_eq!(check(&amm, 1_101, 0), Err(true));
+        assert_eq!(check(&amm, 1_101, 1), Ok(()));
+        assert_eq!(check(&amm, 1_102, 1), Err(true));
+
+        // a swap paying out more than the curve allows
+        amm.reserve_a = 1_001_000;
+        amm.reserve_b = 999_000;
+        assert_eq!(check(&amm, 1_000, 0), Ok(()));
+        amm.reserve_b = 998_000;
+        assert_eq!(check(&amm, 1_000, 0), Err(true));
+
+        // a withdrawal taking more than its share
+        amm.reserve_a = 899_000;
+        amm.reserve_b = 900_000;
+        assert_eq!(check(&amm, 900, 0), Err(true));
+    }
+
+    #[test]
+    fn vault_excess_requires_vaults_above_reserves() {
+        let amm = legacy_amm().upgrade(1_000, 2_000, Pubkey::default());
+        assert_eq!(amm.vault_excess(1_500, 2_000).unwrap(), (500, 0));
+        for (vault_a, vault_b) in [(999, 2_000), (1_000, 1_999)] {
+            match amm.vault_excess(vault_a, vault_b) {
+                Err(Error::AnchorError(err)) => assert_eq!(
+                    err.error_code_number,
+                    u32::from(SwapError::VaultBelowReserves)
+                ),
+                _ => panic!("expected VaultBelowReserves"),
+            }
+        }
+    }
+
+    fn swap_result(source: u128, destination: u128, trade_fee: u128, owner_fee: u128) -> SwapResult {
+        SwapResult {
+            new_swap_source_amount: 0,
+            new_swap_destination_amount: 0,
+            source_amount_swapped: source,
+            destination_amount_swapped: destination,
+            trade_fee,
+            owner_fee,
+        }
+    }
+
+    #[test]
+    fn record_swap_counts_both_directions() {
+        let mut stats = PoolStats::default();
+        stats.record_swap(TradeDirection::AtoB, &swap_result(1_000, 990, 3, 1), 0, 7);
+        stats.record_swap(TradeDirection::BtoA, &swap_result(500, 490, 2, 1), 1, 9);
+
+        assert_eq!((stats.volume_a, stats.volume_b), (1_490, 1_490));
+        assert_eq!((stats.trade_fee_a, stats.trade_fee_b), (3, 2));
+        assert_eq!((stats.owner_fee_a, stats.owner_fee_b), (1, 1));
+        assert_eq!(stats.host_fee, 1);
+        assert_eq!(stats.swap_count, 2);
+        assert_eq!(stats.last_trade_slot, 9);
+    }
+
+    #[test]
+    fn record_swap_saturates() {
+        let mut stats = PoolStats {
+            volume_a: u128::MAX - 1,
+            trade_fee_a: u128::MAX,
+            host_fee: u128::MAX,
+            swap_count: u64::MAX,
+            ..PoolStats::default()
+        };
+        stats.record_swap(TradeDirection::AtoB, &swap_result(1_000, 990, 3, 1), 1, 7);
+
+        assert_eq!(stats.volume_a, u128::MAX);
+        assert_eq!(stats.volume_b, 990);
+        assert_eq!(stats.trade_fee_a, u128::MAX);
+        assert_eq!(stats.owner_fee_a, 1);
+        assert_eq!(stats.host_fee, u128::MAX);
+        assert_eq!(stats.swap_count, u64::MAX);
+        assert_eq!(stats.last_trade_slot, 7);
+    }
+
+    #[test]
+    fn multi_pool_share_value_tracks_invariant() {
+        let mut pool = MultiPool {
+            bump_seed: 254,
+            admin: Pubkey::default(),
+            pool_mint: Pubkey::new_unique(),
+            pool_fee_account: Pubkey::new_unique(),
+            fees: FeesInput::default(),
+            amp: 100,
+            token_count: 3,
+            mints: [Pubkey::default(); MAX_TOKENS_IN_MULTI_POOL],
+            vaults: [Pubkey::default(); MAX_TOKENS_IN_MULTI_POOL],
+            reserves: [0; MAX_TOKENS_IN_MULTI_POOL],
+            precision_multipliers: [1; MAX_TOKENS_IN_MULTI_POOL],
+        };
+        pool.reserves[..3].copy_from_slice(&[1_000_000, 1_000_000, 1_000_000]);
+        let before = pool.share_value(3_000).unwrap();
+        let check = |pool: &MultiPool, supply| {
+            pool.check_share_value(&before, supply)
+                .map_err(|err| match err {
+                    Error::AnchorError(err) => {
+                        err.error_code_number == u32::from(SwapError::ShareValueDecreased)
+                    }
+                    _ => false,
+                })
+        };
+
+        // a proportional deposit
+        pool.reserves[..3].copy_from_slice(&[1_100_000, 1_100_000, 1_100_000]);
+        assert_eq!(check(&pool, 3_300), Ok(()));
+        assert_eq!(check(&pool, 3_301), Err(true));
+
+        // a swap paying out more than it took in
+        pool.reserves[..3].copy_from_slice(&[1_001_000, 998_000, 1_000_000]);
+        assert_eq!(check(&pool, 3_000), Err(true));
+
+        // a single-sided withdrawal taking more than its share
+        pool.reserves[..3].copy_from_slice(&[1_000_000, 1_000_000, 899_000]);
+        assert_eq!(check(&pool, 2_900), Err(true));
+        // a proportional withdrawal
+        pool.reserves[..3].copy_from_slice(&[900_000, 900_000, 900_000]);
+        assert_eq!(check(&pool, 2_700), Ok(()));
+        assert_eq!(check(&pool, 2_701), Err(true));
+    }
+}
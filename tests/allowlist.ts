@@ -0,0 +1,288 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { AccountMeta, Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, findAddress, fundPayer, createPool } from "./utils";
+
+describe("permissioned pools", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  const intruder = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+  let native: Record<string, PublicKey>;
+  let wsolAccount: PublicKey;
+
+  const entryOf = (amm: PublicKey, user: PublicKey) =>
+    findAddress(program, "allowlist", amm, user);
+  const remaining = (entry: PublicKey): AccountMeta[] => [
+    { pubkey: entry, isWritable: false, isSigner: false },
+  ];
+
+  const setPermissioned = (
+    target: Record<string, PublicKey>,
+    permissioned: boolean,
+    admin = payer
+  ) =>
+    program.methods
+      .setPermissioned(permissioned)
+      .accounts({ amm: target.amm, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+  const addToAllowlist = async (
+    target: Record<string, PublicKey>,
+    user: PublicKey,
+    admin = payer
+  ) =>
+    program.methods
+      .addToAllowlist(user)
+      .accounts({
+        amm: target.amm,
+        admin: admin.publicKey,
+        allowlistEntry: await entryOf(target.amm, user),
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+  const removeFromAllowlist = async (
+    target: Record<string, PublicKey>,
+    user: PublicKey,
+    admin = payer
+  ) =>
+    program.methods
+      .removeFromAllowlist()
+      .accounts({
+        amm: target.amm,
+        admin: admin.publicKey,
+        allowlistEntry: await entryOf(target.amm, user),
+      })
+      .signers([admin])
+      .rpc();
+
+  const depositSingleAccounts = () => ({
+    amm: pool.amm,
+    authority: pool.poolAuthority,
+    owner: payer.publicKey,
+    depositReceipt: pool.depositReceipt,
+    source: pool.source0,
+    swapTokenA: pool.vault0,
+    swapTokenB: pool.vault1,
+    poolMint: pool.poolMint,
+    destination: pool.destination,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  });
+
+  const nativeAccounts = () => ({
+    amm: native.amm,
+    authority: native.poolAuthority,
+    owner: payer.publicKey,
+    depositReceipt: native.depositReceipt,
+    nativeMint: NATIVE_MINT,
+    wsolAccount,
+    userToken: native.source1,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  });
+
+  // Every instruction that checks the allowlist, called by the payer with
+  // `entries` as its remaining accounts
+  const paths: Record<string, (entries: AccountMeta[]) => Promise<string>> = {
+    swap: (entries) =>
+      program.methods
+        .swap(new BN(1_000), new BN(0))
+        .accounts({
+          poolAuthority: pool.poolAuthority,
+          amm: pool.amm,
+          vaultSourceInfo: pool.vault0,
+          vaultDestinationInfo: pool.vault1,
+          swapSource: pool.source0,
+          swapDestination: pool.source1,
+          poolMint: pool.poolMint,
+          feeAccount: pool.feeAccount,
+          owner: payer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          hostFeeAccount: PublicKey.default,
+        })
+        .remainingAccounts(entries)
+        .signers([payer])
+        .rpc(),
+    depositAll: (entries) =>
+      program.methods
+        .depositAll(new BN(1_000), new BN(1_000_000), new BN(1_000_000))
+        .accounts({
+          amm: pool.amm,
+          poolAuthority: pool.poolAuthority,
+          sourceAInfo: pool.source0,
+          sourceBInfo: pool.source1,
+          vaultTokenA: pool.vault0,
+          vaultTokenB: pool.vault1,
+          poolMint: pool.poolMint,
+          destination: pool.destination,
+          owner: payer.publicKey,
+          depositReceipt: pool.depositReceipt,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(entries)
+        .signers([payer])
+        .rpc(),
+    depositSingle: (entries) =>
+      program.methods
+        .depositSingle(new BN(1_000), new BN(0))
+        .accounts(depositSingleAccounts())
+        .remainingAccounts(entries)
+        .signers([payer])
+        .rpc(),
+    depositSingleExactOut: (entries) =>
+      program.methods
+        .depositSingleExactOut(new BN(1_000), new BN(1_000_000))
+        .accounts(depositSingleAccounts())
+        .remainingAccounts(entries)
+        .signers([payer])
+        .rpc(),
+    zapIn: (entries) =>
+      program.methods
+        .zapIn(new BN(1_000), new BN(0))
+        .accounts({
+          amm: pool.amm,
+          authority: pool.poolAuthority,
+          owner: payer.publicKey,
+          depositReceipt: pool.depositReceipt,
+          source: pool.source0,
+          dustDestination: pool.source1,
+          swapTokenA: pool.vault0,
+          swapTokenB: pool.vault1,
+          poolMint: pool.poolMint,
+          feeAccount: pool.feeAccount,
+          destination: pool.destination,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(entries)
+        .signers([payer])
+        .rpc(),
+  };
+
+  // The same for the wrapped SOL pool
+  const nativePaths: Record<string, (entries: AccountMeta[]) => Promise<string>> = {
+    swapNative: (entries) =>
+      program.methods
+        .swapNative(new BN(1_000), new BN(0), true)
+        .accounts({
+          ...nativeAccounts(),
+          swapTokenA: native.vault0,
+          swapTokenB: native.vault1,
+          poolMint: native.poolMint,
+          feeAccount: native.feeAccount,
+        })
+        .remainingAccounts(entries)
+        .signers([payer])
+        .rpc(),
+    depositAllNative: (entries) =>
+      program.methods
+        .depositAllNative(new BN(1_000), new BN(1_000_000), new BN(1_000_000))
+        .accounts({
+          ...nativeAccounts(),
+          vaultTokenA: native.vault0,
+          vaultTokenB: native.vault1,
+          poolMint: native.poolMint,
+          destination: native.destination,
+        })
+        .remainingAccounts(entries)
+        .signers([payer])
+        .rpc(),
+  };
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    await fundPayer(connection, intruder);
+    pool = await createPool(program, payer);
+    native = await createPool(program, payer, true);
+    wsolAccount = await findAddress(program, "wsol", payer.publicKey);
+    await setPermissioned(pool, true);
+    await setPermissioned(native, true);
+  });
+
+  it("only the admin turns the allowlist on or off", async () => {
+    await expectError(setPermissioned(pool, false, intruder), "ConstraintHasOne");
+    assert.isTrue((await program.account.amm.fetch(pool.amm)).permissioned);
+  });
+
+  it("only the admin adds and removes entries", async () => {
+    await expectError(
+      addToAllowlist(pool, intruder.publicKey, intruder),
+      "ConstraintHasOne"
+    );
+    await addToAllowlist(pool, intruder.publicKey);
+    await expectError(
+      removeFromAllowlist(pool, intruder.publicKey, intruder),
+      "ConstraintHasOne"
+    );
+    await removeFromAllowlist(pool, intruder.publicKey);
+  });
+
+  it("rejects swaps and deposits by a user with no entry", async () => {
+    for (const call of Object.values({ ...paths, ...nativePaths })) {
+      await expectError(call([]), "NotAllowlisted");
+    }
+  });
+
+  it("rejects an entry that is not the user's own", async () => {
+    await addToAllowlist(pool, intruder.publicKey);
+    await addToAllowlist(native, payer.publicKey);
+    // another user's entry for this pool
+    await expectError(
+      paths.swap(remaining(await entryOf(pool.amm, intruder.publicKey))),
+      "NotAllowlisted"
+    );
+    // the user's entry for another pool
+    await expectError(
+      paths.swap(remaining(await entryOf(native.amm, payer.publicKey))),
+      "NotAllowlisted"
+    );
+    // an account that is not an entry at all
+    await expectError(paths.swap(remaining(pool.vault0)), "NotAllowlisted");
+    // the right address, before the entry exists
+    await expectError(
+      paths.depositAll(remaining(await entryOf(pool.amm, payer.publicKey))),
+      "NotAllowlisted"
+    );
+  });
+
+  it("accepts swaps and deposits with the user's entry", async () => {
+    await addToAllowlist(pool, payer.publicKey);
+    const entry = remaining(await entryOf(pool.amm, payer.publicKey));
+    for (const call of Object.values(paths)) {
+      await call(entry);
+    }
+    const nativeEntry = remaining(await entryOf(native.amm, payer.publicKey));
+    for (const call of Object.values(nativePaths)) {
+      await call(nativeEntry);
+    }
+  });
+
+  it("rejects the user again once the entry is removed", async () => {
+    const entry = await entryOf(pool.amm, payer.publicKey);
+    await removeFromAllowlist(pool, payer.publicKey);
+    assert.isNull(await connection.getAccountInfo(entry));
+    await expectError(paths.swap(remaining(entry)), "NotAllowlisted");
+  });
+
+  it("lets anyone trade once the admin turns the allowlist off", async () => {
+    await setPermissioned(pool, false);
+    const tokenBBefore = await balance(connection, pool.source1);
+    await paths.swap([]);
+    assert.isAbove(await balance(connection, pool.source1), tokenBBefore);
+  });
+});
@@ -0,0 +1,128 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, transfer } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, fundPayer, createPool, swap } from "./utils";
+
+describe("circuit breaker", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  const intruder = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  const setCircuitBreaker = (bps: number, admin = payer) =>
+    program.methods
+      .setCircuitBreaker(new BN(bps))
+      .accounts({ amm: pool.amm, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+  const setPauseDuration = (seconds: number, admin = payer) =>
+    program.methods
+      .setPauseDuration(new BN(seconds))
+      .accounts({ amm: pool.amm, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+  const pausePool = (admin = payer) =>
+    program.methods
+      .pausePool()
+      .accounts({ amm: pool.amm, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+  const depositSingle = (amount: number) =>
+    program.methods
+      .depositSingle(new BN(amount), new BN(0))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        depositReceipt: pool.depositReceipt,
+        source: pool.source0,
+        swapTokenA: pool.vault0,
+        swapTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        destination: pool.destination,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([payer])
+      .rpc();
+
+  const pausedUntil = async () =>
+    (await program.account.amm.fetch(pool.amm)).pausedUntil.toNumber();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    await fundPayer(connection, intruder);
+    pool = await createPool(program, payer);
+  });
+
+  it("only the admin configures the breaker or pauses the pool", async () => {
+    await expectError(setCircuitBreaker(500, intruder), "ConstraintHasOne");
+    await expectError(setPauseDuration(3_600, intruder), "ConstraintHasOne");
+    await expectError(pausePool(intruder), "ConstraintHasOne");
+  });
+
+  it("reverts trades moving the price too far with CircuitBreakerTripped", async () => {
+    await setCircuitBreaker(500);
+    // 0.2% of the reserves moves the price by about 0.4%
+    await swap(program, payer, pool, 1_000);
+    // 4% moves it by about 8%
+    await expectError(swap(program, payer, pool, 20_000), "CircuitBreakerTripped");
+    await expectError(depositSingle(40_000), "CircuitBreakerTripped");
+
+    // the reverted trades left the pool open
+    assert.strictEqual(await pausedUntil(), 0);
+    await swap(program, payer, pool, 1_000, true);
+  });
+
+  it("can't be tripped by a trade the user can't pay for", async () => {
+    const tokenA = await balance(connection, pool.source0);
+    try {
+      await swap(program, payer, pool, tokenA * 10);
+      assert.fail("expected the transfer to fail");
+    } catch (err) {
+      assert.notInclude(String(err), "expected the transfer to fail");
+    }
+    assert.strictEqual(await pausedUntil(), 0);
+    await swap(program, payer, pool, 1_000);
+  });
+
+  it("reverts a sync of donations moving the price too far", async () => {
+    const sync = () =>
+      program.methods
+        .sync()
+        .accounts({ amm: pool.amm, vaultTokenA: pool.vault0, vaultTokenB: pool.vault1 })
+        .rpc();
+    // 8% of the token A reserve moves the price by about 8%
+    await transfer(connection, payer, pool.source0, pool.vault0, payer, 40_000);
+    await expectError(sync(), "CircuitBreakerTripped");
+
+    // balancing the donation brings the price back within the breaker
+    await transfer(connection, payer, pool.source1, pool.vault1, payer, 40_000);
+    await sync();
+    const amm = await program.account.amm.fetch(pool.amm);
+    assert.strictEqual(amm.reserveA.toNumber(), await balance(connection, pool.vault0));
+  });
+
+  it("rejects trades while the admin has the pool paused with PoolPaused", async () => {
+    await setPauseDuration(3_600);
+    await pausePool();
+    assert.isAbove(await pausedUntil(), Math.floor(Date.now() / 1000));
+    await expectError(swap(program, payer, pool, 1_000), "PoolPaused");
+    await expectError(depositSingle(1_000), "PoolPaused");
+
+    // pausing again for no time lifts the pause
+    await setPauseDuration(0);
+    await pausePool();
+    await swap(program, payer, pool, 1_000);
+  });
+});
@@ -0,0 +1,146 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, getAccount, getMint } from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  expectError,
+  balance,
+  constantProduct,
+  fundPayer,
+  createPool,
+  prepareMigration,
+} from "./utils";
+
+describe("close_pool", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+
+  const setAdmin = (pool: Record<string, PublicKey>, admin: Keypair, newAdmin: Keypair) =>
+    program.methods
+      .setAdmin()
+      .accounts({ amm: pool.amm, admin: admin.publicKey, newAdmin: newAdmin.publicKey })
+      .signers([admin, newAdmin])
+      .rpc();
+
+  const withdrawAll = async (pool: Record<string, PublicKey>) =>
+    program.methods
+      .withdrawAll(new BN(await balance(connection, pool.destination)), new BN(0), new BN(0))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        depositReceipt: pool.depositReceipt,
+        sourceInfo: pool.destination,
+        vaultTokenA: pool.vault0,
+        vaultTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        destTokenAInfo: pool.source0,
+        destTokenBInfo: pool.source1,
+        feeAccount: pool.feeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+
+  const closePool = (pool: Record<string, PublicKey>, admin: Keypair) =>
+    program.methods
+      .closePool()
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        admin: admin.publicKey,
+        receiver: admin.publicKey,
+        vaultTokenA: pool.vault0,
+        vaultTokenB: pool.vault1,
+        receiverTokenA: pool.source0,
+        receiverTokenB: pool.source1,
+        poolMint: pool.poolMint,
+        lockedLiquidity: pool.lockedLiquidity,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+  });
+
+  describe("set_admin", () => {
+    let pool: Record<string, PublicKey>;
+    const newAdmin = Keypair.generate();
+
+    before(async () => {
+      pool = await createPool(program, payer);
+      await fundPayer(connection, newAdmin);
+    });
+
+    it("rejects a caller who is not the admin", async () => {
+      await expectError(setAdmin(pool, newAdmin, newAdmin), "ConstraintHasOne");
+    });
+
+    it("hands the pool over to the new admin", async () => {
+      await setAdmin(pool, payer, newAdmin);
+      const amm = await program.account.amm.fetch(pool.amm);
+      assert.isTrue(amm.admin.equals(newAdmin.publicKey));
+      // the old admin has lost its rights
+      await expectError(setAdmin(pool, payer, payer), "ConstraintHasOne");
+    });
+  });
+
+  describe("close", () => {
+    let pool: Record<string, PublicKey>;
+
+    before(async () => {
+      pool = await createPool(program, payer);
+    });
+
+    it("refuses while LPs still hold pool tokens with PoolNotEmpty", async () => {
+      await expectError(closePool(pool, payer), "PoolNotEmpty");
+    });
+
+    it("burns the lock and returns the vaults once every LP has withdrawn", async () => {
+      await withdrawAll(pool);
+      const tokenABefore = await balance(connection, pool.source0);
+      const lockedTokenA = await balance(connection, pool.vault0);
+
+      await closePool(pool, payer);
+
+      assert.strictEqual(await balance(connection, pool.source0), tokenABefore + lockedTokenA);
+      assert.isNull(await connection.getAccountInfo(pool.amm));
+      assert.isNull(await connection.getAccountInfo(pool.vault0));
+      assert.isNull(await connection.getAccountInfo(pool.lockedLiquidity));
+      assert.strictEqual(Number((await getMint(connection, pool.poolMint)).supply), 0);
+    });
+  });
+
+  describe("close after migrate_pool", () => {
+    let pool: Record<string, PublicKey>;
+    let migrated: Record<string, PublicKey>;
+
+    before(async () => {
+      pool = await createPool(program, payer);
+      const migration = await prepareMigration(program, payer, pool, constantProduct);
+      await migration.migratePool();
+      await migration.migrateLiquidity();
+      migrated = migration.migrated;
+    });
+
+    it("moves the locked minimum liquidity to the new pool mint", async () => {
+      assert.strictEqual(await balance(connection, pool.lockedLiquidity), 0);
+      const newLocked = await getAccount(connection, migrated.lockedLiquidity);
+      assert.isAbove(Number(newLocked.amount), 0);
+      assert.isTrue(newLocked.owner.equals(pool.poolAuthority));
+    });
+
+    it("closes once every LP has withdrawn from the new pool mint", async () => {
+      await withdrawAll(migrated);
+      await closePool(migrated, payer);
+      assert.isNull(await connection.getAccountInfo(pool.amm));
+    });
+  });
+});
@@ -0,0 +1,143 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, findAddress, sleep, fundPayer, createPool } from "./utils";
+
+describe("dca", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  const cranker = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  // Schedules `amount` of token A to be sold for token B in `trancheCount`
+  // tranches a second apart and returns the schedule
+  const createDca = async (amount: number, trancheCount: number, maxSlippageBps: number) => {
+    const dca = Keypair.generate();
+    await program.methods
+      .createDca(new BN(amount), new BN(trancheCount), new BN(1), new BN(maxSlippageBps))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        sourceMint: pool.mint0,
+        outputMint: pool.mint1,
+        source: pool.source0,
+        dca: dca.publicKey,
+        sourceEscrow: await findAddress(program, "dca_source", dca.publicKey),
+        outputEscrow: await findAddress(program, "dca_output", dca.publicKey),
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([payer, dca])
+      .rpc();
+    return dca.publicKey;
+  };
+
+  const crankDca = async (dca: PublicKey) =>
+    program.methods
+      .crankDca()
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        cranker: cranker.publicKey,
+        dca,
+        sourceEscrow: await findAddress(program, "dca_source", dca),
+        outputEscrow: await findAddress(program, "dca_output", dca),
+        swapTokenA: pool.vault0,
+        swapTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        feeAccount: pool.feeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([cranker])
+      .rpc();
+
+  const closeDca = async (dca: PublicKey) =>
+    program.methods
+      .closeDca()
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        dca,
+        sourceEscrow: await findAddress(program, "dca_source", dca),
+        outputEscrow: await findAddress(program, "dca_output", dca),
+        sourceRefund: pool.source0,
+        destination: pool.source1,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    await fundPayer(connection, cranker);
+    pool = await createPool(program, payer);
+  });
+
+  it("sells one tranche per interval and pays out on withdraw and close", async () => {
+    const dca = await createDca(2_000, 2, 100);
+    // the first tranche is due one interval after creation
+    await expectError(crankDca(dca), "DcaNotDue");
+
+    await sleep(2_000);
+    await crankDca(dca);
+    let schedule = await program.account.dca.fetch(dca);
+    assert.strictEqual(schedule.tranchesDone.toNumber(), 1);
+    assert.strictEqual(schedule.totalIn.toNumber(), 1_000);
+    const outputEscrow = await findAddress(program, "dca_output", dca);
+    assert.strictEqual(await balance(connection, outputEscrow), schedule.totalOut.toNumber());
+
+    const tokenBBefore = await balance(connection, pool.source1);
+    await program.methods
+      .withdrawDca()
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        dca,
+        outputEscrow,
+        destination: pool.source1,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+    assert.strictEqual(
+      (await balance(connection, pool.source1)) - tokenBBefore,
+      schedule.totalOut.toNumber()
+    );
+
+    await sleep(2_000);
+    await crankDca(dca);
+    schedule = await program.account.dca.fetch(dca);
+    assert.strictEqual(schedule.totalIn.toNumber(), 2_000);
+    // every tranche is done
+    await sleep(2_000);
+    await expectError(crankDca(dca), "DcaNotDue");
+
+    const tokenABefore = await balance(connection, pool.source0);
+    await closeDca(dca);
+    assert.strictEqual(await balance(connection, pool.source0), tokenABefore);
+    assert.isNull(await connection.getAccountInfo(dca));
+    assert.isNull(await connection.getAccountInfo(outputEscrow));
+  });
+
+  it("rejects a tranche below the TWAP with TwapSlippageExceeded", async () => {
+    // no slippage allowed leaves nothing for the trading fee
+    const dca = await createDca(2_000, 2, 0);
+    await sleep(2_000);
+    await expectError(crankDca(dca), "TwapSlippageExceeded");
+
+    // closing refunds the unsold tokens
+    const tokenABefore = await balance(connection, pool.source0);
+    await closeDca(dca);
+    assert.strictEqual((await balance(connection, pool.source0)) - tokenABefore, 2_000);
+  });
+});
@@ -0,0 +1,62 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getMint } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, fundPayer, createPool, depositAll } from "./utils";
+
+describe("deposit caps", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  const setDepositCaps = (
+    maxReserveA: number,
+    maxReserveB: number,
+    maxPoolSupply: number,
+    admin = payer
+  ) =>
+    program.methods
+      .setDepositCaps(new BN(maxReserveA), new BN(maxReserveB), new BN(maxPoolSupply))
+      .accounts({ amm: pool.amm, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    // 500_000 of each token against 500_000 pool tokens
+    pool = await createPool(program, payer);
+  });
+
+  it("only the admin sets the caps", async () => {
+    const intruder = Keypair.generate();
+    await fundPayer(connection, intruder);
+    await expectError(setDepositCaps(1, 1, 1, intruder), "ConstraintHasOne");
+  });
+
+  it("accepts deposits up to the reserve cap and rejects those past it", async () => {
+    await setDepositCaps(510_000, 0, 0);
+    await depositAll(program, payer, pool, 5_000);
+    await expectError(depositAll(program, payer, pool, 10_000), "DepositCapExceeded");
+
+    const amm = await program.account.amm.fetch(pool.amm);
+    assert.strictEqual(amm.maxReserveA.toNumber(), 510_000);
+    assert.isAtMost(amm.reserveA.toNumber(), 510_000);
+  });
+
+  it("rejects deposits past the pool supply cap", async () => {
+    const supply = Number((await getMint(connection, pool.poolMint)).supply);
+    await setDepositCaps(0, 0, supply + 100);
+    await depositAll(program, payer, pool, 100);
+    await expectError(depositAll(program, payer, pool, 1), "DepositCapExceeded");
+  });
+
+  it("lifts a cap set back to 0", async () => {
+    await setDepositCaps(0, 0, 0);
+    await depositAll(program, payer, pool, 10_000);
+  });
+});
@@ -0,0 +1,710 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, createAccount } from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  expectError,
+  constantProduct,
+  balance,
+  findAddress,
+  fundPayer,
+  preparePool,
+  createPool,
+  prepareMigration,
+  swap,
+} from "./utils";
+
+describe("fbnx-amm errors", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+  });
+
+  it("rejects an unknown curve type with UnsupportedCurveType", async () => {
+    const { initPool } = await preparePool(
+      program,
+      payer,
+      { curveType: 7, curveParameters: new BN(0) },
+      1_000_000,
+      1_000_000
+    );
+    await expectError(initPool(), "UnsupportedCurveType");
+  });
+
+  it("rejects an empty initial deposit with EmptySupply", async () => {
+    const { initPool } = await preparePool(program, payer, constantProduct, 0, 1_000_000);
+    await expectError(initPool(), "EmptySupply");
+  });
+
+  it("rejects a dust initial deposit with InsufficientInitialLiquidity", async () => {
+    const { initPool } = await preparePool(program, payer, constantProduct, 10, 10);
+    await expectError(initPool(), "InsufficientInitialLiquidity");
+  });
+
+  describe("on an initialized pool", () => {
+    let pool: Record<string, PublicKey>;
+
+    before(async () => {
+      pool = await createPool(program, payer);
+    });
+
+    it("rejects a negative pause duration with InvalidInput", async () => {
+      await expectError(
+        program.methods
+          .setPauseDuration(new BN(-1))
+          .accounts({ amm: pool.amm, admin: payer.publicKey })
+          .signers([payer])
+          .rpc(),
+        "InvalidInput"
+      );
+    });
+
+    it("rejects a zap below the minimum pool tokens with ExceededSlippage", async () => {
+      const destination = Keypair.generate();
+      await expectError(
+        program.methods
+          .zapIn(new BN(10_000), new BN("18446744073709551615"))
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            owner: payer.publicKey,
+            depositReceipt: pool.depositReceipt,
+            source: pool.source0,
+            dustDestination: pool.source1,
+            swapTokenA: pool.vault0,
+            swapTokenB: pool.vault1,
+            poolMint: pool.poolMint,
+            feeAccount: pool.feeAccount,
+            destination: destination.publicKey,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([payer, destination])
+          .rpc(),
+        "ExceededSlippage"
+      );
+    });
+
+    // Each case passes valid accounts except for one, taken from a second
+    // pool or from another slot of the same pool
+    describe("with a substituted account", () => {
+      let other: Record<string, PublicKey>;
+
+      before(async () => {
+        other = await createPool(program, payer);
+      });
+
+      const swap = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .swap(new BN(1_000), new BN(0))
+          .accounts({
+            poolAuthority: pool.poolAuthority,
+            amm: pool.amm,
+            vaultSourceInfo: pool.vault0,
+            vaultDestinationInfo: pool.vault1,
+            swapSource: pool.source0,
+            swapDestination: pool.source1,
+            poolMint: pool.poolMint,
+            feeAccount: pool.feeAccount,
+            owner: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            hostFeeAccount: PublicKey.default,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      const depositSingle = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .depositSingle(new BN(1_000), new BN(0))
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            owner: payer.publicKey,
+            depositReceipt: pool.depositReceipt,
+            source: pool.source0,
+            swapTokenA: pool.vault0,
+            swapTokenB: pool.vault1,
+            poolMint: pool.poolMint,
+            destination: pool.destination,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      const withdrawSingle = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .withdrawSingle(new BN(1_000), new BN(1_000_000))
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            owner: payer.publicKey,
+            depositReceipt: pool.depositReceipt,
+            source: pool.destination,
+            swapTokenA: pool.vault0,
+            swapTokenB: pool.vault1,
+            poolMint: pool.poolMint,
+            destination: pool.source0,
+            feeAccount: pool.feeAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      const withdrawAll = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .withdrawAll(new BN(1_000), new BN(0), new BN(0))
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            owner: payer.publicKey,
+            depositReceipt: pool.depositReceipt,
+            sourceInfo: pool.destination,
+            vaultTokenA: pool.vault0,
+            vaultTokenB: pool.vault1,
+            poolMint: pool.poolMint,
+            destTokenAInfo: pool.source0,
+            destTokenBInfo: pool.source1,
+            feeAccount: pool.feeAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      const depositAll = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .depositAll(new BN(1_000), new BN(1_000_000), new BN(1_000_000))
+          .accounts({
+            amm: pool.amm,
+            poolAuthority: pool.poolAuthority,
+            sourceAInfo: pool.source0,
+            sourceBInfo: pool.source1,
+            vaultTokenA: pool.vault0,
+            vaultTokenB: pool.vault1,
+            poolMint: pool.poolMint,
+            destination: pool.destination,
+            owner: payer.publicKey,
+            depositReceipt: pool.depositReceipt,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      const depositSingleExactOut = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .depositSingleExactOut(new BN(1_000), new BN(1_000_000))
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            owner: payer.publicKey,
+            depositReceipt: pool.depositReceipt,
+            source: pool.source0,
+            swapTokenA: pool.vault0,
+            swapTokenB: pool.vault1,
+            poolMint: pool.poolMint,
+            destination: pool.destination,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      const withdrawSingleExactIn = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .withdrawSingleExactIn(new BN(1_000), new BN(0))
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            owner: payer.publicKey,
+            depositReceipt: pool.depositReceipt,
+            source: pool.destination,
+            swapTokenA: pool.vault0,
+            swapTokenB: pool.vault1,
+            poolMint: pool.poolMint,
+            destination: pool.source0,
+            feeAccount: pool.feeAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      const zapIn = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .zapIn(new BN(1_000), new BN(0))
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            owner: payer.publicKey,
+            depositReceipt: pool.depositReceipt,
+            source: pool.source0,
+            dustDestination: pool.source1,
+            swapTokenA: pool.vault0,
+            swapTokenB: pool.vault1,
+            poolMint: pool.poolMint,
+            feeAccount: pool.feeAccount,
+            destination: pool.destination,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      const sync = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .sync()
+          .accounts({
+            amm: pool.amm,
+            vaultTokenA: pool.vault0,
+            vaultTokenB: pool.vault1,
+            ...substitute,
+          })
+          .rpc();
+
+      const skim = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .skim()
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            vaultTokenA: pool.vault0,
+            vaultTokenB: pool.vault1,
+            recipientTokenA: pool.source0,
+            recipientTokenB: pool.source1,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ...substitute,
+          })
+          .rpc();
+
+      const closePool = (substitute: Record<string, PublicKey>) =>
+        program.methods
+          .closePool()
+          .accounts({
+            amm: pool.amm,
+            authority: pool.poolAuthority,
+            admin: payer.publicKey,
+            receiver: payer.publicKey,
+            vaultTokenA: pool.vault0,
+            vaultTokenB: pool.vault1,
+            receiverTokenA: pool.source0,
+            receiverTokenB: pool.source1,
+            poolMint: pool.poolMint,
+            lockedLiquidity: pool.lockedLiquidity,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ...substitute,
+          })
+          .signers([payer])
+          .rpc();
+
+      it("swap pays out to another wallet's account", async () => {
+        const recipient = await createAccount(
+          connection,
+          payer,
+          pool.mint1,
+          Keypair.generate().publicKey
+        );
+        await swap({ swapDestination: recipient });
+        assert.isAbove(await balance(connection, recipient), 0);
+      });
+
+      it("swap rejects the paying vault as destination", async () => {
+        await expectError(
+          swap({ swapDestination: pool.vault1 }),
+          "IncorrectSwapAccount"
+        );
+      });
+
+      it("swap rejects another pool's vault", async () => {
+        await expectError(
+          swap({ vaultSourceInfo: other.vault0 }),
+          "IncorrectSwapAccount"
+        );
+        await expectError(
+          swap({ vaultDestinationInfo: other.vault1 }),
+          "IncorrectSwapAccount"
+        );
+      });
+
+      it("swap rejects the same vault on both sides", async () => {
+        await expectError(
+          swap({ vaultDestinationInfo: pool.vault0 }),
+          "InvalidInput"
+        );
+      });
+
+      it("swap rejects a user account of the wrong mint", async () => {
+        await expectError(
+          swap({ swapSource: pool.source1 }),
+          "IncorrectSwapAccount"
+        );
+      });
+
+      it("swap rejects another pool with this pool's vaults", async () => {
+        await expectError(
+          swap({ amm: other.amm, poolAuthority: other.poolAuthority }),
+          "IncorrectSwapAccount"
+        );
+      });
+
+      it("swap rejects another pool's mint and fee account", async () => {
+        await expectError(swap({ poolMint: other.poolMint }), "IncorrectPoolMint");
+        await expectError(
+          swap({ feeAccount: other.feeAccount }),
+          "IncorrectFeeAccount"
+        );
+      });
+
+      it("deposit_single rejects another pool's vault and mint", async () => {
+        await expectError(
+          depositSingle({ swapTokenB: other.vault1 }),
+          "IncorrectSwapAccount"
+        );
+        await expectError(
+          depositSingle({ poolMint: other.poolMint, destination: other.destination }),
+          "IncorrectPoolMint"
+        );
+      });
+
+      it("withdraw_single rejects another pool's vault and fee account", async () => {
+        await expectError(
+          withdrawSingle({ swapTokenA: other.vault0 }),
+          "IncorrectSwapAccount"
+        );
+        await expectError(
+          withdrawSingle({ feeAccount: other.feeAccount }),
+          "IncorrectFeeAccount"
+        );
+      });
+
+      it("withdraw_all rejects another pool's vault, mint and fee account", async () => {
+        await expectError(
+          withdrawAll({ vaultTokenB: other.vault1 }),
+          "IncorrectSwapAccount"
+        );
+        await expectError(
+          withdrawAll({ poolMint: other.poolMint, sourceInfo: other.destination }),
+          "IncorrectPoolMint"
+        );
+        await expectError(
+          withdrawAll({ feeAccount: other.feeAccount }),
+          "IncorrectFeeAccount"
+        );
+      });
+
+      it("deposit_all rejects another pool's vault and mint", async () => {
+        await expectError(
+          depositAll({ vaultTokenA: other.vault0 }),
+          "IncorrectSwapAccount"
+        );
+        await expectError(
+          depositAll({ poolMint: other.poolMint, destination: other.destination }),
+          "IncorrectPoolMint"
+        );
+      });
+
+      it("deposit_single_exact_out rejects another pool's vault and mint", async () => {
+        await expectError(
+          depositSingleExactOut({ swapTokenA: other.vault0 }),
+          "IncorrectSwapAccount"
+        );
+        await expectError(
+          depositSingleExactOut({ poolMint: other.poolMint, destination: other.destination }),
+          "IncorrectPoolMint"
+        );
+      });
+
+      it("withdraw_single_exact_in rejects another pool's vault, mint and fee account", async () => {
+        await expectError(
+          withdrawSingleExactIn({ swapTokenB: other.vault1 }),
+          "IncorrectSwapAccount"
+        );
+        await expectError(
+          withdrawSingleExactIn({ poolMint: other.poolMint, source: other.destination }),
+          "IncorrectPoolMint"
+        );
+        await expectError(
+          withdrawSingleExactIn({ feeAccount: other.feeAccount }),
+          "IncorrectFeeAccount"
+        );
+      });
+
+      it("zap_in rejects another pool's vault, mint and fee account", async () => {
+        await expectError(zapIn({ swapTokenB: other.vault1 }), "IncorrectSwapAccount");
+        await expectError(zapIn({ poolMint: other.poolMint }), "IncorrectPoolMint");
+        await expectError(zapIn({ feeAccount: other.feeAccount }), "IncorrectFeeAccount");
+      });
+
+      it("sync and skim reject another pool's vault", async () => {
+        await expectError(sync({ vaultTokenA: other.vault0 }), "IncorrectSwapAccount");
+        await expectError(skim({ vaultTokenB: other.vault1 }), "IncorrectSwapAccount");
+      });
+
+      it("close_pool rejects another pool's vault and mint", async () => {
+        await expectError(
+          closePool({ vaultTokenB: other.vault1 }),
+          "IncorrectSwapAccount"
+        );
+        await expectError(
+          closePool({ poolMint: other.poolMint }),
+          "IncorrectPoolMint"
+        );
+        await expectError(
+          closePool({ receiverTokenA: pool.source1 }),
+          "IncorrectSwapAccount"
+        );
+      });
+
+      it("migrate_pool rejects another pool's mint and fee account", async () => {
+        const { migratePool } = await prepareMigration(program, payer, pool, constantProduct);
+        await expectError(
+          migratePool({ poolMint: other.poolMint }),
+          "IncorrectPoolMint"
+        );
+        await expectError(
+          migratePool({ feeAccount: other.feeAccount }),
+          "IncorrectFeeAccount"
+        );
+      });
+
+      it("migrate_liquidity rejects a new mint other than the migration's", async () => {
+        const migrating = await createPool(program, payer);
+        const { migratePool, migrateLiquidity } = await prepareMigration(
+          program,
+          payer,
+          migrating,
+          constantProduct
+        );
+        await migratePool();
+        await expectError(
+          migrateLiquidity({ newPoolMint: other.poolMint }),
+          "IncorrectPoolMint"
+        );
+      });
+
+      describe("of a native SOL pool", () => {
+        let native: Record<string, PublicKey>;
+        let wsolAccount: PublicKey;
+
+        before(async () => {
+          native = await createPool(program, payer, true);
+          wsolAccount = await findAddress(program, "wsol", payer.publicKey);
+        });
+
+        const nativeAccounts = () => ({
+          amm: native.amm,
+          authority: native.poolAuthority,
+          owner: payer.publicKey,
+          depositReceipt: native.depositReceipt,
+          nativeMint: NATIVE_MINT,
+          wsolAccount,
+          userToken: native.source1,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        });
+
+        const swapNative = (substitute: Record<string, PublicKey>) =>
+          program.methods
+            .swapNative(new BN(1_000), new BN(0), true)
+            .accounts({
+              ...nativeAccounts(),
+              swapTokenA: native.vault0,
+              swapTokenB: native.vault1,
+              poolMint: native.poolMint,
+              feeAccount: native.feeAccount,
+              ...substitute,
+            })
+            .signers([payer])
+            .rpc();
+
+        const depositAllNative = (substitute: Record<string, PublicKey>) =>
+          program.methods
+            .depositAllNative(new BN(1_000), new BN(1_000_000), new BN(1_000_000))
+            .accounts({
+              ...nativeAccounts(),
+              vaultTokenA: native.vault0,
+              vaultTokenB: native.vault1,
+              poolMint: native.poolMint,
+              destination: native.destination,
+              ...substitute,
+            })
+            .signers([payer])
+            .rpc();
+
+        const withdrawAllNative = (substitute: Record<string, PublicKey>) =>
+          program.methods
+            .withdrawAllNative(new BN(1_000), new BN(0), new BN(0))
+            .accounts({
+              ...nativeAccounts(),
+              sourceInfo: native.destination,
+              vaultTokenA: native.vault0,
+              vaultTokenB: native.vault1,
+              poolMint: native.poolMint,
+              feeAccount: native.feeAccount,
+              ...substitute,
+            })
+            .signers([payer])
+            .rpc();
+
+        it("swap_native rejects another pool's vault, mint and fee account", async () => {
+          await expectError(swapNative({ swapTokenB: other.vault1 }), "IncorrectSwapAccount");
+          await expectError(swapNative({ poolMint: other.poolMint }), "IncorrectPoolMint");
+          await expectError(
+            swapNative({ feeAccount: other.feeAccount }),
+            "IncorrectFeeAccount"
+          );
+        });
+
+        it("deposit_all_native rejects another pool's vault and mint", async () => {
+          await expectError(
+            depositAllNative({ vaultTokenB: other.vault1 }),
+            "IncorrectSwapAccount"
+          );
+          await expectError(
+            depositAllNative({ poolMint: other.poolMint, destination: other.destination }),
+            "IncorrectPoolMint"
+          );
+        });
+
+        it("withdraw_all_native rejects another pool's vault, mint and fee account", async () => {
+          await expectError(
+            withdrawAllNative({ vaultTokenA: other.vault0 }),
+            "IncorrectSwapAccount"
+          );
+          await expectError(
+            withdrawAllNative({ poolMint: other.poolMint, sourceInfo: other.destination }),
+            "IncorrectPoolMint"
+          );
+          await expectError(
+            withdrawAllNative({ feeAccount: other.feeAccount }),
+            "IncorrectFeeAccount"
+          );
+        });
+      });
+    });
+  });
+
+  // ShareValueDecreased and VaultBelowReserves guard invariants that no
+  // sequence of instructions can break, so the unit tests in state.rs assert
+  // their codes instead
+  describe("on a pool with its guards turned on", () => {
+    let pool: Record<string, PublicKey>;
+
+    beforeEach(async () => {
+      pool = await createPool(program, payer);
+    });
+
+    const withdrawAll = () =>
+      program.methods
+        .withdrawAll(new BN(1_000), new BN(0), new BN(0))
+        .accounts({
+          amm: pool.amm,
+          authority: pool.poolAuthority,
+          owner: payer.publicKey,
+          depositReceipt: pool.depositReceipt,
+          sourceInfo: pool.destination,
+          vaultTokenA: pool.vault0,
+          vaultTokenB: pool.vault1,
+          poolMint: pool.poolMint,
+          destTokenAInfo: pool.source0,
+          destTokenBInfo: pool.source1,
+          feeAccount: pool.feeAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([payer]);
+
+    it("rejects a user off the allowlist with NotAllowlisted", async () => {
+      await program.methods
+        .setPermissioned(true)
+        .accounts({ amm: pool.amm, admin: payer.publicKey })
+        .signers([payer])
+        .rpc();
+      await expectError(swap(program, payer, pool, 1_000), "NotAllowlisted");
+    });
+
+    it("rejects a swap past the MEV band with PriceBandExceeded", async () => {
+      await program.methods
+        .setMevProtection(new BN(100))
+        .accounts({ amm: pool.amm, admin: payer.publicKey })
+        .signers([payer])
+        .rpc();
+      // 2% of the reserves moves the price by about 4%
+      await expectError(swap(program, payer, pool, 10_000), "PriceBandExceeded");
+    });
+
+    it("rejects a withdrawal in the slot of a deposit with SameSlotWithdrawal", async () => {
+      await program.methods
+        .setMevProtection(new BN(100))
+        .accounts({ amm: pool.amm, admin: payer.publicKey })
+        .signers([payer])
+        .rpc();
+      const depositIx = await program.methods
+        .depositAll(new BN(1_000), new BN(1_000_000), new BN(1_000_000))
+        .accounts({
+          amm: pool.amm,
+          poolAuthority: pool.poolAuthority,
+          sourceAInfo: pool.source0,
+          sourceBInfo: pool.source1,
+          vaultTokenA: pool.vault0,
+          vaultTokenB: pool.vault1,
+          poolMint: pool.poolMint,
+          destination: pool.destination,
+          owner: payer.publicKey,
+          depositReceipt: pool.depositReceipt,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction();
+      await expectError(
+        withdrawAll().preInstructions([depositIx]).rpc(),
+        "SameSlotWithdrawal"
+      );
+    });
+
+    it("rejects a swap while the pool is paused with PoolPaused", async () => {
+      await program.methods
+        .setPauseDuration(new BN(3_600))
+        .accounts({ amm: pool.amm, admin: payer.publicKey })
+        .signers([payer])
+        .rpc();
+      await program.methods
+        .pausePool()
+        .accounts({ amm: pool.amm, admin: payer.publicKey })
+        .signers([payer])
+        .rpc();
+      await expectError(swap(program, payer, pool, 1_000), "PoolPaused");
+    });
+
+    it("rejects a swap past the circuit breaker with CircuitBreakerTripped", async () => {
+      await program.methods
+        .setCircuitBreaker(new BN(500))
+        .accounts({ amm: pool.amm, admin: payer.publicKey })
+        .signers([payer])
+        .rpc();
+      await swap(program, payer, pool, 1_000);
+      // 4% of the reserves moves the price by about 8%
+      await expectError(swap(program, payer, pool, 20_000), "CircuitBreakerTripped");
+    });
+  });
+});
@@ -0,0 +1,141 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN, EventParser } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, getMint, transfer } from "@solana/spl-token";
+import { assert } from "chai";
+import { balance, fundPayer, createPool, depositAll, swap } from "./utils";
+
+describe("events", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  // The single event of `name` the transaction logged
+  const eventOf = async (signature: string, name: string) => {
+    await connection.confirmTransaction(signature, "confirmed");
+    const tx = await connection.getTransaction(signature, { commitment: "confirmed" });
+    const events = [];
+    new EventParser(program.programId, program.coder).parseLogs(
+      tx.meta.logMessages,
+      (event) => events.push(event)
+    );
+    const matching = events.filter((event) => event.name === name);
+    assert.lengthOf(matching, 1);
+    return matching[0].data;
+  };
+
+  // Checks the reserves and supply an event reports against the accounts
+  const assertPoolState = async (data) => {
+    const amm = await program.account.amm.fetch(pool.amm);
+    assert.isTrue(data.amm.equals(pool.amm));
+    assert.strictEqual(data.reserveA.toNumber(), amm.reserveA.toNumber());
+    assert.strictEqual(data.reserveB.toNumber(), amm.reserveB.toNumber());
+    if (data.poolMintSupply !== undefined) {
+      const supply = Number((await getMint(connection, pool.poolMint)).supply);
+      assert.strictEqual(data.poolMintSupply.toNumber(), supply);
+    }
+  };
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer);
+  });
+
+  it("emits SwapEvent", async () => {
+    const tokenBBefore = await balance(connection, pool.source1);
+
+    const data = await eventOf(await swap(program, payer, pool, 10_000), "SwapEvent");
+
+    assert.isTrue(data.user.equals(payer.publicKey));
+    assert.isTrue(data.sourceMint.equals(pool.mint0));
+    assert.isTrue(data.destinationMint.equals(pool.mint1));
+    assert.strictEqual(data.amountIn.toNumber(), 10_000);
+    assert.strictEqual(
+      data.amountOut.toNumber(),
+      (await balance(connection, pool.source1)) - tokenBBefore
+    );
+    // 25 and 5 basis points of the amount in
+    assert.strictEqual(data.tradeFee.toNumber(), 25);
+    assert.strictEqual(data.ownerFee.toNumber(), 5);
+    // no host fee account was passed
+    assert.strictEqual(data.hostFee.toNumber(), 0);
+    await assertPoolState(data);
+  });
+
+  it("emits DepositEvent", async () => {
+    const tokenABefore = await balance(connection, pool.source0);
+    const tokenBBefore = await balance(connection, pool.source1);
+
+    const data = await eventOf(await depositAll(program, payer, pool, 1_000), "DepositEvent");
+
+    assert.isTrue(data.user.equals(payer.publicKey));
+    assert.strictEqual(
+      data.tokenAAmount.toNumber(),
+      tokenABefore - (await balance(connection, pool.source0))
+    );
+    assert.strictEqual(
+      data.tokenBAmount.toNumber(),
+      tokenBBefore - (await balance(connection, pool.source1))
+    );
+    assert.strictEqual(data.poolTokenAmount.toNumber(), 1_000);
+    await assertPoolState(data);
+  });
+
+  it("emits WithdrawEvent", async () => {
+    const tokenABefore = await balance(connection, pool.source0);
+    const tokenBBefore = await balance(connection, pool.source1);
+
+    const signature = await program.methods
+      .withdrawAll(new BN(1_000), new BN(0), new BN(0))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        depositReceipt: pool.depositReceipt,
+        sourceInfo: pool.destination,
+        vaultTokenA: pool.vault0,
+        vaultTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        destTokenAInfo: pool.source0,
+        destTokenBInfo: pool.source1,
+        feeAccount: pool.feeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+    const data = await eventOf(signature, "WithdrawEvent");
+
+    assert.isTrue(data.user.equals(payer.publicKey));
+    assert.strictEqual(
+      data.tokenAAmount.toNumber(),
+      (await balance(connection, pool.source0)) - tokenABefore
+    );
+    assert.strictEqual(
+      data.tokenBAmount.toNumber(),
+      (await balance(connection, pool.source1)) - tokenBBefore
+    );
+    // the pool charges no withdraw fee
+    assert.strictEqual(data.poolTokenAmount.toNumber(), 1_000);
+    assert.strictEqual(data.withdrawFee.toNumber(), 0);
+    await assertPoolState(data);
+  });
+
+  it("emits SyncEvent", async () => {
+    await transfer(connection, payer, pool.source0, pool.vault0, payer, 1_000);
+    await transfer(connection, payer, pool.source1, pool.vault1, payer, 1_000);
+
+    const signature = await program.methods
+      .sync()
+      .accounts({ amm: pool.amm, vaultTokenA: pool.vault0, vaultTokenB: pool.vault1 })
+      .rpc();
+    const data = await eventOf(signature, "SyncEvent");
+
+    assert.strictEqual(data.reserveA.toNumber(), await balance(connection, pool.vault0));
+    assert.strictEqual(data.reserveB.toNumber(), await balance(connection, pool.vault1));
+    await assertPoolState(data);
+  });
+});
@@ -0,0 +1,145 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, findAddress, fundPayer, createPool, sleep } from "./utils";
+
+describe("farm", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+  let rewardMint: PublicKey;
+  let rewardSource: PublicKey;
+  let farm: PublicKey;
+  let rewardVault: PublicKey;
+  let stakeVault: PublicKey;
+
+  const createFarm = (admin: Keypair) =>
+    program.methods
+      .createFarm(new BN(100))
+      .accounts({
+        amm: pool.amm,
+        admin: admin.publicKey,
+        poolMint: pool.poolMint,
+        rewardMint,
+        farm,
+        rewardVault,
+        stakeVault,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+  const stake = async (amount: number) =>
+    program.methods
+      .stake(new BN(amount))
+      .accounts({
+        farm,
+        userStake: await findAddress(program, "stake", farm, payer.publicKey),
+        owner: payer.publicKey,
+        source: pool.destination,
+        stakeVault,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([payer])
+      .rpc();
+
+  const unstake = async (amount: number) =>
+    program.methods
+      .unstake(new BN(amount))
+      .accounts({
+        farm,
+        userStake: await findAddress(program, "stake", farm, payer.publicKey),
+        owner: payer.publicKey,
+        destination: pool.destination,
+        stakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer);
+    rewardMint = await createMint(connection, payer, payer.publicKey, null, 0);
+    rewardSource = await createAccount(connection, payer, rewardMint, payer.publicKey);
+    await mintTo(connection, payer, rewardMint, rewardSource, payer, 1_000_000);
+    [farm] = await PublicKey.findProgramAddress(
+      [Buffer.from("farm"), pool.amm.toBuffer(), rewardMint.toBuffer()],
+      program.programId
+    );
+    rewardVault = await findAddress(program, "farm_rewards", farm);
+    stakeVault = await findAddress(program, "farm_stake", farm);
+  });
+
+  it("only the admin creates a farm", async () => {
+    const intruder = Keypair.generate();
+    await fundPayer(connection, intruder);
+    await expectError(createFarm(intruder), "ConstraintHasOne");
+  });
+
+  it("pays funded rewards to stakers", async () => {
+    await createFarm(payer);
+    await program.methods
+      .fundRewards(new BN(10_000))
+      .accounts({
+        farm,
+        funder: payer.publicKey,
+        source: rewardSource,
+        rewardVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+
+    const poolTokensBefore = await balance(connection, pool.destination);
+    await stake(10_000);
+    assert.strictEqual(await balance(connection, stakeVault), 10_000);
+    await sleep(2_000);
+
+    const rewardDestination = await createAccount(
+      connection,
+      payer,
+      rewardMint,
+      payer.publicKey,
+      Keypair.generate()
+    );
+    await program.methods
+      .claim()
+      .accounts({
+        farm,
+        userStake: await findAddress(program, "stake", farm, payer.publicKey),
+        owner: payer.publicKey,
+        destination: rewardDestination,
+        rewardVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+    // 100 per second for at least the two seconds slept, capped by the funding
+    const claimed = await balance(connection, rewardDestination);
+    assert.isAtLeast(claimed, 100);
+    assert.isAtMost(claimed, 10_000);
+
+    await unstake(10_000);
+    assert.strictEqual(await balance(connection, pool.destination), poolTokensBefore);
+  });
+
+  it("rejects unstaking more than staked with InsufficientStake", async () => {
+    await stake(1_000);
+    await expectError(unstake(1_001), "InsufficientStake");
+  });
+
+  it("rejects an empty stake with InvalidInput", async () => {
+    await expectError(stake(0), "InvalidInput");
+  });
+});
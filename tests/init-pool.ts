@@ -0,0 +1,110 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, getAccount, getMint } from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  expectError,
+  balance,
+  constantProduct,
+  fundPayer,
+  preparePool,
+  createPool,
+} from "./utils";
+
+// Mirrors `MINIMUM_LIQUIDITY` in curve/calculator.rs
+const MINIMUM_LIQUIDITY = 1_000;
+
+describe("init_pool", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+  });
+
+  describe("minimum liquidity", () => {
+    let pool: Record<string, PublicKey>;
+
+    before(async () => {
+      pool = await createPool(program, payer);
+    });
+
+    it("locks the minimum liquidity in an account of the pool authority", async () => {
+      const locked = await getAccount(connection, pool.lockedLiquidity);
+      assert.strictEqual(Number(locked.amount), MINIMUM_LIQUIDITY);
+      assert.isTrue(locked.owner.equals(pool.poolAuthority));
+      const supply = Number((await getMint(connection, pool.poolMint)).supply);
+      assert.strictEqual(
+        await balance(connection, pool.destination),
+        supply - MINIMUM_LIQUIDITY
+      );
+    });
+
+    it("keeps the locked share in the vaults when every LP withdraws", async () => {
+      await program.methods
+        .withdrawAll(
+          new BN(await balance(connection, pool.destination)),
+          new BN(0),
+          new BN(0)
+        )
+        .accounts({
+          amm: pool.amm,
+          authority: pool.poolAuthority,
+          owner: payer.publicKey,
+          depositReceipt: pool.depositReceipt,
+          sourceInfo: pool.destination,
+          vaultTokenA: pool.vault0,
+          vaultTokenB: pool.vault1,
+          poolMint: pool.poolMint,
+          destTokenAInfo: pool.source0,
+          destTokenBInfo: pool.source1,
+          feeAccount: pool.feeAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([payer])
+        .rpc();
+
+      assert.strictEqual(
+        Number((await getMint(connection, pool.poolMint)).supply),
+        MINIMUM_LIQUIDITY
+      );
+      assert.isAtLeast(await balance(connection, pool.vault0), MINIMUM_LIQUIDITY);
+      assert.isAtLeast(await balance(connection, pool.vault1), MINIMUM_LIQUIDITY);
+    });
+  });
+
+  describe("initial supply", () => {
+    it("mints the geometric mean of the deposits at the pool's decimals", async () => {
+      // sqrt(400_000 * 100_000) = 200_000 at 2 token decimals, scaled to 6
+      const { accounts, initPool } = await preparePool(
+        program,
+        payer,
+        constantProduct,
+        400_000,
+        100_000,
+        6
+      );
+      await initPool();
+      const poolMint = await getMint(connection, accounts.poolMint);
+      assert.strictEqual(poolMint.decimals, 6);
+      assert.strictEqual(Number(poolMint.supply), 2_000_000_000);
+    });
+
+    it("rejects more than 18 pool decimals with InvalidInput", async () => {
+      const { initPool } = await preparePool(
+        program,
+        payer,
+        constantProduct,
+        400_000,
+        100_000,
+        19
+      );
+      await expectError(initPool(), "InvalidInput");
+    });
+  });
+});
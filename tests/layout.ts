@@ -4,7 +4,8 @@ import { TypeDef } from "@project-serum/anchor/dist/cjs/program/namespace/types"
 
 export const CurveType = Object.freeze({
     ConstantProduct : 0,
-    ConstantPrice : 1
+    ConstantPrice : 1,
+    Stable : 2
   })
 
   const SWAP_PROGRAM_OWNER_FEE_ADDRESS =
@@ -0,0 +1,109 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createAccount } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, findAddress, fundPayer, createPool } from "./utils";
+
+describe("liquidity locks", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  const now = () => Math.floor(Date.now() / 1000);
+
+  // Locks `amount` of the payer's pool tokens and returns the lock receipt
+  const lock = async (amount: number, unlockTs: number, vestingEndTs = 0) => {
+    const lockReceipt = Keypair.generate();
+    await program.methods
+      .lockLiquidity(new BN(amount), new BN(unlockTs), new BN(vestingEndTs))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        poolMint: pool.poolMint,
+        source: pool.destination,
+        lockReceipt: lockReceipt.publicKey,
+        escrow: await findAddress(program, "lock_escrow", lockReceipt.publicKey),
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([payer, lockReceipt])
+      .rpc();
+    return lockReceipt.publicKey;
+  };
+
+  const withdrawLocked = async (
+    lockReceipt: PublicKey,
+    owner: Keypair,
+    destination: PublicKey
+  ) =>
+    program.methods
+      .withdrawLocked()
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: owner.publicKey,
+        lockReceipt,
+        escrow: await findAddress(program, "lock_escrow", lockReceipt),
+        destination,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([owner])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer);
+  });
+
+  it("releases pool tokens once unlocked", async () => {
+    const before = await balance(connection, pool.destination);
+    const lockReceipt = await lock(10_000, now() - 60);
+    assert.strictEqual(await balance(connection, pool.destination), before - 10_000);
+
+    await withdrawLocked(lockReceipt, payer, pool.destination);
+    assert.strictEqual(await balance(connection, pool.destination), before);
+  });
+
+  it("rejects a withdrawal before the unlock with LiquidityStillLocked", async () => {
+    const lockReceipt = await lock(10_000, now() + 3_600);
+    await expectError(
+      withdrawLocked(lockReceipt, payer, pool.destination),
+      "LiquidityStillLocked"
+    );
+  });
+
+  it("rejects vesting that ends before the unlock with InvalidLockSchedule", async () => {
+    await expectError(lock(10_000, now() + 3_600, now() + 60), "InvalidLockSchedule");
+  });
+
+  it("lets only the new owner withdraw a transferred lock", async () => {
+    const newOwner = Keypair.generate();
+    await fundPayer(connection, newOwner);
+    const newOwnerTokens = await createAccount(
+      connection,
+      payer,
+      pool.poolMint,
+      newOwner.publicKey
+    );
+    const lockReceipt = await lock(5_000, now() - 60);
+    await program.methods
+      .transferLock(newOwner.publicKey)
+      .accounts({ owner: payer.publicKey, lockReceipt })
+      .signers([payer])
+      .rpc();
+
+    await expectError(
+      withdrawLocked(lockReceipt, payer, pool.destination),
+      "ConstraintHasOne"
+    );
+    await withdrawLocked(lockReceipt, newOwner, newOwnerTokens);
+    assert.strictEqual(await balance(connection, newOwnerTokens), 5_000);
+  });
+});
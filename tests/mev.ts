@@ -0,0 +1,143 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createAccount, mintTo } from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  expectError,
+  balance,
+  findAddress,
+  fundPayer,
+  createPool,
+  depositAll,
+  swap,
+} from "./utils";
+
+describe("mev protection", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  const setMevProtection = (mevBandBps: number, admin = payer) =>
+    program.methods
+      .setMevProtection(new BN(mevBandBps))
+      .accounts({ amm: pool.amm, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+  const withdrawAll = (poolTokenAmount: number) =>
+    program.methods
+      .withdrawAll(new BN(poolTokenAmount), new BN(0), new BN(0))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        depositReceipt: pool.depositReceipt,
+        sourceInfo: pool.destination,
+        vaultTokenA: pool.vault0,
+        vaultTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        destTokenAInfo: pool.source0,
+        destTokenBInfo: pool.source1,
+        feeAccount: pool.feeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer]);
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer);
+  });
+
+  it("only the admin sets the band", async () => {
+    const intruder = Keypair.generate();
+    await fundPayer(connection, intruder);
+    await expectError(setMevProtection(100, intruder), "ConstraintHasOne");
+  });
+
+  it("rejects a swap moving the price past the band with PriceBandExceeded", async () => {
+    await setMevProtection(100);
+    const amm = await program.account.amm.fetch(pool.amm);
+    assert.strictEqual(amm.mevBandBps.toNumber(), 100);
+
+    // 0.2% of the reserves moves the price by about 0.4%
+    await swap(program, payer, pool, 1_000);
+    // 2% moves it by about 4%
+    await expectError(swap(program, payer, pool, 10_000), "PriceBandExceeded");
+  });
+
+  it("rejects a withdrawal in the slot of a deposit with SameSlotWithdrawal", async () => {
+    const depositIx = await program.methods
+      .depositAll(new BN(1_000), new BN(1_000_000), new BN(1_000_000))
+      .accounts({
+        amm: pool.amm,
+        poolAuthority: pool.poolAuthority,
+        sourceAInfo: pool.source0,
+        sourceBInfo: pool.source1,
+        vaultTokenA: pool.vault0,
+        vaultTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        destination: pool.destination,
+        owner: payer.publicKey,
+        depositReceipt: pool.depositReceipt,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .instruction();
+    await expectError(
+      withdrawAll(1_000).preInstructions([depositIx]).rpc(),
+      "SameSlotWithdrawal"
+    );
+
+    // a later slot is fine
+    await depositAll(program, payer, pool, 1_000);
+    const poolTokens = await balance(connection, pool.destination);
+    await withdrawAll(1_000).rpc();
+    assert.strictEqual(await balance(connection, pool.destination), poolTokens - 1_000);
+  });
+
+  it("only blocks the withdrawals of the depositor", async () => {
+    const other = Keypair.generate();
+    await fundPayer(connection, other);
+    const sourceA = await createAccount(connection, payer, pool.mint0, other.publicKey);
+    const sourceB = await createAccount(connection, payer, pool.mint1, other.publicKey);
+    await mintTo(connection, payer, pool.mint0, sourceA, payer, 100_000);
+    await mintTo(connection, payer, pool.mint1, sourceB, payer, 100_000);
+    const destination = await createAccount(connection, payer, pool.poolMint, other.publicKey);
+
+    const depositIx = await program.methods
+      .depositAll(new BN(1_000), new BN(1_000_000), new BN(1_000_000))
+      .accounts({
+        amm: pool.amm,
+        poolAuthority: pool.poolAuthority,
+        sourceAInfo: sourceA,
+        sourceBInfo: sourceB,
+        vaultTokenA: pool.vault0,
+        vaultTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        destination,
+        owner: other.publicKey,
+        depositReceipt: await findAddress(program, "deposit", pool.amm, other.publicKey),
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .instruction();
+    const poolTokens = await balance(connection, pool.destination);
+    await withdrawAll(1_000).preInstructions([depositIx]).signers([payer, other]).rpc();
+    assert.strictEqual(await balance(connection, pool.destination), poolTokens - 1_000);
+    assert.strictEqual(await balance(connection, destination), 1_000);
+  });
+
+  it("turning the band off lifts both checks", async () => {
+    await setMevProtection(0);
+    await swap(program, payer, pool, 10_000);
+    await depositAll(program, payer, pool, 1_000);
+    await withdrawAll(1_000).rpc();
+  });
+});
@@ -0,0 +1,44 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { expectError, fundPayer, createPool } from "./utils";
+
+// Unversioned pools can't be created by this program any more, so their
+// rewrite is covered by the unit tests in state.rs
+describe("migrate_account", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+  });
+
+  it("rejects a pool already on the current layout with UnsupportedAccountVersion", async () => {
+    const pool = await createPool(program, payer);
+    await expectError(
+      program.methods
+        .migrateAccount()
+        .accounts({
+          amm: pool.amm,
+          authority: pool.poolAuthority,
+          vaultTokenA: pool.vault0,
+          vaultTokenB: pool.vault1,
+          poolMint: pool.poolMint,
+          feeAccount: pool.feeAccount,
+          lockedLiquidity: pool.lockedLiquidity,
+          payer: payer.publicKey,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([payer])
+        .rpc(),
+      "UnsupportedAccountVersion"
+    );
+  });
+});
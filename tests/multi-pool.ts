@@ -0,0 +1,204 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo, getMint } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, feesInput, balance, findAddress, fundPayer } from "./utils";
+
+describe("multi-asset pools", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+
+  const TOKEN_COUNT = 3;
+  const INITIAL_AMOUNT = 100_000;
+
+  const multiPool = Keypair.generate();
+  const feeAccount = Keypair.generate();
+  const destination = Keypair.generate();
+  let authority: PublicKey;
+  let poolMint: PublicKey;
+  let lockedLiquidity: PublicKey;
+  const mints: PublicKey[] = [];
+  const vaults: PublicKey[] = [];
+  const sources: PublicKey[] = [];
+
+  const meta = (pubkey: PublicKey, isWritable = true) => ({ pubkey, isWritable, isSigner: false });
+
+  const initMultiPool = (initialAmounts: number[]) =>
+    program.methods
+      .initMultiPool(feesInput, new BN(100), 2, initialAmounts.map((x) => new BN(x)))
+      .accounts({
+        poolAuthority: authority,
+        multiPool: multiPool.publicKey,
+        poolMint,
+        feeAccount: feeAccount.publicKey,
+        destination: destination.publicKey,
+        lockedLiquidity,
+        payer: payer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(
+        initialAmounts.flatMap((_, k) => [meta(mints[k], false), meta(vaults[k]), meta(sources[k])])
+      )
+      .signers([payer, multiPool, feeAccount, destination])
+      .rpc();
+
+  const multiSwap = (i: number, j: number, amountIn: number, minimumAmountOut: number) =>
+    program.methods
+      .multiSwap(new BN(amountIn), new BN(minimumAmountOut))
+      .accounts({
+        multiPool: multiPool.publicKey,
+        authority,
+        owner: payer.publicKey,
+        source: sources[i],
+        destination: sources[j],
+        vaultSource: vaults[i],
+        vaultDestination: vaults[j],
+        poolMint,
+        feeAccount: feeAccount.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+
+  const multiDepositAll = (poolTokenAmount: number, tokenCount = TOKEN_COUNT) =>
+    program.methods
+      .multiDepositAll(new BN(poolTokenAmount), Array(tokenCount).fill(new BN(1_000_000)))
+      .accounts({
+        multiPool: multiPool.publicKey,
+        authority,
+        owner: payer.publicKey,
+        poolMint,
+        destination: destination.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(
+        [...Array(tokenCount).keys()].flatMap((k) => [meta(sources[k]), meta(vaults[k])])
+      )
+      .signers([payer])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    authority = await findAddress(program, "authority", multiPool.publicKey);
+    poolMint = await findAddress(program, "pool_mint", multiPool.publicKey);
+    lockedLiquidity = await findAddress(program, "locked_liquidity", multiPool.publicKey);
+    for (let k = 0; k < TOKEN_COUNT; k++) {
+      const mint = await createMint(connection, payer, payer.publicKey, null, 2);
+      mints.push(mint);
+      vaults.push(await createAccount(connection, payer, mint, authority, Keypair.generate()));
+      const source = await createAccount(connection, payer, mint, payer.publicKey);
+      await mintTo(connection, payer, mint, source, payer, 1_000_000);
+      sources.push(source);
+    }
+  });
+
+  it("rejects a single token with InvalidTokenCount", async () => {
+    await expectError(initMultiPool([INITIAL_AMOUNT]), "InvalidTokenCount");
+  });
+
+  it("initializes a pool over three tokens", async () => {
+    await initMultiPool(Array(TOKEN_COUNT).fill(INITIAL_AMOUNT));
+    for (const vault of vaults) {
+      assert.strictEqual(await balance(connection, vault), INITIAL_AMOUNT);
+    }
+    // the invariant of a balanced pool is the sum of its balances
+    assert.strictEqual(
+      await balance(connection, destination.publicKey),
+      TOKEN_COUNT * INITIAL_AMOUNT - 1_000
+    );
+    const pool = await program.account.multiPool.fetch(multiPool.publicKey);
+    assert.strictEqual(pool.tokenCount, TOKEN_COUNT);
+  });
+
+  it("swaps between any two tokens near the peg", async () => {
+    const before = await balance(connection, sources[2]);
+    await multiSwap(0, 2, 1_000, 990);
+    assert.isAtLeast((await balance(connection, sources[2])) - before, 990);
+    await expectError(multiSwap(1, 2, 1_000, 1_000), "ExceededSlippage");
+  });
+
+  it("deposits and withdraws every token in proportion", async () => {
+    const supply = Number((await getMint(connection, poolMint)).supply);
+    const poolTokenAmount = Math.floor(supply / 10);
+    const poolTokensBefore = await balance(connection, destination.publicKey);
+    await multiDepositAll(poolTokenAmount);
+    assert.strictEqual(
+      (await balance(connection, destination.publicKey)) - poolTokensBefore,
+      poolTokenAmount
+    );
+
+    const vaultsBefore = await Promise.all(vaults.map((vault) => balance(connection, vault)));
+    await program.methods
+      .multiWithdrawAll(new BN(poolTokenAmount), Array(TOKEN_COUNT).fill(new BN(0)))
+      .accounts({
+        multiPool: multiPool.publicKey,
+        authority,
+        owner: payer.publicKey,
+        source: destination.publicKey,
+        poolMint,
+        feeAccount: feeAccount.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(vaults.flatMap((vault, k) => [meta(vault), meta(sources[k])]))
+      .signers([payer])
+      .rpc();
+    assert.strictEqual(await balance(connection, destination.publicKey), poolTokensBefore);
+    for (let k = 0; k < TOKEN_COUNT; k++) {
+      assert.isBelow(await balance(connection, vaults[k]), vaultsBefore[k]);
+    }
+  });
+
+  it("rejects accounts for too few tokens with InvalidTokenCount", async () => {
+    await expectError(multiDepositAll(1_000, TOKEN_COUNT - 1), "InvalidTokenCount");
+  });
+
+  it("deposits and withdraws a single token", async () => {
+    const poolTokensBefore = await balance(connection, destination.publicKey);
+    await program.methods
+      .multiDepositSingle(new BN(10_000), new BN(1))
+      .accounts({
+        multiPool: multiPool.publicKey,
+        authority,
+        owner: payer.publicKey,
+        source: sources[1],
+        vault: vaults[1],
+        poolMint,
+        destination: destination.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+    const minted = (await balance(connection, destination.publicKey)) - poolTokensBefore;
+    // near the peg, a token is worth about a pool token, less the imbalance fee
+    assert.isAbove(minted, 9_900);
+    assert.isAtMost(minted, 10_000);
+
+    const tokenBefore = await balance(connection, sources[1]);
+    await program.methods
+      .multiWithdrawSingle(new BN(minted), new BN(1))
+      .accounts({
+        multiPool: multiPool.publicKey,
+        authority,
+        owner: payer.publicKey,
+        source: destination.publicKey,
+        vault: vaults[1],
+        destination: sources[1],
+        poolMint,
+        feeAccount: feeAccount.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+    // the round trip pays the imbalance fee twice
+    const withdrawn = (await balance(connection, sources[1])) - tokenBefore;
+    assert.isAbove(withdrawn, 9_800);
+    assert.isBelow(withdrawn, 10_000);
+  });
+});
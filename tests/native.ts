@@ -0,0 +1,136 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, NATIVE_MINT, getMint } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, findAddress, fundPayer, createPool } from "./utils";
+
+describe("native SOL", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+  let wsolAccount: PublicKey;
+
+  const swapNative = (
+    amountIn: number,
+    wrapSource: boolean,
+    substitute: Record<string, PublicKey> = {}
+  ) =>
+    program.methods
+      .swapNative(new BN(amountIn), new BN(1), wrapSource)
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        nativeMint: NATIVE_MINT,
+        wsolAccount,
+        userToken: pool.source1,
+        swapTokenA: pool.vault0,
+        swapTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        feeAccount: pool.feeAccount,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        ...substitute,
+      })
+      .signers([payer])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer, true);
+    wsolAccount = await findAddress(program, "wsol", payer.publicKey);
+  });
+
+  it("swaps lamports for token B", async () => {
+    const vaultABefore = await balance(connection, pool.vault0);
+    const tokenBBefore = await balance(connection, pool.source1);
+
+    await swapNative(10_000, true);
+
+    // rounding in the swap can leave a few lamports unswapped with the owner
+    const swapped = (await balance(connection, pool.vault0)) - vaultABefore;
+    assert.isAtMost(swapped, 10_000);
+    assert.isAbove(swapped, 9_900);
+    assert.isAbove(await balance(connection, pool.source1), tokenBBefore);
+    // the temporary wrapped SOL account is closed again
+    assert.isNull(await connection.getAccountInfo(wsolAccount));
+  });
+
+  it("swaps token B for lamports", async () => {
+    const vaultABefore = await balance(connection, pool.vault0);
+    const tokenBBefore = await balance(connection, pool.source1);
+
+    await swapNative(1_000, false);
+
+    assert.isAtMost(tokenBBefore - (await balance(connection, pool.source1)), 1_000);
+    assert.isBelow(await balance(connection, pool.vault0), vaultABefore);
+    assert.isNull(await connection.getAccountInfo(wsolAccount));
+  });
+
+  it("deposits and withdraws both sides with lamports", async () => {
+    const accounts = {
+      amm: pool.amm,
+      authority: pool.poolAuthority,
+      owner: payer.publicKey,
+      depositReceipt: pool.depositReceipt,
+      nativeMint: NATIVE_MINT,
+      wsolAccount,
+      userToken: pool.source1,
+      vaultTokenA: pool.vault0,
+      vaultTokenB: pool.vault1,
+      poolMint: pool.poolMint,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    const supplyBefore = Number((await getMint(connection, pool.poolMint)).supply);
+    const poolTokens = Math.floor(supplyBefore / 100);
+
+    await program.methods
+      .depositAllNative(new BN(poolTokens), new BN(1_000_000), new BN(1_000_000))
+      .accounts({ ...accounts, destination: pool.destination })
+      .signers([payer])
+      .rpc();
+    assert.strictEqual(
+      Number((await getMint(connection, pool.poolMint)).supply),
+      supplyBefore + poolTokens
+    );
+
+    await program.methods
+      .withdrawAllNative(new BN(poolTokens), new BN(0), new BN(0))
+      .accounts({
+        ...accounts,
+        sourceInfo: pool.destination,
+        feeAccount: pool.feeAccount,
+      })
+      .signers([payer])
+      .rpc();
+    assert.strictEqual(
+      Number((await getMint(connection, pool.poolMint)).supply),
+      supplyBefore
+    );
+    assert.isNull(await connection.getAccountInfo(wsolAccount));
+  });
+
+  it("rejects a pool without a wrapped SOL side with ExpectedNativeMint", async () => {
+    const other = await createPool(program, payer);
+    await expectError(
+      swapNative(1_000, true, {
+        amm: other.amm,
+        authority: other.poolAuthority,
+        userToken: other.source1,
+        swapTokenA: other.vault0,
+        swapTokenB: other.vault1,
+        poolMint: other.poolMint,
+        feeAccount: other.feeAccount,
+      }),
+      "ExpectedNativeMint"
+    );
+  });
+});
@@ -0,0 +1,113 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, findAddress, fundPayer, createPool } from "./utils";
+
+describe("limit orders", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  const keeper = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  const now = () => Math.floor(Date.now() / 1000);
+
+  // Places an order selling `amountIn` of token A and returns the order
+  const placeOrder = async (
+    amountIn: number,
+    minimumAmountOut: number,
+    bounty: number,
+    expiryTs = 0
+  ) => {
+    const order = Keypair.generate();
+    await program.methods
+      .placeOrder(new BN(amountIn), new BN(minimumAmountOut), new BN(bounty), new BN(expiryTs))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        sourceMint: pool.mint0,
+        source: pool.source0,
+        destination: pool.source1,
+        order: order.publicKey,
+        escrow: await findAddress(program, "order_escrow", order.publicKey),
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([payer, order])
+      .rpc();
+    return order.publicKey;
+  };
+
+  const executeOrder = async (order: PublicKey) =>
+    program.methods
+      .executeOrder()
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        keeper: keeper.publicKey,
+        owner: payer.publicKey,
+        order,
+        escrow: await findAddress(program, "order_escrow", order),
+        destination: pool.source1,
+        swapTokenA: pool.vault0,
+        swapTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        feeAccount: pool.feeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([keeper])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    await fundPayer(connection, keeper);
+    pool = await createPool(program, payer);
+  });
+
+  it("a keeper fills an order at its price and collects the bounty", async () => {
+    const tokenBBefore = await balance(connection, pool.source1);
+    const order = await placeOrder(10_000, 9_000, 1_000_000);
+    const keeperLamports = await connection.getBalance(keeper.publicKey);
+
+    await executeOrder(order);
+
+    assert.isAtLeast((await balance(connection, pool.source1)) - tokenBBefore, 9_000);
+    // the bounty and the order's rent, less the transaction fee
+    assert.isAbove(await connection.getBalance(keeper.publicKey), keeperLamports + 900_000);
+    assert.isNull(await connection.getAccountInfo(order));
+  });
+
+  it("rejects a fill below the order's price with OrderPriceNotMet", async () => {
+    const tokenABefore = await balance(connection, pool.source0);
+    const order = await placeOrder(10_000, 10_000, 0);
+    await expectError(executeOrder(order), "OrderPriceNotMet");
+
+    // the owner cancels and gets the escrow back
+    await program.methods
+      .cancelOrder()
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        order,
+        escrow: await findAddress(program, "order_escrow", order),
+        refund: pool.source0,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([payer])
+      .rpc();
+    assert.strictEqual(await balance(connection, pool.source0), tokenABefore);
+    assert.isNull(await connection.getAccountInfo(order));
+  });
+
+  it("rejects an order that already expired with OrderExpired", async () => {
+    await expectError(placeOrder(10_000, 1, 0, now() - 60), "OrderExpired");
+  });
+});
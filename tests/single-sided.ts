@@ -0,0 +1,117 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, fundPayer, createPool } from "./utils";
+
+describe("single-sided deposits and withdrawals", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  const withdrawAccounts = () => ({
+    amm: pool.amm,
+    authority: pool.poolAuthority,
+    owner: payer.publicKey,
+    depositReceipt: pool.depositReceipt,
+    source: pool.destination,
+    swapTokenA: pool.vault0,
+    swapTokenB: pool.vault1,
+    poolMint: pool.poolMint,
+    destination: pool.source0,
+    feeAccount: pool.feeAccount,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  const depositAccounts = () => ({
+    amm: pool.amm,
+    authority: pool.poolAuthority,
+    owner: payer.publicKey,
+    depositReceipt: pool.depositReceipt,
+    source: pool.source0,
+    swapTokenA: pool.vault0,
+    swapTokenB: pool.vault1,
+    poolMint: pool.poolMint,
+    destination: pool.destination,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  });
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer);
+  });
+
+  describe("deposit_single_exact_out", () => {
+    it("mints exactly the requested pool tokens", async () => {
+      const poolTokensBefore = await balance(connection, pool.destination);
+      const tokenABefore = await balance(connection, pool.source0);
+
+      await program.methods
+        .depositSingleExactOut(new BN(10_000), new BN(30_000))
+        .accounts(depositAccounts())
+        .signers([payer])
+        .rpc();
+
+      assert.strictEqual(
+        (await balance(connection, pool.destination)) - poolTokensBefore,
+        10_000
+      );
+      const spent = tokenABefore - (await balance(connection, pool.source0));
+      // a 2% share of the pool costs a little over 4% of the A side alone
+      assert.isAbove(spent, 20_000);
+      assert.isBelow(spent, 21_000);
+    });
+
+    it("rejects a maximum below the required amount with ExceededSlippage", async () => {
+      await expectError(
+        program.methods
+          .depositSingleExactOut(new BN(10_000), new BN(1_000))
+          .accounts(depositAccounts())
+          .signers([payer])
+          .rpc(),
+        "ExceededSlippage"
+      );
+    });
+  });
+
+  describe("withdraw_single_exact_in", () => {
+    it("burns exactly the given pool tokens for one token", async () => {
+      const poolTokensBefore = await balance(connection, pool.destination);
+      const tokenABefore = await balance(connection, pool.source0);
+
+      await program.methods
+        .withdrawSingleExactIn(new BN(10_000), new BN(1))
+        .accounts(withdrawAccounts())
+        .signers([payer])
+        .rpc();
+
+      assert.strictEqual(
+        poolTokensBefore - (await balance(connection, pool.destination)),
+        10_000
+      );
+      const received = (await balance(connection, pool.source0)) - tokenABefore;
+      // about a 2% share of the pool, taken from the A side alone, less the
+      // fee for swapping half of it
+      assert.isAbove(received, 19_000);
+      assert.isBelow(received, 21_000);
+    });
+
+    it("rejects a minimum above the withdrawn amount with ExceededSlippage", async () => {
+      await expectError(
+        program.methods
+          .withdrawSingleExactIn(new BN(10_000), new BN(1_000_000))
+          .accounts(withdrawAccounts())
+          .signers([payer])
+          .rpc(),
+        "ExceededSlippage"
+      );
+    });
+  });
+});
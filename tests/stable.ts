@@ -0,0 +1,84 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, getMint } from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  expectError,
+  balance,
+  stable,
+  fundPayer,
+  createPool,
+  prepareMigration,
+} from "./utils";
+
+describe("stable curve", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+  });
+
+  it("rejects an amplification of zero with InvalidCurve", async () => {
+    const pool = await createPool(program, payer);
+    const { migratePool } = await prepareMigration(program, payer, pool, stable(0));
+    await expectError(migratePool(), "InvalidCurve");
+  });
+
+  describe("migrate_pool", () => {
+    let pool: Record<string, PublicKey>;
+    let migrated: Record<string, PublicKey>;
+    let oldSupply: number;
+
+    before(async () => {
+      pool = await createPool(program, payer);
+      oldSupply = Number((await getMint(connection, pool.poolMint)).supply);
+      const migration = await prepareMigration(program, payer, pool, stable(100));
+      await migration.migratePool();
+      await migration.migrateLiquidity();
+      migrated = migration.migrated;
+    });
+
+    it("moves a balanced pool onto the stable curve at the same share value", async () => {
+      const amm = await program.account.amm.fetch(pool.amm);
+      assert.strictEqual(amm.curve.curveType, 2);
+      assert.strictEqual(amm.curve.curveParameters.toNumber(), 100);
+      // a balanced pool is worth the same under both curves, so the supply is
+      // carried over one for one, give or take the rounding of the values
+      const newSupply = Number((await getMint(connection, migrated.poolMint)).supply);
+      assert.approximately(newSupply, oldSupply, 1);
+      assert.strictEqual(Number((await getMint(connection, pool.poolMint)).supply), 0);
+    });
+
+    it("swaps close to one for one", async () => {
+      const tokenBBefore = await balance(connection, pool.source1);
+      await program.methods
+        .swap(new BN(10_000), new BN(0))
+        .accounts({
+          poolAuthority: pool.poolAuthority,
+          amm: pool.amm,
+          vaultSourceInfo: pool.vault0,
+          vaultDestinationInfo: pool.vault1,
+          swapSource: pool.source0,
+          swapDestination: pool.source1,
+          poolMint: migrated.poolMint,
+          feeAccount: migrated.feeAccount,
+          owner: payer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          hostFeeAccount: PublicKey.default,
+        })
+        .signers([payer])
+        .rpc();
+
+      // the constant product curve would pay out about 9_775 after fees
+      const received = (await balance(connection, pool.source1)) - tokenBBefore;
+      assert.isAbove(received, 9_950);
+      assert.isBelow(received, 10_000);
+    });
+  });
+});
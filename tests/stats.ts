@@ -0,0 +1,45 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { assert } from "chai";
+import { balance, fundPayer, createPool, swap } from "./utils";
+
+describe("pool stats", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer);
+  });
+
+  it("counts swaps in both directions", async () => {
+    const tokenABefore = await balance(connection, pool.source0);
+    const tokenBBefore = await balance(connection, pool.source1);
+
+    await swap(program, payer, pool, 10_000);
+    const bOut = (await balance(connection, pool.source1)) - tokenBBefore;
+    await swap(program, payer, pool, 4_000, true);
+    const aOut = (await balance(connection, pool.source0)) - tokenABefore + 10_000;
+
+    const { stats } = await program.account.amm.fetch(pool.amm);
+    // volumes count the source side with fees and the destination side paid out
+    assert.strictEqual(stats.volumeA.toNumber(), 10_000 + aOut);
+    assert.strictEqual(stats.volumeB.toNumber(), bOut + 4_000);
+    // 25 and 5 basis points of each amount in, in its own token
+    assert.strictEqual(stats.tradeFeeA.toNumber(), 25);
+    assert.strictEqual(stats.ownerFeeA.toNumber(), 5);
+    assert.strictEqual(stats.tradeFeeB.toNumber(), 10);
+    assert.strictEqual(stats.ownerFeeB.toNumber(), 2);
+    // no host fee account was passed
+    assert.strictEqual(stats.hostFee.toNumber(), 0);
+    assert.strictEqual(stats.swapCount.toNumber(), 2);
+    assert.isAbove(stats.lastTradeSlot.toNumber(), 0);
+    assert.isAtMost(stats.lastTradeSlot.toNumber(), await connection.getSlot());
+  });
+});
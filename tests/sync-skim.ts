@@ -0,0 +1,88 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createAccount, transfer } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, fundPayer, createPool } from "./utils";
+
+describe("sync and skim", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  const donate = async (amount: number) => {
+    await transfer(connection, payer, pool.source0, pool.vault0, payer, amount);
+    await transfer(connection, payer, pool.source1, pool.vault1, payer, amount);
+  };
+
+  const skim = (recipientTokenA: PublicKey, recipientTokenB: PublicKey) =>
+    program.methods
+      .skim()
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        vaultTokenA: pool.vault0,
+        vaultTokenB: pool.vault1,
+        recipientTokenA,
+        recipientTokenB,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+  const setSkimRecipient = (recipient: PublicKey, admin: Keypair) =>
+    program.methods
+      .setSkimRecipient(recipient)
+      .accounts({ amm: pool.amm, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer);
+  });
+
+  it("sync folds donated tokens into the reserves", async () => {
+    await donate(1_000);
+    await program.methods
+      .sync()
+      .accounts({ amm: pool.amm, vaultTokenA: pool.vault0, vaultTokenB: pool.vault1 })
+      .rpc();
+
+    const amm = await program.account.amm.fetch(pool.amm);
+    assert.strictEqual(amm.reserveA.toNumber(), await balance(connection, pool.vault0));
+    assert.strictEqual(amm.reserveB.toNumber(), await balance(connection, pool.vault1));
+  });
+
+  it("skim sends donated tokens to the recipient the admin set", async () => {
+    const recipient = Keypair.generate();
+    const recipientA = await createAccount(connection, payer, pool.mint0, recipient.publicKey);
+    const recipientB = await createAccount(connection, payer, pool.mint1, recipient.publicKey);
+    await setSkimRecipient(recipient.publicKey, payer);
+
+    await donate(2_000);
+    await skim(recipientA, recipientB);
+
+    assert.strictEqual(await balance(connection, recipientA), 2_000);
+    assert.strictEqual(await balance(connection, recipientB), 2_000);
+    const amm = await program.account.amm.fetch(pool.amm);
+    assert.strictEqual(amm.reserveA.toNumber(), await balance(connection, pool.vault0));
+  });
+
+  it("skim rejects accounts of anyone but the recipient", async () => {
+    await donate(1_000);
+    await expectError(skim(pool.source0, pool.source1), "InvalidOutputOwner");
+  });
+
+  it("only the admin sets the skim recipient", async () => {
+    const intruder = Keypair.generate();
+    await fundPayer(connection, intruder);
+    await expectError(
+      setSkimRecipient(intruder.publicKey, intruder),
+      "ConstraintHasOne"
+    );
+  });
+});
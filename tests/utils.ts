@@ -0,0 +1,325 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN, AnchorError } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Connection, Keypair, PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  createMint,
+  createAccount,
+  mintTo,
+  getAccount,
+  createWrappedNativeAccount,
+  NATIVE_MINT,
+} from "@solana/spl-token";
+import { assert } from "chai";
+
+// Anchor numbers custom errors from 6000, in `SwapError` order
+export const SwapError = Object.freeze({
+  InvalidOutputOwner: 6003,
+  EmptySupply: 6006,
+  InvalidInput: 6009,
+  IncorrectSwapAccount: 6010,
+  IncorrectPoolMint: 6011,
+  ExceededSlippage: 6016,
+  IncorrectFeeAccount: 6019,
+  UnsupportedCurveType: 6025,
+  InvalidCurve: 6026,
+  InsufficientInitialLiquidity: 6028,
+  ExpectedNativeMint: 6029,
+  PoolNotEmpty: 6030,
+  UnsupportedAccountVersion: 6031,
+  DepositCapExceeded: 6032,
+  NotAllowlisted: 6033,
+  InsufficientStake: 6034,
+  InvalidLockSchedule: 6035,
+  LiquidityStillLocked: 6036,
+  OrderExpired: 6037,
+  OrderPriceNotMet: 6038,
+  DcaNotDue: 6039,
+  TwapSlippageExceeded: 6040,
+  InvalidTokenCount: 6041,
+  PriceBandExceeded: 6042,
+  SameSlotWithdrawal: 6043,
+  PoolPaused: 6044,
+  CircuitBreakerTripped: 6045,
+  ShareValueDecreased: 6046,
+  VaultBelowReserves: 6047,
+});
+
+// Anchor's own account constraint errors
+export const ConstraintError = Object.freeze({
+  ConstraintHasOne: 2001,
+});
+
+const ErrorCode = Object.freeze({ ...SwapError, ...ConstraintError });
+
+export async function expectError(
+  promise: Promise<unknown>,
+  code: keyof typeof ErrorCode
+) {
+  try {
+    await promise;
+  } catch (err) {
+    assert.instanceOf(err, AnchorError);
+    const { errorCode } = (err as AnchorError).error;
+    assert.strictEqual(errorCode.code, code);
+    assert.strictEqual(errorCode.number, ErrorCode[code]);
+    return;
+  }
+  assert.fail(`expected ${code}`);
+}
+
+export const feesInput = {
+  tradeFeeNumerator: new BN(25),
+  tradeFeeDenominator: new BN(10000),
+  ownerTradeFeeNumerator: new BN(5),
+  ownerTradeFeeDenominator: new BN(10000),
+  ownerWithdrawFeeNumerator: new BN(0),
+  ownerWithdrawFeeDenominator: new BN(0),
+  hostFeeNumerator: new BN(20),
+  hostFeeDenominator: new BN(100),
+};
+export const constantProduct = { curveType: 0, curveParameters: new BN(0) };
+export const stable = (amp: number) => ({ curveType: 2, curveParameters: new BN(amp) });
+
+export const balance = async (connection: Connection, account: PublicKey) =>
+  Number((await getAccount(connection, account)).amount);
+
+export const findAddress = async (
+  program: Program<FbnxAmm>,
+  seed: string,
+  ...keys: PublicKey[]
+) =>
+  (
+    await PublicKey.findProgramAddress(
+      [Buffer.from(seed), ...keys.map((key) => key.toBuffer())],
+      program.programId
+    )
+  )[0];
+
+export const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+export async function fundPayer(connection: Connection, payer: Keypair) {
+  await connection.confirmTransaction(
+    await connection.requestAirdrop(payer.publicKey, 10 * LAMPORTS_PER_SOL)
+  );
+}
+
+// Creates two fresh mints, funds the payer with 1_000_000 of each and
+// returns the accounts of a pool over them, with its `init_pool` call.
+// With `nativeA`, token A is wrapped SOL instead of a fresh mint.
+export async function preparePool(
+  program: Program<FbnxAmm>,
+  payer: Keypair,
+  curveInput: { curveType: number; curveParameters: BN },
+  tokenAAmount: number,
+  tokenBAmount: number,
+  poolDecimals = 2,
+  nativeA = false
+) {
+  const connection = program.provider.connection;
+  let mintA: PublicKey;
+  let sourceA: PublicKey;
+  if (nativeA) {
+    mintA = NATIVE_MINT;
+    sourceA = await createWrappedNativeAccount(
+      connection,
+      payer,
+      payer.publicKey,
+      1_000_000,
+      Keypair.generate()
+    );
+  } else {
+    mintA = await createMint(connection, payer, payer.publicKey, null, 2);
+    sourceA = await createAccount(connection, payer, mintA, payer.publicKey);
+    await mintTo(connection, payer, mintA, sourceA, payer, 1_000_000);
+  }
+  const mintB = await createMint(connection, payer, payer.publicKey, null, 2);
+  const sourceB = await createAccount(connection, payer, mintB, payer.publicKey);
+  await mintTo(connection, payer, mintB, sourceB, payer, 1_000_000);
+
+  const [amm] = await PublicKey.findProgramAddress(
+    [Buffer.from("amm"), mintA.toBuffer(), mintB.toBuffer()],
+    program.programId
+  );
+  const feeAccount = Keypair.generate();
+  const destination = Keypair.generate();
+  const poolMint = await findAddress(program, "pool_mint", amm);
+  const accounts = {
+    poolAuthority: await findAddress(program, "authority", amm),
+    amm,
+    poolMint,
+    vault0: await findAddress(program, "vault0", amm),
+    vault1: await findAddress(program, "vault1", amm),
+    feeAccount: feeAccount.publicKey,
+    destination: destination.publicKey,
+    lockedLiquidity: await findAddress(program, "locked_liquidity", amm, poolMint),
+    depositReceipt: await findAddress(program, "deposit", amm, payer.publicKey),
+    payer: payer.publicKey,
+    source0: sourceA,
+    source1: sourceB,
+    mint0: mintA,
+    mint1: mintB,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  };
+  const initPool = () =>
+    program.methods
+      .initPool(
+        feesInput,
+        curveInput,
+        poolDecimals,
+        new BN(tokenAAmount),
+        new BN(tokenBAmount),
+        false
+      )
+      .accounts(accounts)
+      .signers([payer, feeAccount, destination])
+      .rpc();
+  return { accounts, initPool };
+}
+
+// Initializes a constant product pool holding 500_000 of each token
+export async function createPool(
+  program: Program<FbnxAmm>,
+  payer: Keypair,
+  nativeA = false
+) {
+  const { accounts, initPool } = await preparePool(
+    program,
+    payer,
+    constantProduct,
+    500_000,
+    500_000,
+    // wrapped SOL has 9 decimals against token B's 2, so the pool needs more
+    // decimals for the initial supply to clear the minimum liquidity
+    nativeA ? 9 : 2,
+    nativeA
+  );
+  await initPool();
+  return accounts;
+}
+
+// Returns the accounts of `pool` after a `migrate_pool` to `curveInput`, with
+// the `migrate_pool` call and the `migrate_liquidity` call converting all of
+// the payer's pool tokens
+export async function prepareMigration(
+  program: Program<FbnxAmm>,
+  payer: Keypair,
+  pool: Record<string, PublicKey>,
+  curveInput: { curveType: number; curveParameters: BN }
+) {
+  const connection = program.provider.connection;
+  const newPoolMint = await findAddress(program, "pool_mint", pool.amm, pool.poolMint);
+  const migration = await findAddress(program, "migration", pool.amm, pool.poolMint);
+  const escrow = await findAddress(program, "migration_escrow", pool.amm, pool.poolMint);
+  const newFeeAccount = Keypair.generate();
+  const newDestination = Keypair.generate();
+  const migrated = {
+    ...pool,
+    poolMint: newPoolMint,
+    lockedLiquidity: await findAddress(program, "locked_liquidity", pool.amm, newPoolMint),
+    feeAccount: newFeeAccount.publicKey,
+    destination: newDestination.publicKey,
+  };
+  const migratePool = (substitute: Record<string, PublicKey> = {}) =>
+    program.methods
+      .migratePool(curveInput)
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        admin: payer.publicKey,
+        poolMint: pool.poolMint,
+        lockedLiquidity: pool.lockedLiquidity,
+        newPoolMint,
+        newLockedLiquidity: migrated.lockedLiquidity,
+        migration,
+        escrow,
+        feeAccount: pool.feeAccount,
+        feeOwner: payer.publicKey,
+        newFeeAccount: newFeeAccount.publicKey,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        ...substitute,
+      })
+      .signers([payer, newFeeAccount])
+      .rpc();
+  const migrateLiquidity = async (substitute: Record<string, PublicKey> = {}) =>
+    program.methods
+      .migrateLiquidity(new BN(await balance(connection, pool.destination)))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        migration,
+        oldPoolMint: pool.poolMint,
+        newPoolMint,
+        escrow,
+        source: pool.destination,
+        destination: newDestination.publicKey,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        ...substitute,
+      })
+      .signers([payer, newDestination])
+      .rpc();
+  return { migrated, migratePool, migrateLiquidity };
+}
+
+// Deposits both tokens of `pool` for `poolTokenAmount` pool tokens
+export const depositAll = (
+  program: Program<FbnxAmm>,
+  payer: Keypair,
+  pool: Record<string, PublicKey>,
+  poolTokenAmount: number
+) =>
+  program.methods
+    .depositAll(new BN(poolTokenAmount), new BN(1_000_000), new BN(1_000_000))
+    .accounts({
+      amm: pool.amm,
+      poolAuthority: pool.poolAuthority,
+      sourceAInfo: pool.source0,
+      sourceBInfo: pool.source1,
+      vaultTokenA: pool.vault0,
+      vaultTokenB: pool.vault1,
+      poolMint: pool.poolMint,
+      destination: pool.destination,
+      owner: payer.publicKey,
+      depositReceipt: pool.depositReceipt,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([payer])
+    .rpc();
+
+// Swaps `amountIn` of token A of `pool` for token B, or B for A with `bToA`
+export const swap = (
+  program: Program<FbnxAmm>,
+  payer: Keypair,
+  pool: Record<string, PublicKey>,
+  amountIn: number,
+  bToA = false
+) =>
+  program.methods
+    .swap(new BN(amountIn), new BN(0))
+    .accounts({
+      poolAuthority: pool.poolAuthority,
+      amm: pool.amm,
+      vaultSourceInfo: bToA ? pool.vault1 : pool.vault0,
+      vaultDestinationInfo: bToA ? pool.vault0 : pool.vault1,
+      swapSource: bToA ? pool.source1 : pool.source0,
+      swapDestination: bToA ? pool.source0 : pool.source1,
+      poolMint: pool.poolMint,
+      feeAccount: pool.feeAccount,
+      owner: payer.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      hostFeeAccount: PublicKey.default,
+    })
+    .signers([payer])
+    .rpc();
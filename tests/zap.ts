@@ -0,0 +1,66 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, BN } from "@project-serum/anchor";
+import { FbnxAmm } from "../target/types/fbnx_amm";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+import { expectError, balance, fundPayer, createPool } from "./utils";
+
+describe("zap_in", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.FbnxAmm as Program<FbnxAmm>;
+  const connection = provider.connection;
+  const payer = Keypair.generate();
+  let pool: Record<string, PublicKey>;
+
+  const zapIn = (amount: number, substitute: Record<string, PublicKey> = {}) =>
+    program.methods
+      .zapIn(new BN(amount), new BN(1))
+      .accounts({
+        amm: pool.amm,
+        authority: pool.poolAuthority,
+        owner: payer.publicKey,
+        depositReceipt: pool.depositReceipt,
+        source: pool.source0,
+        dustDestination: pool.source1,
+        swapTokenA: pool.vault0,
+        swapTokenB: pool.vault1,
+        poolMint: pool.poolMint,
+        feeAccount: pool.feeAccount,
+        destination: pool.destination,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        ...substitute,
+      })
+      .signers([payer])
+      .rpc();
+
+  before(async () => {
+    await fundPayer(connection, payer);
+    pool = await createPool(program, payer);
+  });
+
+  it("deposits a single token for pool tokens", async () => {
+    const sourceBefore = await balance(connection, pool.source0);
+    const poolTokensBefore = await balance(connection, pool.destination);
+    const vaultBBefore = await balance(connection, pool.vault1);
+
+    await zapIn(10_000);
+
+    const spent = sourceBefore - (await balance(connection, pool.source0));
+    assert.isAbove(spent, 0);
+    assert.isAtMost(spent, 10_000);
+    assert.isAbove(await balance(connection, pool.destination), poolTokensBefore);
+    // only the dust of the internal swap leaves the other vault
+    assert.isAtMost(vaultBBefore - (await balance(connection, pool.vault1)), 10);
+  });
+
+  it("rejects a dust destination of the source mint", async () => {
+    await expectError(
+      zapIn(10_000, { dustDestination: pool.source0 }),
+      "IncorrectSwapAccount"
+    );
+  });
+});